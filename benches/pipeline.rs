@@ -0,0 +1,112 @@
+//! `cargo bench --features bench`: each step of the `--bump-mode first` hot path on its
+//! own, then all of them together.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use pda_grinder::pipeline::{self, Hasher, Preimage, Target};
+use solana_pubkey::Pubkey;
+
+const OWNER: Pubkey = solana_pubkey::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Hashes of consecutive seeds, for the steps that start from one
+fn hashes(count: u64) -> Vec<[u8; 32]> {
+    let mut hasher = Hasher::new(1);
+    let mut preimage = Preimage::new(&OWNER);
+    (0..count)
+        .map(|seed| {
+            preimage.set_seed(seed);
+            hasher.hash(core::slice::from_ref(&preimage))[0]
+        })
+        .collect()
+}
+
+fn preimage(c: &mut Criterion) {
+    let mut preimage = Preimage::new(&OWNER);
+    let mut seed = 0_u64;
+    c.bench_function("preimage", |b| {
+        b.iter(|| {
+            seed += 1;
+            preimage.set_seed(black_box(seed));
+            black_box(preimage.bytes());
+        })
+    });
+}
+
+fn sha256(c: &mut Criterion) {
+    let mut hasher = Hasher::new(1);
+    let mut group = c.benchmark_group(format!("sha256 ({})", hasher.backend()));
+
+    let preimages: Vec<Preimage> = (0..hasher.lanes() as u64)
+        .map(|seed| {
+            let mut preimage = Preimage::new(&OWNER);
+            preimage.set_seed(seed);
+            preimage
+        })
+        .collect();
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("one", |b| {
+        b.iter(|| black_box(hasher.hash(black_box(&preimages[..1]))[0]))
+    });
+    group.throughput(Throughput::Elements(preimages.len() as u64));
+    group.bench_function("lanes", |b| {
+        b.iter(|| black_box(hasher.hash(black_box(&preimages))[0]))
+    });
+    group.finish();
+}
+
+fn bs58(c: &mut Criterion) {
+    let hashes = hashes(1024);
+    let mut out = [0; 44];
+    let mut i = 0;
+    c.bench_function("bs58", |b| {
+        b.iter(|| {
+            i = (i + 1) % hashes.len();
+            black_box(pipeline::encode(black_box(&hashes[i]), &mut out))
+        })
+    });
+
+    let mut group = c.benchmark_group("bs58 batch");
+    let mut out = vec![([0; 44], 0); hashes.len()];
+    group.throughput(Throughput::Elements(hashes.len() as u64));
+    group.bench_function("1024", |b| {
+        b.iter(|| pipeline::encode_batch(black_box(&hashes), &mut out))
+    });
+    group.finish();
+}
+
+fn curve(c: &mut Criterion) {
+    let hashes = hashes(1024);
+    let mut i = 0;
+    c.bench_function("curve check", |b| {
+        b.iter(|| {
+            i = (i + 1) % hashes.len();
+            black_box(pipeline::is_off_curve(black_box(&hashes[i])))
+        })
+    });
+
+    let mut group = c.benchmark_group("curve check batch");
+    let mut out = vec![false; hashes.len()];
+    group.throughput(Throughput::Elements(hashes.len() as u64));
+    group.bench_function("1024", |b| {
+        b.iter(|| pipeline::off_curve_batch(black_box(&hashes), &mut out))
+    });
+    group.finish();
+}
+
+fn candidate(c: &mut Criterion) {
+    // Long enough that nothing ever matches, short enough that it isn't ruled out early
+    let target = Target::new("zzzzzzzz");
+    let mut hasher = Hasher::new(1);
+    let mut group = c.benchmark_group("pipeline");
+    let mut seed = 0;
+    group.throughput(Throughput::Elements(1024));
+    group.bench_function("1024 seeds", |b| {
+        b.iter(|| {
+            seed += 1024;
+            black_box(pipeline::grind(&mut hasher, &target, &OWNER, seed, 1024))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, preimage, sha256, bs58, curve, candidate);
+criterion_main!(benches);
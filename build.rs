@@ -0,0 +1,6 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    // Lets the Node module leave N-API's symbols for Node to supply when it loads it
+    #[cfg(feature = "napi")]
+    napi_build::setup();
+}
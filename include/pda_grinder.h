@@ -0,0 +1,51 @@
+/* The C interface of pda-grinder, built with
+ * `cargo rustc --release --lib --features cdylib --crate-type cdylib`. See src/ffi.rs. */
+
+#ifndef PDA_GRINDER_H
+#define PDA_GRINDER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A match. Fields are only ever added at the end. */
+typedef struct PdaMatch {
+    uint8_t pubkey[32];
+    /* The ground seed's bytes, the first `seed_len` of them */
+    uint8_t seed[32];
+    uint8_t seed_len;
+    uint8_t bump;
+    /* Seeds searched across all threads when this one was found */
+    uint64_t iterations;
+    /* Unix seconds */
+    uint64_t found_at;
+} PdaMatch;
+
+typedef struct PdaGrind PdaGrind;
+
+/* Why the last call on this thread that failed did, valid until another call on the
+ * thread fails, or NULL if none has. */
+const char *pda_last_error(void);
+
+/* Starts a grind given the `grind` command's flags, or returns NULL (see
+ * `pda_last_error`) if they don't parse or can't be ground for. Nothing is written to
+ * files unless the flags give an `--output`, `--output-per-run` or `--manifest`, and a
+ * `--template` or `--idl` grind needs a `--keypair-dir` to save keypairs in. */
+PdaGrind *pda_grind_start(size_t argc, const char *const *argv);
+
+/* Takes the next match into `out` and returns 1, or returns 0 if there is none yet,
+ * or -1 once the grind is over and every match has been taken. */
+int pda_grind_poll(PdaGrind *grind, PdaMatch *out);
+
+/* Stops the grind, waits for it and frees it. Unpolled matches are dropped. Returns 0,
+ * or -1 (see `pda_last_error`) if the grind failed partway. */
+int pda_grind_stop(PdaGrind *grind);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
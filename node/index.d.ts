@@ -0,0 +1,32 @@
+export interface GrindOptions {
+  owner: string
+  target?: string
+  threads?: number
+  /** Any other `grind` command flags, e.g. `['--seed-end', '1000000', '--no-file']` */
+  flags?: string[]
+}
+
+export interface Match {
+  pubkey: string
+  /** As printed, e.g. `1234` or `"vault"` */
+  seed: string
+  /** The ground seed's exact bytes */
+  seedBytes: Buffer
+  bump: number
+  /** Seeds searched across all threads when this one was found */
+  iterations: number
+  /** Unix seconds */
+  foundAt: number
+}
+
+/**
+ * Yields each match as the grind finds it, until it stops. Throws if the options can't
+ * be ground for, or if the grind fails partway.
+ */
+export function grind(options: GrindOptions): AsyncGenerator<Match, void, undefined>
+
+/**
+ * The address `owner` gets from `seed` with its canonical bump. Numbers and bigints are
+ * taken as little-endian u64s, and strings as their UTF-8.
+ */
+export function check(owner: string, seed: number | bigint | Buffer | string): string
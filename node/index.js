@@ -0,0 +1,33 @@
+// Grinds through the native module `cargo rustc --release --lib --features napi
+// --crate-type cdylib` builds, copied here as `pda_grinder.node`.
+const native = require('./pda_grinder.node')
+
+// Yields each match as the grind finds it, until it stops (at the end of a range, or
+// see `--duration`). Breaking out of a `for await` loop stops it. Throws if the options
+// can't be ground for, or if the grind fails partway. No signal handlers are installed,
+// so Ctrl-C is Node's as ever.
+async function* grind(options) {
+  const running = native.start(options)
+  // Node waits for its worker threads on the way out, one of which waits on the grind
+  // for the next match, so a `process.exit` (from a SIGINT listener, say) stops it
+  const stopOnExit = () => {
+    try {
+      running.stop()
+    } catch {}
+  }
+  process.once('exit', stopOnExit)
+  try {
+    for (;;) {
+      const found = await running.next()
+      if (found === null) {
+        return
+      }
+      yield found
+    }
+  } finally {
+    process.removeListener('exit', stopOnExit)
+    running.stop()
+  }
+}
+
+module.exports = { grind, check: native.check }
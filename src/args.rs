@@ -0,0 +1,773 @@
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use clap::{Parser, ValueEnum};
+use solana_pubkey::Pubkey;
+
+use crate::{
+    gpu::{Gpu, GpuDevice},
+    idl::parse_idl_seed,
+    seeds::{
+        parse_hex, parse_seed_literal, parse_seed_template, Endianness, HexBytes, SeedCharset,
+        SeedTemplate, SeedType,
+    },
+    sha256::Backend,
+};
+
+/// Most bumps `--lookahead` can try before the curve check
+pub(crate) const MAX_LOOKAHEAD: usize = 16;
+
+// Parsed once, so the size of `GrindArgs` doesn't matter
+#[allow(clippy::large_enum_variant)]
+#[derive(Parser)]
+pub enum Command {
+    Grind(GrindArgs),
+    Check(CheckArgs),
+    /// Print matches saved to a `--db`
+    List(ListArgs),
+    /// Run the grind loop over a fixed number of seeds and report the hashrate
+    Bench(BenchArgs),
+    /// Time short grinds with different settings and write the fastest to `grinder.toml`
+    Tune(TuneArgs),
+    /// List the CPU and GPUs, and what `--threads`, `--hasher` and `--gpu` can use
+    Devices,
+}
+#[derive(Debug, Parser)]
+pub struct GrindArgs {
+    /// Program the PDAs belong to [required unless a `--template` or `--idl` sets it]
+    #[clap(
+        long,
+        env = "PDA_GRINDER_OWNER",
+        value_parser = parse_pubkey,
+        required_unless_present_any = ["template", "idl"],
+        default_value_t = Pubkey::default(),
+        hide_default_value = true
+    )]
+    pub owner: Pubkey,
+
+    /// Base58 prefix to grind for
+    #[clap(long, env = "PDA_GRINDER_TARGET", required_unless_present_any = ["suffix", "regex"])]
+    pub target: Option<String>,
+
+    /// Match addresses that end with this string. Can be combined with `target`,
+    /// in which case both have to match.
+    #[clap(long, env = "PDA_GRINDER_SUFFIX")]
+    pub suffix: Option<String>,
+
+    /// Match `target` at any position in the address instead of only as a prefix
+    #[clap(long, requires = "target")]
+    pub anywhere: bool,
+
+    /// Match addresses against a regex, e.g. `^(So1|Sol)[1-9]`. Much slower than
+    /// `target`/`suffix` since the regex runs on every encoded candidate.
+    #[clap(long, conflicts_with_all = ["target", "suffix"])]
+    pub regex: Option<String>,
+
+    /// Treat case pairs (a/A) and look-alike digits (e/3, i/1, ...) in `target`
+    /// and `suffix` as interchangeable
+    #[clap(long, conflicts_with_all = ["anywhere", "regex"])]
+    pub fuzzy: bool,
+
+    /// Also report addresses matching at least this many leading characters of
+    /// `target`, while still grinding for the full match
+    #[clap(long, requires = "target", conflicts_with_all = ["anywhere", "regex"])]
+    pub min_len: Option<usize>,
+
+    /// Number of threads, or `max` (all cores) / `max-2` (all but two). With `--gpu`, one
+    /// of them feeds the GPU.
+    #[clap(long, env = "PDA_GRINDER_THREADS", default_value = "max", value_parser = parse_threads)]
+    pub threads: u64,
+
+    /// Only print matches (no stats, partial matches or banners), e.g. to pipe them
+    /// into another tool
+    #[clap(short, long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Also print where each thread starts
+    #[clap(short, long)]
+    pub verbose: bool,
+
+    /// How often to print stats, e.g. `10s` or `1m`
+    #[clap(long, default_value = "10s", value_parser = parse_duration)]
+    pub stats_every: Duration,
+
+    /// Also print where the CPU threads' time goes (hashing, base58 encoding, matching and
+    /// curve checks) with the stats, timing a sample of each so the grind hardly slows
+    #[clap(long)]
+    pub profile: bool,
+
+    /// Print the stats lines for a seed range (or `--exhaustive`) in a terminal, rather
+    /// than a progress bar through it
+    #[clap(long)]
+    pub no_progress: bool,
+
+    /// Also write the stats as a JSON object a line, every `--stats-every` and once more
+    /// when the grind stops, for scripts to follow it by: to stderr, or to this file or
+    /// named pipe (which the grind waits to be opened for reading). They are written with
+    /// `--quiet`, `--tui` or a progress bar too.
+    #[clap(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "-")]
+    pub stats_json: Option<PathBuf>,
+
+    /// Show a live dashboard instead of printing stats and matches: the hashrate over
+    /// time, each thread's rate, the matches so far and how long the next should take.
+    /// q stops the grind and p pauses it. Needs a build with `--features tui`.
+    #[clap(long, conflicts_with_all = ["quiet", "verbose"])]
+    pub tui: bool,
+
+    /// How the log (banners, stats, matches and warnings) is written
+    #[clap(long, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Append the log to this file instead of printing it, e.g. for long headless runs
+    #[clap(long, conflicts_with = "tui")]
+    pub log_file: Option<PathBuf>,
+
+    /// Which bumps a match is accepted at
+    #[clap(long, env = "PDA_GRINDER_BUMP_MODE", value_enum, default_value_t = BumpMode::First)]
+    pub bump_mode: BumpMode,
+
+    /// How many seeds `--bump-mode first` lays out and hashes together before checking
+    /// their addresses [default: as many as the hasher takes at once]. On CPUs that curve
+    /// check in batches, also how many seeds `--bump-mode any` takes through their bumps
+    /// together [default: 512]
+    #[clap(long, env = "PDA_GRINDER_BATCH_SIZE", value_parser = clap::value_parser!(u32).range(1..=65536))]
+    pub batch_size: Option<u32>,
+
+    /// How many bumps (from 255 down) `--bump-mode first` matches before curve checking
+    /// them, at most 16. More finds seeds whose canonical bump is a little lower, at the
+    /// cost of hashing and encoding that many candidates per seed.
+    #[clap(long, env = "PDA_GRINDER_LOOKAHEAD", default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=MAX_LOOKAHEAD as i64))]
+    pub lookahead: u8,
+
+    /// Whether `--bump-mode first` matches candidates before curve checking them or the
+    /// other way around
+    #[clap(long, env = "PDA_GRINDER_CHECK_ORDER", value_enum, default_value_t = CheckOrder::Auto)]
+    pub check_order: CheckOrder,
+
+    /// SHA-256 implementation to hash with [default: the fastest one this CPU supports]
+    #[clap(long, env = "PDA_GRINDER_HASHER", value_enum)]
+    pub hasher: Option<Backend>,
+
+    /// Hash and curve check on a GPU as well as on the other `--threads`, with only the
+    /// canonical candidates in the target's raw ranges coming back to be matched. Needs a
+    /// build with `--features cuda` (or `wgpu`, or `vulkan`), a `--target`, `--bump-mode first` and
+    /// integer or `--seed-len` seeds.
+    #[clap(long, env = "PDA_GRINDER_GPU", value_enum)]
+    pub gpu: Option<Gpu>,
+
+    /// Which of the GPUs `--gpu` finds to hash on, from 0, or `all` of them but software
+    /// renderers, each taking seeds of its own. For `--gpu wgpu` and `vulkan` they are counted
+    /// discrete ones first, and asking for one past the last lists them.
+    #[clap(
+        long,
+        env = "PDA_GRINDER_GPU_DEVICE",
+        default_value = "0",
+        value_parser = parse_gpu_device,
+        requires = "gpu"
+    )]
+    pub gpu_device: GpuDevice,
+
+    /// Seeds to a GPU launch [default: as many as take about a quarter of a second, going
+    /// by launches timed at startup, which also pick the threads per block]
+    #[clap(long, env = "PDA_GRINDER_GPU_BATCH", value_parser = parse_u64, requires = "gpu")]
+    pub gpu_batch: Option<u64>,
+
+    /// How many bumps (from 255 down) `--bump-mode any` tries per seed before giving up on
+    /// it [default: all of them]. Each bump is off the curve half of the time whatever the
+    /// owner, so this only saves hashing the seeds it gives up; the summary says how many.
+    #[clap(long, value_parser = clap::value_parser!(u8).range(1..=255))]
+    pub bump_depth: Option<u8>,
+
+    /// Grind over seeds of this many bytes instead of a u64. The first (up to) 8
+    /// bytes hold the little-endian counter, the rest are random for the whole run.
+    #[clap(long, value_parser = clap::value_parser!(u8).range(1..=32))]
+    pub seed_len: Option<u8>,
+
+    /// Only grind seeds made of these characters, so they can be written as string
+    /// literals in client code. Uses `--seed-len` characters (default 8).
+    #[clap(long, value_enum)]
+    pub seed_charset: Option<SeedCharset>,
+
+    /// Integer type of the ground seed
+    #[clap(long, value_enum, conflicts_with_all = ["seed_len", "seed_charset"])]
+    pub seed_type: Option<SeedType>,
+
+    /// Byte order the integer ground seed is written in, i.e. `seed.to_be_bytes()`
+    #[clap(long, value_enum, default_value_t = Endianness::Le, conflicts_with_all = ["seed_len", "seed_charset", "wordlist"])]
+    pub endianness: Endianness,
+
+    /// Grind over seeds made of words from this file (one per line), so the seed is
+    /// easy to remember, e.g. "happyotter"
+    #[clap(long, conflicts_with_all = ["seed_len", "seed_charset", "seed_type"])]
+    pub wordlist: Option<PathBuf>,
+
+    /// Number of `--wordlist` words per seed
+    #[clap(long, default_value_t = 2, requires = "wordlist", value_parser = clap::value_parser!(u32).range(1..=8))]
+    pub words: u32,
+
+    /// Number of digits after the `--wordlist` words, e.g. "happyotter42"
+    #[clap(long, default_value_t = 0, requires = "wordlist", value_parser = clap::value_parser!(u32).range(0..=9))]
+    pub word_digits: u32,
+
+    /// Search every seed exactly once, in order, and stop when done. Only for seed
+    /// spaces that can be counted, e.g. a u32, a short charset seed or a wordlist.
+    #[clap(long)]
+    pub exhaustive: bool,
+
+    /// First seed (decimal or 0x hex) to grind. Like `--seed-end`, this makes threads
+    /// split the range and stop once every seed in it has been tried.
+    #[clap(long, value_parser = parse_u64, conflicts_with = "exhaustive")]
+    pub seed_start: Option<u64>,
+
+    /// Seed (decimal or 0x hex) to stop before
+    #[clap(long, value_parser = parse_u64, conflicts_with = "exhaustive")]
+    pub seed_end: Option<u64>,
+
+    /// Only grind the `i`th of `n` equal slices of the seeds (or of the `--seed-start`
+    /// range), e.g. `0/4` to `3/4` on four machines, and stop once it is searched. Shards
+    /// of the same `--job` start from the same point, so between them they search each
+    /// seed once; ones that share a `--db` warn if another already took any of their seeds.
+    #[clap(long, value_parser = parse_shard)]
+    pub shard: Option<Shard>,
+
+    /// Take chunks of seeds from a Redis (`redis://[:password@]host[:port][/db]`) shared by
+    /// every grind of the same `--job`, and push matches there too, so that workers can
+    /// join and leave the grind at any time. Needs a build with `--features redis`.
+    #[clap(long, env = "PDA_GRINDER_QUEUE", conflicts_with_all = ["shard", "checkpoint", "resume"])]
+    pub queue: Option<String>,
+
+    /// Name of the search the `--shard`s (or `--queue` workers) split up, which picks the
+    /// point they start from [default: one made from the owner, target and seeds]. Grinding
+    /// the same search again under another name searches other seeds.
+    #[clap(long)]
+    pub job: Option<String>,
+
+    /// Only grind integer seeds where `seed & seed_mask == seed_value`, e.g. `0xffff`
+    /// for multiples of 65536 or `0xffff000000000000` for seeds that fit in 48 bits
+    #[clap(
+        long,
+        value_parser = parse_u64,
+        default_value = "0",
+        conflicts_with_all = ["seed_len", "seed_charset", "wordlist", "exhaustive", "seed_start", "seed_end"]
+    )]
+    pub seed_mask: u64,
+
+    /// Value the `--seed-mask` bits must have (decimal or 0x hex)
+    #[clap(long, value_parser = parse_u64, default_value = "0", requires = "seed_mask")]
+    pub seed_value: u64,
+
+    /// Stop after this long, e.g. `90s`, `15m`, `2h` or `1h30m`, and print a summary
+    #[clap(long, value_parser = parse_duration)]
+    pub duration: Option<Duration>,
+
+    /// Stop at this time, given as unix seconds or UTC `2025-01-31T18:00:00Z`
+    #[clap(long, value_parser = parse_deadline, conflicts_with = "duration")]
+    pub deadline: Option<SystemTime>,
+
+    /// File that found seeds are appended to (parent directories are created)
+    #[clap(long, env = "PDA_GRINDER_OUTPUT", default_value = "results.txt")]
+    pub output: PathBuf,
+
+    /// Only print found seeds instead of also writing them to `--output`
+    #[clap(long, conflicts_with = "output")]
+    pub no_file: bool,
+
+    /// Write to a new file named after `--output`, the start time and a random run
+    /// id (e.g. `results-2024-06-01T12-00-00-abcd.txt`) instead of appending to it
+    #[clap(long, conflicts_with = "no_file")]
+    pub output_per_run: bool,
+
+    /// Write out and fsync each found seed right away, instead of buffering them for up
+    /// to a second
+    #[clap(long, conflicts_with = "no_file")]
+    pub fsync: bool,
+
+    /// How found seeds are written to `--output`
+    #[clap(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Also print the code that derives each match, to paste into a program or client:
+    /// `rust` for an Anchor `seeds = [...], bump = <bump>` constraint and the
+    /// `Pubkey::create_program_address` call that gives the same address, `ts` for the
+    /// `PublicKey.findProgramAddressSync` call that does (`--emit rust,ts` for both)
+    #[clap(long, value_enum, value_name = "LANGUAGE", value_delimiter = ',')]
+    pub emit: Vec<Emit>,
+
+    /// Also insert found seeds into this SQLite database (created if needed), which
+    /// any number of grinds can share
+    #[clap(long)]
+    pub db: Option<PathBuf>,
+
+    /// Also POST each match to this URL as JSON, with the pubkey, seed, seed_hex, bump,
+    /// owner, timestamp, this machine's hostname and its hashrate
+    #[clap(long, env = "PDA_GRINDER_WEBHOOK")]
+    pub webhook: Option<String>,
+
+    /// Solana JSON-RPC endpoint `--check-unused` asks, e.g. `https://api.devnet.solana.com`
+    #[clap(long, env = "PDA_GRINDER_RPC")]
+    pub rpc: Option<String>,
+
+    /// Look each match up with `getAccountInfo` on the `--rpc` endpoint before saving it,
+    /// and leave out (with a warning) the ones whose address already holds an account.
+    /// One that can't be looked up is kept.
+    #[clap(long, requires = "rpc")]
+    pub check_unused: bool,
+
+    /// JSON file describing the run (arguments, seed offset, first seed), updated with
+    /// totals and where a range got to when the grind stops
+    #[clap(long, default_value = "run.json")]
+    pub manifest: PathBuf,
+
+    /// Don't write a `--manifest`
+    #[clap(long, conflicts_with = "manifest")]
+    pub no_manifest: bool,
+
+    /// Keep a file of where the grind got to (its random start and the chunks of seeds
+    /// the threads are partway through), written every `--checkpoint-every` and when it
+    /// stops, for `--resume`
+    #[clap(long)]
+    pub checkpoint: Option<PathBuf>,
+
+    /// How often to write the `--checkpoint`
+    #[clap(long, default_value = "1m", value_parser = parse_duration)]
+    pub checkpoint_every: Duration,
+
+    /// Carry on from a `--checkpoint` (and keep it up to date, unless `--checkpoint` names
+    /// another file) rather than starting from new random seeds. The seeds and what they
+    /// are ground for have to be the same as for the grind that wrote it.
+    #[clap(long)]
+    pub resume: Option<PathBuf>,
+
+    /// S3-compatible bucket, with any prefix, to upload the `--checkpoint` and `--output`
+    /// to when the grind stops (on a spot instance's SIGTERM, say), and to download them
+    /// from and resume when it starts, e.g. `https://s3.us-east-1.amazonaws.com/bucket/vault`
+    /// (the bucket goes in the path). Requests are signed with `AWS_ACCESS_KEY_ID`,
+    /// `AWS_SECRET_ACCESS_KEY` and `AWS_REGION` when they are set.
+    #[clap(
+        long,
+        env = "PDA_GRINDER_SYNC_URL",
+        requires = "checkpoint",
+        conflicts_with_all = ["resume", "no_file", "output_per_run"]
+    )]
+    pub sync_url: Option<String>,
+
+    /// Fixed seed that comes before the ground seed, i.e. `[b"vault", ground]`.
+    /// Taken as UTF-8 unless it starts with `0x`, in which case it is hex.
+    #[clap(long, value_parser = parse_seed_literal)]
+    pub seed_prefix: Option<HexBytes>,
+
+    /// Full seed list, e.g. `"vault",<pubkey:KEY>,<grind:u64>`, made of `"string"`,
+    /// `0x<hex>` and `<pubkey:KEY>` fixed seeds around exactly one `<grind>` seed
+    #[clap(long, conflicts_with = "seed_prefix", value_parser = parse_seed_template)]
+    pub seeds: Option<SeedTemplate>,
+
+    /// Grind for an account a well-known program derives, with its seeds and owner built
+    /// in (in place of `--owner`), over new keypairs for the key in them that is ground
+    /// (e.g. a mint). Each match's keypair is saved to `--keypair-dir`.
+    #[clap(
+        long,
+        value_enum,
+        conflicts_with_all = [
+            "seeds", "seed_prefix", "seed_len", "seed_charset", "seed_type",
+            "wordlist", "exhaustive", "seed_start", "seed_end", "shard", "queue", "checkpoint",
+            "resume", "seed_mask", "gpu",
+        ]
+    )]
+    pub template: Option<Template>,
+
+    /// Wallet whose `--template ata` token accounts to grind mints for
+    #[clap(long, value_parser = parse_pubkey, requires = "template")]
+    pub wallet: Option<Pubkey>,
+
+    /// Mint whose `--template ata` token accounts to grind wallets for
+    #[clap(long, value_parser = parse_pubkey, requires = "template", conflicts_with = "wallet")]
+    pub mint: Option<Pubkey>,
+
+    /// Grind mint keypairs, e.g. for the `--wallet`'s token account of each to match
+    #[clap(long, requires = "template")]
+    pub mint_grind: bool,
+
+    /// Grind wallet keypairs, for each one's token account of the `--mint` to match
+    #[clap(long, requires = "mint", conflicts_with = "mint_grind")]
+    pub wallet_grind: bool,
+
+    /// Token program of `--template ata`'s token accounts, e.g. Token-2022's
+    /// `TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb` [default: the SPL token program]
+    #[clap(long, value_parser = parse_pubkey, requires = "template")]
+    pub token_program: Option<Pubkey>,
+
+    /// Anchor IDL (e.g. the `target/idl/<program>.json` that `anchor build` writes) to
+    /// take the seeds and program of `--account` from, in place of `--seeds` and `--owner`
+    #[clap(
+        long,
+        requires = "account",
+        conflicts_with_all = ["template", "seeds", "seed_prefix", "seed_type", "wordlist"]
+    )]
+    pub idl: Option<PathBuf>,
+
+    /// Account of one of the `--idl`'s instructions whose PDA to grind, e.g. `vault` (or
+    /// `Vault`)
+    #[clap(long, requires = "idl")]
+    pub account: Option<String>,
+
+    /// Value of one of the `--account`'s seeds that comes from another account or an
+    /// instruction argument, as `PATH=VALUE` with its path in the IDL, e.g.
+    /// `authority=<KEY>` or `params.id=7`. Can be repeated.
+    #[clap(long, requires = "idl", value_parser = parse_idl_seed)]
+    pub idl_seed: Vec<(String, String)>,
+
+    /// Path of the `--account`'s seed to grind [default: the one `--idl-seed` gives no value]
+    #[clap(long, requires = "idl")]
+    pub idl_grind: Option<String>,
+
+    /// Directory a grind of keypairs (for a `--template`, or an `--idl` seed that is a key)
+    /// saves each match's keypair in, as `<pubkey>.json` in the format `solana-keygen` writes
+    #[clap(long, default_value = ".")]
+    pub keypair_dir: PathBuf,
+}
+
+/// The slice of the seeds a `--shard` grinds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    pub index: u64,
+    pub count: u64,
+}
+
+impl Shard {
+    /// The part of `len` seeds this shard takes, as the first and one past the last
+    pub(crate) fn slice(self, len: u128) -> (u128, u128) {
+        let bound = |index: u64| len * index as u128 / self.count as u128;
+        (bound(self.index), bound(self.index + 1))
+    }
+
+    /// Whether the two shards have any seeds in common
+    pub(crate) fn overlaps(self, other: Shard) -> bool {
+        let (index, count) = (self.index as u128, self.count as u128);
+        let (other_index, other_count) = (other.index as u128, other.count as u128);
+        index * other_count < (other_index + 1) * count
+            && other_index * count < (index + 1) * other_count
+    }
+}
+
+impl std::fmt::Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum BumpMode {
+    /// Only bump 255, which is canonical for about half of all seeds. Candidates are
+    /// matched before the (expensive) curve check, which makes this the fastest.
+    #[value(alias = "canonical")]
+    First,
+    /// Whichever bump is canonical for the seed, curve checking every candidate
+    Any,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CheckOrder {
+    /// Match every candidate and only curve check the ones that match, which is
+    /// cheapest for rare targets
+    HashFirst,
+    /// Curve check candidates and only match the first one off the curve, which is
+    /// cheapest for targets (or regexes) that match often with a `--lookahead` above 1
+    CurveFirst,
+    /// Start with `hash-first`, and switch whenever the share of candidates that match
+    /// says the other order is cheaper
+    Auto,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// `<pubkey>: <seed> hex <seed bytes> bump <bump> owner <owner>` lines
+    Text,
+    /// One JSON object per line, with the pubkey, seed, seed_hex, bump, owner and unix
+    /// timestamp
+    Jsonl,
+    /// Spreadsheet friendly, with a header row of
+    /// `pubkey,seed,seed_hex,bump,owner,found_at,iterations`
+    Csv,
+}
+
+/// Languages `--emit` can print each match's derivation in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Emit {
+    /// An Anchor account constraint and a `Pubkey::create_program_address` call
+    Rust,
+    /// A `PublicKey.findProgramAddressSync` call, for `@solana/web3.js` and `bn.js`
+    Ts,
+}
+
+/// Accounts `--template` can grind for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Template {
+    /// An associated token account, from `[wallet, token program, mint]`, grinding the
+    /// mint (`--wallet <KEY> --mint-grind`) or the wallet (`--mint <KEY> --wallet-grind`)
+    Ata,
+    /// A mint's Metaplex token metadata account, from
+    /// `["metadata", metadata program, mint]`, grinding the mint
+    MetaplexMetadata,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Plain lines, warnings and errors on stderr
+    Text,
+    /// One JSON object per line, with the time, level, message, fields (the seed and
+    /// address of a match, the hashrate of a stats line, ...) and the thread it came from
+    Json,
+    /// Timestamped, with each event's fields and thread on lines of their own
+    Pretty,
+}
+
+#[derive(Debug, Parser)]
+pub struct CheckArgs {
+    #[clap(long, env = "PDA_GRINDER_OWNER", value_parser = parse_pubkey)]
+    pub owner: Pubkey,
+
+    #[clap(long, required_unless_present_any = ["seed_hex", "seed_str"])]
+    pub seed: Option<u128>,
+
+    /// Integer type `seed` is written as
+    #[clap(long, value_enum)]
+    pub seed_type: Option<SeedType>,
+
+    /// Byte order `seed` is written in
+    #[clap(long, value_enum, default_value_t = Endianness::Le)]
+    pub endianness: Endianness,
+
+    /// Seed bytes as hex, for seeds found with `--seed-len`
+    #[clap(long, conflicts_with = "seed", value_parser = parse_hex)]
+    pub seed_hex: Option<HexBytes>,
+
+    /// Seed as a string, for seeds found with `--seed-charset` or `--wordlist`
+    #[clap(long, conflicts_with_all = ["seed", "seed_hex"])]
+    pub seed_str: Option<String>,
+
+    /// Same as for `grind`
+    #[clap(long, value_parser = parse_seed_literal)]
+    pub seed_prefix: Option<HexBytes>,
+
+    /// Same as for `grind`
+    #[clap(long, conflicts_with = "seed_prefix", value_parser = parse_seed_template)]
+    pub seeds: Option<SeedTemplate>,
+}
+
+#[derive(Debug, Parser)]
+pub struct ListArgs {
+    /// Database that `grind --db` inserted matches into
+    #[clap(long)]
+    pub db: PathBuf,
+
+    /// Only list matches for this program
+    #[clap(long, value_parser = parse_pubkey)]
+    pub owner: Option<Pubkey>,
+
+    /// Only list addresses that start with this string
+    #[clap(long)]
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+pub struct BenchArgs {
+    /// Seeds to grind for each thread (decimal or 0x hex), shared out between them as
+    /// they go
+    #[clap(long, default_value = "10000000", value_parser = parse_u64)]
+    pub iterations: u64,
+
+    /// Same as for `grind`
+    #[clap(long, env = "PDA_GRINDER_THREADS", default_value = "max", value_parser = parse_threads)]
+    pub threads: u64,
+
+    /// Same as for `grind`
+    #[clap(long, value_enum, default_value_t = BumpMode::First)]
+    pub bump_mode: BumpMode,
+
+    /// Same as for `grind`
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..=65536))]
+    pub batch_size: Option<u32>,
+
+    /// Same as for `grind`
+    #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=MAX_LOOKAHEAD as i64))]
+    pub lookahead: u8,
+
+    /// Same as for `grind`
+    #[clap(long, value_enum, default_value_t = CheckOrder::Auto)]
+    pub check_order: CheckOrder,
+
+    /// Same as for `grind`
+    #[clap(long, value_parser = clap::value_parser!(u8).range(1..=255))]
+    pub bump_depth: Option<u8>,
+
+    /// Same as for `grind`
+    #[clap(long, value_enum)]
+    pub hasher: Option<Backend>,
+}
+
+#[derive(Debug, Parser)]
+pub struct TuneArgs {
+    /// How long to grind with each setting, e.g. `3s` or `1m`
+    #[clap(long, default_value = "3s", value_parser = parse_duration)]
+    pub trial: Duration,
+
+    /// Same as for `grind`
+    #[clap(long, env = "PDA_GRINDER_THREADS", default_value = "max", value_parser = parse_threads)]
+    pub threads: u64,
+
+    /// Which `grind --bump-mode` to tune for
+    #[clap(long, env = "PDA_GRINDER_BUMP_MODE", value_enum, default_value_t = BumpMode::First)]
+    pub bump_mode: BumpMode,
+
+    /// Same as for `grind`, since the best `--check-order` depends on how often it matches
+    /// [default: one that never matches]
+    #[clap(long, env = "PDA_GRINDER_TARGET")]
+    pub target: Option<String>,
+
+    /// Same as for `grind`
+    #[clap(long, env = "PDA_GRINDER_SUFFIX")]
+    pub suffix: Option<String>,
+
+    /// Config file to write the fastest settings to, keeping whatever else it sets
+    #[clap(long, env = "PDA_GRINDER_CONFIG", default_value = "grinder.toml")]
+    pub config: PathBuf,
+
+    /// Only print the fastest settings
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+/// A `--threads` count: a number, `max` for every core, or `max-N` for all but `N` of
+/// them (at least one)
+fn parse_threads(s: &str) -> Result<u64, String> {
+    let max = std::thread::available_parallelism().map_or(1, |n| n.get() as u64);
+    let threads = match s.strip_prefix("max") {
+        Some("") => max,
+        Some(rest) => {
+            let fewer: u64 = rest
+                .strip_prefix('-')
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| format!("expected a number, max or max-N, got {s:?}"))?;
+            max.saturating_sub(fewer).max(1)
+        }
+        None => s
+            .parse()
+            .map_err(|_| format!("expected a number, max or max-N, got {s:?}"))?,
+    };
+    if threads == 0 {
+        return Err("need at least one thread".to_string());
+    }
+    Ok(threads)
+}
+
+fn parse_gpu_device(s: &str) -> Result<GpuDevice, String> {
+    match s {
+        "all" => Ok(GpuDevice::All),
+        _ => s
+            .parse()
+            .map(GpuDevice::Index)
+            .map_err(|_| format!("expected a number or all, got {s:?}")),
+    }
+}
+
+fn parse_shard(s: &str) -> Result<Shard, String> {
+    let expected = || format!("expected i/n with i from 0 to n - 1, e.g. 0/4, got {s:?}");
+    let (index, count) = s.split_once('/').ok_or_else(expected)?;
+    let (index, count) = (
+        index.parse().map_err(|_| expected())?,
+        count.parse().map_err(|_| expected())?,
+    );
+    if index >= count {
+        return Err(expected());
+    }
+    Ok(Shard { index, count })
+}
+
+fn parse_u64(s: &str) -> Result<u64, String> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|e| e.to_string())
+}
+
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let mut secs = 0;
+    let mut number = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return Err(format!("unknown unit {c:?}, expected s, m, h or d")),
+        };
+        let n: u64 = number
+            .parse()
+            .map_err(|_| format!("expected a number before {c:?}"))?;
+        secs = n
+            .checked_mul(unit)
+            .and_then(|n| n.checked_add(secs))
+            .ok_or_else(|| format!("duration {s:?} is too long"))?;
+        number.clear();
+    }
+    if !number.is_empty() || secs == 0 {
+        return Err("expected a duration like 90s, 15m, 2h or 1h30m".to_string());
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+fn parse_deadline(s: &str) -> Result<SystemTime, String> {
+    let secs = match s.parse::<u64>() {
+        Ok(secs) => secs,
+        Err(_) => parse_utc(s).ok_or_else(|| {
+            format!("expected unix seconds or a UTC time like 2025-01-31T18:00:00Z, got {s:?}")
+        })?,
+    };
+    Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Seconds since the unix epoch for a `YYYY-MM-DDTHH:MM:SSZ` time
+fn parse_utc(s: &str) -> Option<u64> {
+    let (date, time) = s.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<u64>);
+    let (y, m, d) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, ':').map(str::parse::<u64>);
+    let (h, min, sec) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    let leap = y % 4 == 0 && (y % 100 != 0 || y % 400 == 0);
+    let month_len = match m {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if y < 1970
+        || !(1..=12).contains(&m)
+        || !(1..=month_len).contains(&d)
+        || h > 23
+        || min > 59
+        || sec > 59
+    {
+        return None;
+    }
+
+    // Days since the epoch, counting years from March so leap days come last
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y / 400;
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    Some(days * 24 * 60 * 60 + h * 60 * 60 + min * 60 + sec)
+}
+
+pub(crate) fn parse_pubkey(s: &str) -> Result<Pubkey, String> {
+    Pubkey::from_str(s).map_err(|e| e.to_string())
+}
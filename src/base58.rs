@@ -0,0 +1,225 @@
+//! Batched base58 encoding of candidate addresses, for matchers that have to see every
+//! candidate encoded (`--regex`, `--anywhere`, ...) rather than ruling most of them out
+//! on the raw hash.
+//!
+//! This is five8's conversion, the one `five8::encode_32` does one at a time: the hash
+//! is rewritten in base `58^5` by way of a table of what each of its limbs is worth in
+//! that base, and each of those digits is then split into five base58 ones. With 16 bit
+//! limbs every sum stays below 2^53, so on CPUs with AVX2 four hashes go through it at
+//! once in the lanes of double precision vectors, where all of it is exact.
+
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+/// Hashes encoded at once with AVX2
+pub(crate) const LANES: usize = 4;
+
+/// An encoded address and its length, as `five8::encode_32` leaves them
+pub(crate) type Encoded = ([u8; 44], u8);
+
+/// `58^5`
+#[cfg(target_arch = "x86_64")]
+const R: u64 = 656356768;
+
+/// Base `58^5` digits in a 256 bit number
+#[cfg(target_arch = "x86_64")]
+const DIGITS: usize = 9;
+
+/// `2^(16 (15 - k))` in base `58^5`, most significant digit first: what the `k`th 16
+/// bit limb of a hash is worth
+#[cfg(target_arch = "x86_64")]
+const TABLE: [[f64; DIGITS]; 16] = {
+    let mut table = [[0.0; DIGITS]; 16];
+    let mut power = [0_u64; DIGITS];
+    power[DIGITS - 1] = 1;
+    let mut k = 16;
+    while k > 0 {
+        k -= 1;
+        let mut j = 0;
+        while j < DIGITS {
+            table[k][j] = power[j] as f64;
+            j += 1;
+        }
+        let mut carry = 0;
+        let mut j = DIGITS;
+        while j > 0 {
+            j -= 1;
+            let wide = power[j] * 65536 + carry;
+            power[j] = wide % R;
+            carry = wide / R;
+        }
+    }
+    table
+};
+
+/// Whether this CPU encodes `LANES` hashes at once, rather than one at a time
+pub(crate) fn batched() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma")
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+/// `five8::encode_32` for each of `hashes`, `LANES` at a time if `batched()`
+pub(crate) fn encode_batch(hashes: &[[u8; 32]], out: &mut [Encoded]) {
+    let out = &mut out[..hashes.len()];
+    #[cfg(target_arch = "x86_64")]
+    if batched() {
+        let mut groups = hashes.chunks_exact(LANES);
+        let mut outs = out.chunks_exact_mut(LANES);
+        for (group, out) in (&mut groups).zip(&mut outs) {
+            unsafe { encode_avx2(group.try_into().unwrap(), out) };
+        }
+        for (hash, out) in groups.remainder().iter().zip(outs.into_remainder()) {
+            out.1 = five8::encode_32(hash, &mut out.0);
+        }
+        return;
+    }
+    for (hash, out) in hashes.iter().zip(out) {
+        out.1 = five8::encode_32(hash, &mut out.0);
+    }
+}
+
+/// Each lane's `x / d` and `x % d`, for whole `x` below 2^50 and `d` below 2^30. With
+/// the reciprocal rounded up, the product never falls short of a whole quotient, and
+/// it doesn't come near enough to the next one to be rounded up to it.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+#[inline]
+fn div_rem(x: __m256d, d: f64) -> (__m256d, __m256d) {
+    let q = _mm256_floor_pd(_mm256_mul_pd(x, _mm256_set1_pd((1.0 / d).next_up())));
+    (q, _mm256_fnmadd_pd(q, _mm256_set1_pd(d), x))
+}
+
+/// The base58 character for each of the digit bytes `d`: the digit past `'1'`, moved
+/// along for each stretch of ASCII the alphabet leaves out
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+#[inline]
+fn to_chars(d: __m256i) -> __m256i {
+    let mut c = _mm256_add_epi8(d, _mm256_set1_epi8(b'1' as i8));
+    // ':'..='@' before 'A', 'I', 'O', '['..='`' before 'a', and 'l'
+    for (after, gap) in [(8, 7), (16, 1), (21, 1), (32, 6), (43, 1)] {
+        let past = _mm256_cmpgt_epi8(d, _mm256_set1_epi8(after));
+        c = _mm256_add_epi8(c, _mm256_and_si256(past, _mm256_set1_epi8(gap)));
+    }
+    c
+}
+
+/// Transposes the 32 bit words of four rows, in each 128 bit half on its own
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+#[inline]
+fn transpose(rows: [__m256i; 4]) -> [__m256i; 4] {
+    let [a, b, c, d] = rows;
+    let (ab_lo, ab_hi) = (_mm256_unpacklo_epi32(a, b), _mm256_unpackhi_epi32(a, b));
+    let (cd_lo, cd_hi) = (_mm256_unpacklo_epi32(c, d), _mm256_unpackhi_epi32(c, d));
+    [
+        _mm256_unpacklo_epi64(ab_lo, cd_lo),
+        _mm256_unpackhi_epi64(ab_lo, cd_lo),
+        _mm256_unpacklo_epi64(ab_hi, cd_hi),
+        _mm256_unpackhi_epi64(ab_hi, cd_hi),
+    ]
+}
+
+/// `encode_batch` for four hashes at once
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+fn encode_avx2(hashes: &[[u8; 32]; LANES], out: &mut [Encoded]) {
+    // Each hash's big endian 16 bit limbs, widened to 32 bits, eight to a register
+    let swap = _mm256_setr_epi8(
+        1, 0, 3, 2, 5, 4, 7, 6, 9, 8, 11, 10, 13, 12, 15, 14, 1, 0, 3, 2, 5, 4, 7, 6, 9, 8, 11, 10,
+        13, 12, 15, 14,
+    );
+    let rows: [[__m256i; 2]; LANES] = core::array::from_fn(|lane| {
+        let bytes = unsafe { _mm256_loadu_si256(hashes[lane].as_ptr().cast()) };
+        let bytes = _mm256_shuffle_epi8(bytes, swap);
+        [
+            _mm256_cvtepu16_epi32(_mm256_castsi256_si128(bytes)),
+            _mm256_cvtepu16_epi32(_mm256_extracti128_si256::<1>(bytes)),
+        ]
+    });
+    // Turned around to one register per limb, with a lane per hash
+    let mut limbs = [_mm256_setzero_pd(); 16];
+    for half in 0..2 {
+        let columns = transpose(core::array::from_fn(|lane| rows[lane][half]));
+        for (i, column) in columns.into_iter().enumerate() {
+            limbs[8 * half + i] = _mm256_cvtepi32_pd(_mm256_castsi256_si128(column));
+            limbs[8 * half + 4 + i] = _mm256_cvtepi32_pd(_mm256_extracti128_si256::<1>(column));
+        }
+    }
+
+    // Every limb's worth in base 58^5 added up: 16 products below 2^46 each
+    let mut x = [_mm256_setzero_pd(); DIGITS];
+    for (limb, worth) in limbs.iter().zip(&TABLE) {
+        for (x, &worth) in x.iter_mut().zip(worth) {
+            if worth != 0.0 {
+                *x = _mm256_fmadd_pd(*limb, _mm256_set1_pd(worth), *x);
+            }
+        }
+    }
+    // Then carried so that each digit is below 58^5
+    for j in (1..DIGITS).rev() {
+        let (carry, digit) = div_rem(x[j], R as f64);
+        x[j] = digit;
+        x[j - 1] = _mm256_add_pd(x[j - 1], carry);
+    }
+
+    // Five base58 digits out of each, most significant first (and a few zeros to pad
+    // them out to a multiple of 16)
+    let mut digits = [_mm256_setzero_pd(); 48];
+    for (j, &x) in x.iter().enumerate() {
+        let mut x = x;
+        for i in (1..5).rev() {
+            let (rest, digit) = div_rem(x, 58.0);
+            digits[5 * j + i] = digit;
+            x = rest;
+        }
+        digits[5 * j] = x;
+    }
+    // Four at a time as the bytes of a 32 bit word, first digit lowest
+    let words: [__m128i; 12] = core::array::from_fn(|n| {
+        let [d0, d1, d2, d3] = core::array::from_fn(|i| digits[4 * n + i]);
+        let word = _mm256_fmadd_pd(d3, _mm256_set1_pd(256.0), d2);
+        let word = _mm256_fmadd_pd(word, _mm256_set1_pd(256.0), d1);
+        let word = _mm256_fmadd_pd(word, _mm256_set1_pd(256.0), d0);
+        _mm256_cvtpd_epi32(word)
+    });
+    // Turned around so that each of the three runs of 16 characters has a row per lane
+    // (two runs to a register, one in each half), then written out
+    let mut text = [[0_u8; 64]; LANES];
+    for (low, high) in [(0, 1), (2, 2)] {
+        let rows =
+            core::array::from_fn(|i| _mm256_set_m128i(words[4 * high + i], words[4 * low + i]));
+        for (text, row) in text.iter_mut().zip(transpose(rows)) {
+            let row = to_chars(row);
+            unsafe {
+                _mm_storeu_si128(
+                    text[16 * low..].as_mut_ptr().cast(),
+                    _mm256_castsi256_si128(row),
+                );
+                _mm_storeu_si128(
+                    text[16 * high..].as_mut_ptr().cast(),
+                    _mm256_extracti128_si256::<1>(row),
+                );
+            }
+        }
+    }
+
+    // Leading zero digits ('1's) are dropped, but for one per leading zero byte (there
+    // are always at least as many of the former, see five8)
+    for ((hash, text), (bs58, len)) in hashes.iter().zip(&text).zip(out) {
+        let zero_bytes = hash.iter().take_while(|byte| **byte == 0).count();
+        let zero_digits = text[..5 * DIGITS]
+            .iter()
+            .take_while(|c| **c == b'1')
+            .count();
+        let skip = zero_digits - zero_bytes;
+        bs58.copy_from_slice(&text[skip..skip + 44]);
+        *len = (5 * DIGITS - skip) as u8;
+    }
+}
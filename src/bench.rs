@@ -0,0 +1,93 @@
+use std::{
+    sync::{atomic::AtomicBool, Arc},
+    time::Instant,
+};
+
+use clap::Parser;
+
+use crate::{
+    args::{BenchArgs, BumpMode, GrindArgs},
+    energy::{energy_line, EnergyMeter},
+    grind::{install_handlers, run, CancellationToken, Grinder},
+    sha256::Backend,
+};
+
+/// No address will realistically start with this, so a bench never stops for a match
+pub(crate) const TARGET: &str = "zzzzzzzzzzzz";
+
+/// Grinds `--iterations` seeds per thread for `TARGET` and prints how fast each thread
+/// (and all of them together) went
+pub fn bench(args: BenchArgs) {
+    let Some(seeds) = args.iterations.checked_mul(args.threads) else {
+        eprintln!(
+            "--iterations {} is too many for {} threads",
+            args.iterations, args.threads
+        );
+        std::process::exit(1);
+    };
+    let seed_end = seeds.to_string();
+    let mut grind_args = GrindArgs::parse_from([
+        "grind",
+        "--owner",
+        "11111111111111111111111111111111",
+        "--target",
+        TARGET,
+        "--seed-start",
+        "0",
+        "--seed-end",
+        &seed_end,
+    ]);
+    // Set here rather than parsed, so `grinder.toml` and the environment (which are for
+    // real grinds) can't add to them or conflict with them
+    grind_args.suffix = None;
+    grind_args.no_file = true;
+    grind_args.no_manifest = true;
+    grind_args.quiet = true;
+    grind_args.threads = args.threads;
+    grind_args.bump_mode = args.bump_mode;
+    grind_args.batch_size = args.batch_size;
+    grind_args.lookahead = args.lookahead;
+    grind_args.check_order = args.check_order;
+    grind_args.bump_depth = args.bump_depth;
+    grind_args.hasher = args.hasher;
+    grind_args.gpu = None;
+
+    let backend = args
+        .hasher
+        .unwrap_or_else(|| Backend::detect(args.bump_mode == BumpMode::First));
+    println!(
+        "benchmarking {} seeds on each of {} threads, hashing with {}",
+        args.iterations,
+        args.threads,
+        backend.name()
+    );
+    let (cancel, paused) = (CancellationToken::new(), Arc::new(AtomicBool::new(false)));
+    install_handlers(cancel.clone(), paused.clone(), true);
+    let grinder = Grinder::new(grind_args)
+        .cancellation_token(cancel)
+        .pause_flag(paused);
+    let mut energy = EnergyMeter::open().ok();
+    let start_time = Instant::now();
+    let threads = run(grinder).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    });
+    let elapsed = start_time.elapsed().as_secs_f64();
+    let joules = energy.as_mut().and_then(EnergyMeter::joules);
+
+    for (i, (searched, thread_elapsed)) in threads.iter().enumerate() {
+        let thread_elapsed = thread_elapsed.as_secs_f64();
+        println!(
+            "thread {i}: {searched} seeds in {thread_elapsed:.2}s ({:.0} seeds/s)",
+            *searched as f64 / thread_elapsed
+        );
+    }
+    let searched: u64 = threads.iter().map(|(searched, _)| searched).sum();
+    println!(
+        "total: {searched} seeds in {elapsed:.2}s ({:.0} seeds/s){}",
+        searched as f64 / elapsed,
+        joules
+            .map(|joules| energy_line(joules, searched, elapsed))
+            .unwrap_or_default()
+    );
+}
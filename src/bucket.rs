@@ -0,0 +1,188 @@
+//! The S3-compatible bucket a `--sync-url` names, which a grind uploads its checkpoint
+//! and results to when it stops (on a spot instance's SIGTERM, say) and picks them back
+//! up from when it starts. Requests are signed with AWS Signature Version 4 when there
+//! are credentials in the environment, and sent unsigned otherwise, for buckets that let
+//! anyone write to them.
+
+use std::{io::Read, time::Duration};
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    results::{format_utc, unix_now},
+    seeds::to_hex,
+};
+
+/// How long an upload or download can take
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// `AWS_ACCESS_KEY_ID` and the rest, as the AWS CLI reads them
+pub struct Credentials {
+    pub access_key: String,
+    pub secret_key: String,
+    pub session_token: Option<String>,
+    pub region: String,
+}
+
+impl Credentials {
+    fn from_env() -> Option<Credentials> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Some(Credentials {
+            access_key: var("AWS_ACCESS_KEY_ID")?,
+            secret_key: var("AWS_SECRET_ACCESS_KEY")?,
+            session_token: var("AWS_SESSION_TOKEN"),
+            region: var("AWS_REGION")
+                .or_else(|| var("AWS_DEFAULT_REGION"))
+                .unwrap_or("us-east-1".to_string()),
+        })
+    }
+}
+
+pub(crate) struct Bucket {
+    /// Where objects go, e.g. `https://s3.us-east-1.amazonaws.com/bucket/prefix`, with
+    /// the bucket in the path rather than the host
+    url: String,
+    credentials: Option<Credentials>,
+    agent: ureq::Agent,
+}
+
+impl Bucket {
+    pub(crate) fn new(url: &str) -> Result<Bucket, String> {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(format!(
+                "expected an http:// or https:// --sync-url with the bucket in the path, got {url:?}"
+            ));
+        }
+        Ok(Bucket {
+            url: url.trim_end_matches('/').to_owned(),
+            credentials: Credentials::from_env(),
+            agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+        })
+    }
+
+    /// The URL of the object called `name`
+    pub(crate) fn object_url(&self, name: &str) -> String {
+        format!("{}/{}", self.url, encode(name))
+    }
+
+    /// The object called `name`, or `None` if there isn't one
+    pub(crate) fn get(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+        let url = self.object_url(name);
+        match self.request("GET", &url, &[]).call() {
+            Ok(response) => {
+                let mut body = vec![];
+                response
+                    .into_reader()
+                    .read_to_end(&mut body)
+                    .map_err(|e| format!("failed to download {url}: {e}"))?;
+                Ok(Some(body))
+            }
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(format!("failed to download {url}: {e}")),
+        }
+    }
+
+    /// Uploads `body` as the object called `name`, replacing any there was
+    pub(crate) fn put(&self, name: &str, body: &[u8]) -> Result<(), String> {
+        let url = self.object_url(name);
+        self.request("PUT", &url, body)
+            .send_bytes(body)
+            .map(drop)
+            .map_err(|e| format!("failed to upload {url}: {e}"))
+    }
+
+    /// A request for `url`, signed if there are credentials
+    fn request(&self, method: &str, url: &str, body: &[u8]) -> ureq::Request {
+        let request = self.agent.request(method, url);
+        let Some(credentials) = &self.credentials else {
+            return request;
+        };
+
+        // `20250131T180000Z`
+        let time = format_utc(unix_now()).replace(['-', ':'], "");
+        let (host, path) = url
+            .split_once("://")
+            .map(|(_, rest)| rest.split_once('/').unwrap_or((rest, "")))
+            .expect("checked in `new`");
+        let mut headers = vec![
+            ("host", host.to_owned()),
+            ("x-amz-content-sha256", to_hex(&Sha256::digest(body))),
+            ("x-amz-date", time.clone()),
+        ];
+        if let Some(token) = &credentials.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        // Object names are already encoded, see `object_url`
+        let authorization =
+            authorization(credentials, method, &format!("/{path}"), &headers, &time);
+        headers
+            .into_iter()
+            .filter(|(name, _)| *name != "host")
+            .fold(request, |request, (name, value)| request.set(name, &value))
+            .set("Authorization", &authorization)
+    }
+}
+
+/// The AWS Signature Version 4 `Authorization` header of an S3 request (without a query)
+/// for the already encoded `path`, made at `time` (`20250131T180000Z`). `headers` are
+/// the ones it signs, `host` among them, with lowercase names in sorted order. The body
+/// is signed by its `x-amz-content-sha256` header, or as `UNSIGNED-PAYLOAD` without one.
+pub fn authorization(
+    credentials: &Credentials,
+    method: &str,
+    path: &str,
+    headers: &[(&str, String)],
+    time: &str,
+) -> String {
+    let date = &time[..8];
+    let payload_hash = headers
+        .iter()
+        .find(|(name, _)| *name == "x-amz-content-sha256")
+        .map_or("UNSIGNED-PAYLOAD", |(_, hash)| hash);
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect();
+    let canonical_request =
+        format!("{method}\n{path}\n\n{canonical_headers}\n{signed_headers}\n{payload_hash}");
+    let scope = format!("{date}/{}/s3/aws4_request", credentials.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{time}\n{scope}\n{}",
+        to_hex(&Sha256::digest(canonical_request))
+    );
+    let key = [date, &credentials.region, "s3", "aws4_request"]
+        .iter()
+        .fold(
+            format!("AWS4{}", credentials.secret_key).into_bytes(),
+            |key, part| hmac(&key, part.as_bytes()),
+        );
+    let signature = to_hex(&hmac(&key, string_to_sign.as_bytes()));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        credentials.access_key
+    )
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encodes everything in `s` but unreserved characters, as signing expects
+fn encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            b => format!("%{b:02X}"),
+        })
+        .collect()
+}
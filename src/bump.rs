@@ -0,0 +1,38 @@
+//! Finding a seed's canonical bump among a window of its candidate addresses. The
+//! canonical bump is the first one down from 255 whose address is off the curve, so a
+//! window of the addresses at bumps 255, 254, ... holds it exactly when one of them is
+//! off the curve, and it is the first of those.
+
+/// The canonical bump, if it is among `candidates` (the addresses at bumps 255, 254, ...
+/// in turn, at most 255 of them) and `matches` says its candidate matches.
+///
+/// `curve_first` puts candidates through `off_curve` in turn until one is, and only asks
+/// `matches` about that one. Otherwise `matches` is asked about every candidate first, and
+/// the window is only curve checked if one of them matched. The answer is the same either
+/// way, whatever the length of the window; each candidate is asked about at most once.
+pub fn canonical_bump_matches(
+    candidates: &[[u8; 32]],
+    curve_first: bool,
+    off_curve: impl FnMut(&[u8; 32]) -> bool,
+    mut matches: impl FnMut(usize) -> bool,
+) -> Option<u8> {
+    assert!(candidates.len() <= u8::MAX as usize);
+    let bump = |offset: usize| u8::MAX - offset as u8;
+
+    if curve_first {
+        let offset = candidates.iter().position(off_curve)?;
+        return matches(offset).then(|| bump(offset));
+    }
+
+    let mut matched = [0_u64; 4];
+    for offset in 0..candidates.len() {
+        if matches(offset) {
+            matched[offset / 64] |= 1 << (offset % 64);
+        }
+    }
+    if matched == [0; 4] {
+        return None;
+    }
+    let offset = candidates.iter().position(off_curve)?;
+    (matched[offset / 64] >> (offset % 64) & 1 == 1).then(|| bump(offset))
+}
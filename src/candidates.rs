@@ -0,0 +1,101 @@
+//! The canonical addresses of seed after seed, for programs that pick addresses by
+//! something a `--target` can't say. Seeds are hashed a batch at a time, as a grind hashes
+//! them, and only the ones whose address at bump 255 is on the curve go on to lower bumps.
+
+use solana_pubkey::Pubkey;
+
+use crate::{
+    curve::{off_curve, off_curve_batch},
+    grind::PDA_MARKER,
+    sha256::{Backend, MultiHasher},
+};
+
+/// Seeds hashed at a time
+const BATCH: usize = 1024;
+
+/// Where the bump goes in a preimage, after the seed
+const BUMP_AT: usize = 8;
+
+/// The `(seed, bump, address)` of every `u64` seed from `start_seed` up to `u64::MAX`,
+/// with the address and bump `Pubkey::find_program_address(&[&seed.to_le_bytes()], &owner)`
+/// gives. The rare seed with no bump off the curve is skipped.
+pub struct SeedCandidates {
+    /// The first seed of the next batch, `None` once the last one is hashed
+    next_seed: Option<u64>,
+    hasher: MultiHasher,
+    off_curve: Vec<bool>,
+    /// The rest of the current batch, last first
+    batch: Vec<(u64, u8, Pubkey)>,
+}
+
+impl SeedCandidates {
+    pub fn new(owner: Pubkey, start_seed: u64) -> SeedCandidates {
+        let mut hasher = MultiHasher::new(Backend::detect(true), BATCH);
+        hasher.set_len(BUMP_AT + 1 + 32 + PDA_MARKER.len());
+        for slot in 0..BATCH {
+            let msg = hasher.msg_mut(slot);
+            msg[BUMP_AT + 1..BUMP_AT + 33].copy_from_slice(owner.as_ref());
+            msg[BUMP_AT + 33..].copy_from_slice(PDA_MARKER);
+        }
+        SeedCandidates {
+            next_seed: Some(start_seed),
+            hasher,
+            off_curve: vec![false; BATCH],
+            batch: Vec::with_capacity(BATCH),
+        }
+    }
+
+    /// Works out the canonical addresses of the next batch of seeds
+    fn fill(&mut self, first: u64) {
+        let count = (u64::MAX - first).min(BATCH as u64 - 1) as usize + 1;
+        self.next_seed = first.checked_add(count as u64);
+
+        let hasher = &mut self.hasher;
+        for slot in 0..count {
+            let msg = hasher.msg_mut(slot);
+            msg[..BUMP_AT].copy_from_slice(&(first + slot as u64).to_le_bytes());
+            msg[BUMP_AT] = u8::MAX;
+        }
+        hasher.hash(count);
+        off_curve_batch(&hasher.hashes()[..count], &mut self.off_curve);
+        let mut found: Vec<_> = (0..count)
+            .map(|slot| self.off_curve[slot].then(|| (u8::MAX, hasher.hashes()[slot])))
+            .collect();
+
+        // The rest go down through the bumps in the first slot, whose seed is done with
+        for (slot, found) in found.iter_mut().enumerate() {
+            if found.is_some() {
+                continue;
+            }
+            hasher.copy_msg(slot, 0);
+            hasher.fix_all_but(BUMP_AT);
+            *found = (1..u8::MAX).rev().find_map(|bump| {
+                hasher.hash_byte(bump);
+                let key = hasher.hashes()[0];
+                off_curve(&key).then_some((bump, key))
+            });
+        }
+
+        self.batch.extend(
+            found
+                .into_iter()
+                .enumerate()
+                .rev()
+                .filter_map(|(slot, found)| {
+                    found
+                        .map(|(bump, key)| (first + slot as u64, bump, Pubkey::new_from_array(key)))
+                }),
+        );
+    }
+}
+
+impl Iterator for SeedCandidates {
+    type Item = (u64, u8, Pubkey);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.batch.is_empty() {
+            self.fill(self.next_seed?);
+        }
+        self.batch.pop()
+    }
+}
@@ -0,0 +1,232 @@
+//! Where a grind got to, for `--checkpoint` and `--resume`. Positions count seeds from
+//! the start of the run's sequence (see `chunk_at` in `grind`), so together with the
+//! random offset and seed filler the run started with they pin down exactly which seeds
+//! have been searched. With a `--queue`, the cursor takes them from there instead.
+
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use tracing::error;
+
+use crate::{
+    queue::Queue,
+    results::{format_utc, unix_now, write_manifest},
+    seeds::{parse_hex, to_hex, HexBytes},
+};
+
+/// The cursor the threads take chunks of seeds from, which also keeps track of the
+/// chunks they are partway through
+pub(crate) struct Cursor {
+    chunks: Mutex<Chunks>,
+    queue: Option<Arc<Queue>>,
+}
+
+struct Chunks {
+    /// The first chunk no thread has taken yet
+    next: u64,
+    /// Chunks left over from a `--resume`d run, taken before `next`, last first
+    unfinished: Vec<u64>,
+    /// The chunks each thread took and hasn't finished
+    taken: BTreeMap<usize, Vec<u64>>,
+    /// With a `--queue`, chunks finished since the last `renew`, and before it
+    finished: Vec<u64>,
+    releasing: Vec<u64>,
+    /// Set once the `--queue` couldn't be reached, so that no more chunks are taken
+    lost: bool,
+}
+
+impl Cursor {
+    pub(crate) fn new(next: u64, mut unfinished: Vec<u64>, queue: Option<Arc<Queue>>) -> Cursor {
+        unfinished.sort_unstable_by(|a, b| b.cmp(a));
+        Cursor {
+            chunks: Mutex::new(Chunks {
+                next,
+                unfinished,
+                taken: BTreeMap::new(),
+                finished: vec![],
+                releasing: vec![],
+                lost: false,
+            }),
+            queue,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Chunks> {
+        self.chunks.lock().unwrap()
+    }
+
+    /// The position of the next chunk of `chunk_len` seeds for `thread` to grind, or
+    /// `None` once the `--queue` can't be reached
+    pub(crate) fn take(&self, thread: usize, chunk_len: u64) -> Option<u64> {
+        let mut chunks = self.lock();
+        let position = match &self.queue {
+            _ if chunks.lost => return None,
+            Some(queue) => match queue.take(chunk_len) {
+                Ok(position) => position,
+                Err(e) => {
+                    error!("{e}");
+                    chunks.lost = true;
+                    return None;
+                }
+            },
+            None => chunks.unfinished.pop().unwrap_or_else(|| {
+                let next = chunks.next;
+                chunks.next = next.saturating_add(chunk_len);
+                next
+            }),
+        };
+        chunks.taken.entry(thread).or_default().push(position);
+        Some(position)
+    }
+
+    /// Marks the chunks `thread` took as searched, once what it found in them is sent
+    /// to be written out
+    pub(crate) fn finish(&self, thread: usize) {
+        let mut chunks = self.lock();
+        if let Some(taken) = chunks.taken.remove(&thread) {
+            if self.queue.is_some() {
+                chunks.finished.extend(taken);
+            }
+        }
+    }
+
+    /// The first chunk no thread has taken yet, and the ones before it that aren't
+    /// searched yet, lowest first
+    pub(crate) fn position(&self) -> (u64, Vec<u64>) {
+        let chunks = self.lock();
+        let mut unfinished: Vec<_> = chunks
+            .unfinished
+            .iter()
+            .chain(chunks.taken.values().flatten())
+            .copied()
+            .collect();
+        unfinished.sort_unstable();
+        (chunks.next, unfinished)
+    }
+
+    /// Renews the `--queue` leases of the chunks the threads are partway through, and
+    /// releases the ones they finished before the last `renew`, by now long enough ago
+    /// for the matches in them to have been pushed
+    pub(crate) fn renew(&self) {
+        let Some(queue) = &self.queue else {
+            return;
+        };
+        let (taken, searched) = {
+            let mut chunks = self.lock();
+            let finished = std::mem::take(&mut chunks.finished);
+            let searched = std::mem::replace(&mut chunks.releasing, finished);
+            let taken: Vec<_> = chunks.taken.values().flatten().copied().collect();
+            (taken, searched)
+        };
+        for result in [queue.renew(&taken), queue.release(&searched)] {
+            if let Err(e) = result {
+                error!("{e}");
+            }
+        }
+    }
+
+    /// Releases every chunk the threads finished and gives the `--queue` back the ones
+    /// they didn't, once the matches are all pushed
+    pub(crate) fn leave(&self) {
+        let Some(queue) = &self.queue else {
+            return;
+        };
+        let mut chunks = self.lock();
+        let mut searched = std::mem::take(&mut chunks.finished);
+        searched.append(&mut chunks.releasing);
+        let taken: Vec<_> = std::mem::take(&mut chunks.taken)
+            .into_values()
+            .flatten()
+            .collect();
+        for result in [queue.release(&searched), queue.give_back(&taken)] {
+            if let Err(e) = result {
+                error!("{e}");
+            }
+        }
+    }
+
+    /// How many seeds along every seed before has been searched
+    pub(crate) fn searched_below(&self) -> u64 {
+        let (next, unfinished) = self.position();
+        unfinished.first().map_or(next, |first| next.min(*first))
+    }
+}
+
+/// A `--checkpoint` file
+pub(crate) struct Checkpoint {
+    /// The arguments that decide which seeds are searched and what for, which a
+    /// `--resume` has to share
+    pub(crate) search: serde_json::Value,
+    pub(crate) offset: u64,
+    pub(crate) seed_tail: [u8; 32],
+    pub(crate) chunk_len: u64,
+    pub(crate) next: u64,
+    pub(crate) unfinished: Vec<u64>,
+}
+
+impl Checkpoint {
+    pub(crate) fn read(path: &Path) -> Result<Checkpoint, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        let invalid = |what: &str| format!("{} isn't a checkpoint: bad {what}", path.display());
+        let json: serde_json::Value =
+            serde_json::from_str(&contents).map_err(|e| format!("{}: {e}", invalid("JSON")))?;
+        let number = |key: &str| json[key].as_u64().ok_or_else(|| invalid(key));
+        let HexBytes(seed_tail) = json["seed_tail"]
+            .as_str()
+            .and_then(|hex| parse_hex(hex).ok())
+            .ok_or_else(|| invalid("seed_tail"))?;
+        let unfinished = json["unfinished"]
+            .as_array()
+            .and_then(|positions| positions.iter().map(|p| p.as_u64()).collect())
+            .ok_or_else(|| invalid("unfinished"))?;
+        Ok(Checkpoint {
+            search: json["search"].clone(),
+            offset: number("offset")?,
+            seed_tail: seed_tail.try_into().map_err(|_| invalid("seed_tail"))?,
+            chunk_len: number("chunk_len")?,
+            next: number("next")?,
+            unfinished,
+        })
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::json!({
+            "search": self.search,
+            "offset": self.offset,
+            "seed_tail": to_hex(&self.seed_tail),
+            "chunk_len": self.chunk_len,
+            "next": self.next,
+            "unfinished": self.unfinished,
+            "written_at": format_utc(unix_now()),
+        });
+        write_manifest(path, &json)
+    }
+
+    /// Fails, naming the arguments that differ, unless this checkpoint was written by a
+    /// grind for `search`
+    pub(crate) fn check(&self, path: &Path, search: &serde_json::Value) -> Result<(), String> {
+        let (Some(theirs), Some(ours)) = (self.search.as_object(), search.as_object()) else {
+            return Err(format!("{} doesn't say what it searched", path.display()));
+        };
+        let differing: Vec<_> = ours
+            .iter()
+            .filter(|(key, value)| theirs.get(*key) != Some(*value))
+            .map(|(key, value)| {
+                let theirs = theirs.get(key).unwrap_or(&serde_json::Value::Null);
+                format!("\n  {key}: {theirs} there, {value} here")
+            })
+            .collect();
+        if differing.is_empty() {
+            return Ok(());
+        }
+        Err(format!(
+            "{} was written by a different grind:{}",
+            path.display(),
+            differing.concat()
+        ))
+    }
+}
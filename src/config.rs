@@ -0,0 +1,63 @@
+use std::path::Path;
+
+/// Keys a config file can set, each the name of a `grind` flag (with `_` for `-`) with a
+/// `PDA_GRINDER_*` environment variable
+const CONFIG_KEYS: &[&str] = &[
+    "owner",
+    "target",
+    "suffix",
+    "threads",
+    "output",
+    "bump_mode",
+    "batch_size",
+    "lookahead",
+    "check_order",
+    "hasher",
+    "gpu",
+    "gpu_device",
+    "gpu_batch",
+    "webhook",
+    "rpc",
+];
+
+/// Reads `grinder.toml` (or the file named by `PDA_GRINDER_CONFIG`) into the
+/// `PDA_GRINDER_*` environment variables that are not already set, so that flags
+/// override the environment which overrides the config file. Has to run before any
+/// other threads are spawned.
+pub fn load_config() -> Result<(), String> {
+    let path = std::env::var_os("PDA_GRINDER_CONFIG");
+    let path = match &path {
+        Some(path) => Path::new(path),
+        None if Path::new("grinder.toml").exists() => Path::new("grinder.toml"),
+        None => return Ok(()),
+    };
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let config: toml::Table = contents
+        .parse()
+        .map_err(|e| format!("failed to parse {}: {e}", path.display()))?;
+
+    for (key, value) in config {
+        if !CONFIG_KEYS.contains(&key.as_str()) {
+            return Err(format!(
+                "unknown key {key:?} in {}, expected one of {CONFIG_KEYS:?}",
+                path.display()
+            ));
+        }
+        let value = match value {
+            toml::Value::String(value) => value,
+            toml::Value::Integer(value) => value.to_string(),
+            value => {
+                return Err(format!(
+                    "expected a string or integer for {key:?} in {}, got {value}",
+                    path.display()
+                ))
+            }
+        };
+        let var = format!("PDA_GRINDER_{}", key.to_uppercase());
+        if std::env::var_os(&var).is_none() {
+            std::env::set_var(var, value);
+        }
+    }
+    Ok(())
+}
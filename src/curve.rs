@@ -0,0 +1,496 @@
+//! Ed25519 curve checks. A candidate address is on the curve when its bytes decompress
+//! to a point, i.e. when `(y² - 1) / (d·y² + 1)` has a square root mod `p = 2^255 - 19`.
+//! Rather than decompressing (which works the root out and builds the point), this only
+//! asks whether the root exists, with the Legendre symbol of `(y² - 1)·(d·y² + 1)`: it is
+//! a square exactly when the quotient is, and needs no inversion.
+//!
+//! There is nothing cheaper to put in front of that. Decompression ignores the sign bit
+//! and takes a y past `p` mod `p`, so the encoding never rules a candidate out, and about
+//! half of all y are on the curve, so whatever decides it has to get the symbol right.
+//!
+//! One at a time, the symbol comes from a binary GCD with `p`, flipping its sign by
+//! quadratic reciprocity along the way, in the style of Pornin's "Optimized Binary GCD
+//! for Modular Inversion": steps are worked out on 64 bit approximations of the two
+//! numbers, 29 at a time, then applied to the full ones in a single go. That is about
+//! three times quicker than `Pubkey::is_on_curve()`. On CPUs with AVX-512 IFMA (52 bit
+//! multiply-adds) eight candidates instead go through Euler's criterion at once, one per
+//! lane: a long exponentiation, ~250 squarings, but quicker still per candidate.
+//!
+//! curve25519-dalek keeps its field arithmetic to itself, so this has its own: five 51
+//! bit limbs, the representation of dalek's `u64` backend (and, with 52 bit products
+//! split in two, of its IFMA one).
+
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+/// Candidates checked at once with IFMA
+pub const LANES: usize = 8;
+
+const MASK: u64 = (1 << 51) - 1;
+
+/// `d = -121665/121666`, the curve constant
+const D: Fe = [
+    929955233495203,
+    466365720129213,
+    1662059464998953,
+    2033849074728123,
+    1442794654840575,
+];
+
+const ONE: Fe = [1, 0, 0, 0, 0];
+
+/// A field element, `sum(limbs[i] * 2^(51 i))`, with limbs of (a little over) 51 bits
+type Fe = [u64; 5];
+
+/// Reads `bytes` as a y coordinate, ignoring the top bit (the sign of x) like
+/// decompression does. Values past `p` are taken mod `p`, as they are there too.
+#[inline(always)]
+fn from_bytes(bytes: &[u8; 32]) -> Fe {
+    let load = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+    [
+        load(0) & MASK,
+        (load(6) >> 3) & MASK,
+        (load(12) >> 6) & MASK,
+        (load(19) >> 1) & MASK,
+        (load(24) >> 12) & MASK,
+    ]
+}
+
+/// Carries each limb into the next, leaving limbs of at most 52 bits
+#[inline(always)]
+fn reduce(limbs: [u64; 5]) -> Fe {
+    let c: [u64; 5] = core::array::from_fn(|i| limbs[i] >> 51);
+    [
+        (limbs[0] & MASK) + c[4] * 19,
+        (limbs[1] & MASK) + c[0],
+        (limbs[2] & MASK) + c[1],
+        (limbs[3] & MASK) + c[2],
+        (limbs[4] & MASK) + c[3],
+    ]
+}
+
+#[inline(always)]
+fn add(a: &Fe, b: &Fe) -> Fe {
+    reduce(core::array::from_fn(|i| a[i] + b[i]))
+}
+
+#[inline(always)]
+fn sub(a: &Fe, b: &Fe) -> Fe {
+    // Adds 16p first so that nothing underflows
+    reduce([
+        (a[0] + 36028797018963664) - b[0],
+        (a[1] + 36028797018963952) - b[1],
+        (a[2] + 36028797018963952) - b[2],
+        (a[3] + 36028797018963952) - b[3],
+        (a[4] + 36028797018963952) - b[4],
+    ])
+}
+
+/// Carries 128 bit column sums back down to 51 bit limbs
+#[inline(always)]
+fn carry(mut c: [u128; 5]) -> Fe {
+    let mut out = [0; 5];
+    for i in 0..4 {
+        c[i + 1] += c[i] >> 51;
+        out[i] = c[i] as u64 & MASK;
+    }
+    out[4] = c[4] as u64 & MASK;
+    out[0] += (c[4] >> 51) as u64 * 19;
+    out[1] += out[0] >> 51;
+    out[0] &= MASK;
+    out
+}
+
+#[inline(always)]
+fn mul(a: &Fe, b: &Fe) -> Fe {
+    let m = |x: u64, y: u64| x as u128 * y as u128;
+    let b1 = b[1] * 19;
+    let b2 = b[2] * 19;
+    let b3 = b[3] * 19;
+    let b4 = b[4] * 19;
+    carry([
+        m(a[0], b[0]) + m(a[4], b1) + m(a[3], b2) + m(a[2], b3) + m(a[1], b4),
+        m(a[1], b[0]) + m(a[0], b[1]) + m(a[4], b2) + m(a[3], b3) + m(a[2], b4),
+        m(a[2], b[0]) + m(a[1], b[1]) + m(a[0], b[2]) + m(a[4], b3) + m(a[3], b4),
+        m(a[3], b[0]) + m(a[2], b[1]) + m(a[1], b[2]) + m(a[0], b[3]) + m(a[4], b4),
+        m(a[4], b[0]) + m(a[3], b[1]) + m(a[2], b[2]) + m(a[1], b[3]) + m(a[0], b[4]),
+    ])
+}
+
+#[inline(always)]
+fn square(a: &Fe) -> Fe {
+    let m = |x: u64, y: u64| x as u128 * y as u128;
+    let a0_2 = a[0] * 2;
+    let a1_2 = a[1] * 2;
+    let a3_19 = a[3] * 19;
+    let a4_19 = a[4] * 19;
+    carry([
+        m(a[0], a[0]) + m(a1_2, a4_19) + m(a[2] * 2, a3_19),
+        m(a0_2, a[1]) + m(a[2] * 2, a4_19) + m(a[3], a3_19),
+        m(a0_2, a[2]) + m(a[1], a[1]) + m(a[3] * 2, a4_19),
+        m(a0_2, a[3]) + m(a1_2, a[2]) + m(a[4], a4_19),
+        m(a0_2, a[4]) + m(a1_2, a[3]) + m(a[2], a[2]),
+    ])
+}
+
+/// `(y² - 1)·(d·y² + 1)` for the candidate `key`, a square exactly when it is on the curve
+#[inline(always)]
+fn uv(key: &[u8; 32]) -> Fe {
+    let yy = square(&from_bytes(key));
+    mul(&sub(&yy, &ONE), &add(&mul(&yy, &D), &ONE))
+}
+
+/// `x` fully reduced, as four 64 bit words
+#[inline(always)]
+fn canonical(x: &Fe) -> [u64; 4] {
+    // As in dalek's `as_bytes`
+    let mut limbs = reduce(*x);
+    let mut q = (limbs[0] + 19) >> 51;
+    for limb in &limbs[1..] {
+        q = (limb + q) >> 51;
+    }
+    limbs[0] += 19 * q;
+    for i in 0..4 {
+        limbs[i + 1] += limbs[i] >> 51;
+        limbs[i] &= MASK;
+    }
+    limbs[4] &= MASK;
+    [
+        limbs[0] | limbs[1] << 51,
+        limbs[1] >> 13 | limbs[2] << 38,
+        limbs[2] >> 26 | limbs[3] << 25,
+        limbs[3] >> 39 | limbs[4] << 12,
+    ]
+}
+
+/// Whether `x` is `-1`, the Legendre symbol of a non-square
+#[inline(always)]
+fn is_minus_one(x: &Fe) -> bool {
+    // x + 1 reduces to 0 exactly when x is -1
+    canonical(&add(x, &ONE)) == [0; 4]
+}
+
+/// `p`, as four 64 bit words
+const P: [u64; 4] = [u64::MAX - 18, u64::MAX, u64::MAX, u64::MAX >> 1];
+
+/// Binary GCD steps taken per pass over the full numbers. Each doubles the factors that
+/// the approximations gather, which have to stay within an `i64` and, times a 256 bit
+/// number, within the 64 bit words `combine` carries.
+const STEPS: u32 = 29;
+
+#[inline(always)]
+fn bit_len(a: &[u64; 4]) -> u32 {
+    for i in (0..4).rev() {
+        if a[i] != 0 {
+            return 64 * i as u32 + 64 - a[i].leading_zeros();
+        }
+    }
+    0
+}
+
+/// The top 33 of `a`'s `len` bits over its bottom 31, all a run of `STEPS` steps looks at
+#[inline(always)]
+fn approximate(a: &[u64; 4], len: u32) -> u64 {
+    let shift = len - 33;
+    let (word, bit) = ((shift / 64) as usize, shift % 64);
+    let mut top = a[word] >> bit;
+    if bit != 0 && word < 3 {
+        top |= a[word + 1] << (64 - bit);
+    }
+    (top & ((1 << 33) - 1)) << 31 | (a[0] & ((1 << 31) - 1))
+}
+
+/// `(f·a + g·b) / 2^STEPS`, or `None` if it is negative
+#[inline(always)]
+fn combine(a: &[u64; 4], b: &[u64; 4], f: i64, g: i64) -> Option<[u64; 4]> {
+    let mut out = [0; 5];
+    let mut carry = 0_i128;
+    for i in 0..4 {
+        let t = a[i] as i128 * f as i128 + b[i] as i128 * g as i128 + carry;
+        out[i] = t as u64;
+        carry = t >> 64;
+    }
+    if carry < 0 {
+        return None;
+    }
+    out[4] = carry as u64;
+    Some(core::array::from_fn(|i| {
+        out[i] >> STEPS | out[i + 1] << (64 - STEPS)
+    }))
+}
+
+/// `a < b`
+#[inline(always)]
+fn less(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    for i in (0..4).rev() {
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+    }
+    false
+}
+
+/// `a -= b`, for `b <= a`
+#[inline(always)]
+fn sub_assign(a: &mut [u64; 4], b: &[u64; 4]) {
+    let mut borrow = false;
+    for i in 0..4 {
+        let (d, b1) = a[i].overflowing_sub(b[i]);
+        let (d, b2) = d.overflowing_sub(borrow as u64);
+        a[i] = d;
+        borrow = b1 | b2;
+    }
+}
+
+/// `a >>= 1`
+#[inline(always)]
+fn halve(a: &mut [u64; 4]) {
+    for i in 0..3 {
+        a[i] = a[i] >> 1 | a[i + 1] << 63;
+    }
+    a[3] >>= 1;
+}
+
+/// Whether the candidate address `key` is off the curve, i.e. a valid PDA. Gives the
+/// same answer as `!Pubkey::is_on_curve()`.
+///
+/// Works out the Jacobi symbol `(a / b)` with `a` the candidate's `(y² - 1)·(d·y² + 1)`
+/// and `b = p`, keeping `b` odd: an odd `a` below `b` swaps with it (flipping the sign
+/// when both are 3 mod 4), then `a -= b`, and each halving of `a` flips it when `b` is 3
+/// or 5 mod 8. `b` ends up as the GCD, 1 unless `a` started at 0.
+#[inline(always)]
+pub fn off_curve(key: &[u8; 32]) -> bool {
+    let mut a = canonical(&uv(key));
+    let mut b = P;
+    let mut flip = 0;
+    while a != [0; 4] {
+        let len = bit_len(&a).max(bit_len(&b));
+        if len <= 64 {
+            // Both fit in a word, so the rest is exact
+            let (mut a, mut b) = (a[0], b[0]);
+            while a != 0 {
+                let odd = (a & 1).wrapping_neg();
+                let swap = odd & ((a < b) as u64).wrapping_neg();
+                let t = (a ^ b) & swap;
+                a ^= t;
+                b ^= t;
+                flip ^= swap & (a & b) >> 1 & 1;
+                a -= b & odd;
+                a >>= 1;
+                flip ^= (b >> 1 ^ b >> 2) & 1;
+            }
+            return b == 1 && flip == 1;
+        }
+
+        // The same steps without branches, on the approximations, gathering what they do
+        // as `a' = (f0·a + g0·b) / 2^STEPS` and `b' = (f1·a + g1·b) / 2^STEPS`. The low
+        // bits are exact and decide every step; the top ones are only needed for the
+        // comparisons, which they get right but for the rare case caught below.
+        let (mut xa, mut xb) = (approximate(&a, len), approximate(&b, len));
+        let (mut f0, mut g0, mut f1, mut g1) = (1_i64, 0_i64, 0_i64, 1_i64);
+        let mut steps_flip = 0;
+        for _ in 0..STEPS {
+            let odd = (xa & 1).wrapping_neg();
+            let swap = odd & ((xa < xb) as u64).wrapping_neg();
+            let t = (xa ^ xb) & swap;
+            xa ^= t;
+            xb ^= t;
+            let t = (f0 ^ f1) & swap as i64;
+            f0 ^= t;
+            f1 ^= t;
+            let t = (g0 ^ g1) & swap as i64;
+            g0 ^= t;
+            g1 ^= t;
+            steps_flip ^= swap & (xa & xb) >> 1 & 1;
+            xa -= xb & odd;
+            f0 -= f1 & odd as i64;
+            g0 -= g1 & odd as i64;
+            xa >>= 1;
+            f1 <<= 1;
+            g1 <<= 1;
+            steps_flip ^= (xb >> 1 ^ xb >> 2) & 1;
+        }
+        match (combine(&a, &b, f0, g0), combine(&a, &b, f1, g1)) {
+            (Some(next_a), Some(next_b)) => {
+                a = next_a;
+                b = next_b;
+                flip ^= steps_flip;
+            }
+            // An approximation got a comparison wrong; take the steps on the full numbers
+            _ => {
+                for _ in 0..STEPS {
+                    if a[0] & 1 == 1 {
+                        if less(&a, &b) {
+                            core::mem::swap(&mut a, &mut b);
+                            flip ^= (a[0] & b[0]) >> 1 & 1;
+                        }
+                        sub_assign(&mut a, &b);
+                    }
+                    halve(&mut a);
+                    flip ^= (b[0] >> 1 ^ b[0] >> 2) & 1;
+                }
+            }
+        }
+    }
+    b == [1, 0, 0, 0] && flip == 1
+}
+
+/// Whether this CPU checks `LANES` candidates at once, rather than one at a time
+pub fn batched() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512ifma")
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+/// `off_curve` for each of `keys`, `LANES` at a time if `batched()`
+pub fn off_curve_batch(keys: &[[u8; 32]], out: &mut [bool]) {
+    let out = &mut out[..keys.len()];
+    #[cfg(target_arch = "x86_64")]
+    if batched() {
+        let mut groups = keys.chunks_exact(LANES);
+        let mut outs = out.chunks_exact_mut(LANES);
+        for (group, out) in (&mut groups).zip(&mut outs) {
+            out.copy_from_slice(&unsafe { off_curve_ifma(group.try_into().unwrap()) });
+        }
+        // The rest, padded out with copies of the first of them
+        let rest = groups.remainder();
+        if let Some(first) = rest.first() {
+            let group = core::array::from_fn(|lane| *rest.get(lane).unwrap_or(first));
+            let group = unsafe { off_curve_ifma(&group) };
+            outs.into_remainder().copy_from_slice(&group[..rest.len()]);
+        }
+        return;
+    }
+    for (key, out) in keys.iter().zip(out) {
+        *out = off_curve(key);
+    }
+}
+
+/// Eight field elements, one per 64 bit lane of each limb's register
+#[cfg(target_arch = "x86_64")]
+type Fe8 = [__m512i; 5];
+
+/// `x·19`, for limbs small enough not to overflow
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+#[inline]
+fn mul19(x: __m512i) -> __m512i {
+    let x16 = _mm512_slli_epi64::<4>(x);
+    let x2 = _mm512_slli_epi64::<1>(x);
+    _mm512_add_epi64(_mm512_add_epi64(x16, x2), x)
+}
+
+/// Folds the column sums of a product, each below 2^56, into limbs of at most 52
+/// bits, the most the multiply-adds read
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+#[inline]
+fn fold(z: [__m512i; 10]) -> Fe8 {
+    // 2^255 = 19 (mod p), so each column past the fifth comes back around times 19
+    let r: [__m512i; 5] = core::array::from_fn(|i| _mm512_add_epi64(z[i], mul19(z[i + 5])));
+    let mask = _mm512_set1_epi64(MASK as i64);
+    let c: [__m512i; 5] = core::array::from_fn(|i| _mm512_srli_epi64::<51>(r[i]));
+    let low: [__m512i; 5] = core::array::from_fn(|i| _mm512_and_si512(r[i], mask));
+    [
+        _mm512_add_epi64(low[0], mul19(c[4])),
+        _mm512_add_epi64(low[1], c[0]),
+        _mm512_add_epi64(low[2], c[1]),
+        _mm512_add_epi64(low[3], c[2]),
+        _mm512_add_epi64(low[4], c[3]),
+    ]
+}
+
+/// Each lane's `a·b`. The multiply-adds split a limb product into its low 52 bits and
+/// the rest, which lands in the next column doubled since limbs are 51 bits apart.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512ifma")]
+#[inline]
+fn mul8(a: &Fe8, b: &Fe8) -> Fe8 {
+    let mut lo = [_mm512_setzero_si512(); 10];
+    let mut hi = [_mm512_setzero_si512(); 10];
+    for i in 0..5 {
+        for j in 0..5 {
+            lo[i + j] = _mm512_madd52lo_epu64(lo[i + j], a[i], b[j]);
+            hi[i + j + 1] = _mm512_madd52hi_epu64(hi[i + j + 1], a[i], b[j]);
+        }
+    }
+    fold(core::array::from_fn(|k| {
+        _mm512_add_epi64(lo[k], _mm512_add_epi64(hi[k], hi[k]))
+    }))
+}
+
+/// Each lane's `a²`, with every product of two different limbs taken once and doubled
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512ifma")]
+#[inline]
+fn square8(a: &Fe8) -> Fe8 {
+    // Columns counted once, twice and four times
+    let mut z1 = [_mm512_setzero_si512(); 10];
+    let mut z2 = [_mm512_setzero_si512(); 10];
+    let mut z4 = [_mm512_setzero_si512(); 10];
+    for i in 0..5 {
+        z1[2 * i] = _mm512_madd52lo_epu64(z1[2 * i], a[i], a[i]);
+        z2[2 * i + 1] = _mm512_madd52hi_epu64(z2[2 * i + 1], a[i], a[i]);
+        for j in i + 1..5 {
+            z2[i + j] = _mm512_madd52lo_epu64(z2[i + j], a[i], a[j]);
+            z4[i + j + 1] = _mm512_madd52hi_epu64(z4[i + j + 1], a[i], a[j]);
+        }
+    }
+    fold(core::array::from_fn(|k| {
+        let z2 = _mm512_add_epi64(z2[k], _mm512_slli_epi64::<1>(z4[k]));
+        _mm512_add_epi64(z1[k], _mm512_slli_epi64::<1>(z2))
+    }))
+}
+
+/// Each lane's `x^((p - 1) / 2) = x^(2^254 - 10)`: 1 for squares, `p - 1` for the rest
+/// and 0 for 0
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512ifma")]
+fn legendre8(x: &Fe8) -> Fe8 {
+    let pow2k = |mut t: Fe8, k: usize| {
+        for _ in 0..k {
+            t = square8(&t);
+        }
+        t
+    };
+    // The start of the usual inversion chain, up to x^(2^250 - 1)
+    let x2 = square8(x);
+    let x9 = mul8(x, &pow2k(x2, 2));
+    let x11 = mul8(&x2, &x9);
+    let e5 = mul8(&x9, &square8(&x11));
+    let e10 = mul8(&pow2k(e5, 5), &e5);
+    let e20 = mul8(&pow2k(e10, 10), &e10);
+    let e40 = mul8(&pow2k(e20, 20), &e20);
+    let e50 = mul8(&pow2k(e40, 10), &e10);
+    let e100 = mul8(&pow2k(e50, 50), &e50);
+    let e200 = mul8(&pow2k(e100, 100), &e100);
+    let e250 = mul8(&pow2k(e200, 50), &e50);
+    // x^(2^254 - 16) * x^6
+    let x6 = mul8(&x2, &square8(&x2));
+    mul8(&pow2k(e250, 4), &x6)
+}
+
+/// `off_curve` for eight candidates at once
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512ifma")]
+unsafe fn off_curve_ifma(keys: &[[u8; 32]; LANES]) -> [bool; LANES] {
+    // The few multiplications before the exponentiation are left to the scalar code
+    let uv: [Fe; LANES] = core::array::from_fn(|lane| uv(&keys[lane]));
+    let x: Fe8 = core::array::from_fn(|limb| {
+        let limbs: [u64; LANES] = core::array::from_fn(|lane| uv[lane][limb]);
+        unsafe { _mm512_loadu_si512(limbs.as_ptr().cast()) }
+    });
+
+    let symbols = legendre8(&x);
+
+    let mut limbs = [[0_u64; LANES]; 5];
+    for (limb, symbol) in limbs.iter_mut().zip(symbols) {
+        unsafe { _mm512_storeu_si512(limb.as_mut_ptr().cast(), symbol) };
+    }
+    core::array::from_fn(|lane| is_minus_one(&core::array::from_fn(|limb| limbs[limb][lane])))
+}
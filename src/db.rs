@@ -0,0 +1,194 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use rusqlite::{params, Connection, OpenFlags, TransactionBehavior};
+use solana_pubkey::Pubkey;
+use tracing::error;
+
+use crate::{
+    args::{ListArgs, Shard},
+    results::{unix_now, Found},
+    sinks::ResultSink,
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS pdas (
+        pubkey TEXT NOT NULL,
+        seed TEXT NOT NULL,
+        seed_hex TEXT,
+        bump INTEGER NOT NULL,
+        owner TEXT NOT NULL,
+        found_at INTEGER NOT NULL,
+        iterations INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS pdas_pubkey ON pdas (pubkey);
+    CREATE TABLE IF NOT EXISTS shards (
+        job TEXT NOT NULL,
+        shard INTEGER NOT NULL,
+        shards INTEGER NOT NULL,
+        pid INTEGER NOT NULL,
+        claimed_at INTEGER NOT NULL
+    );
+";
+
+/// The `--db` database
+pub(crate) struct ResultsDb {
+    path: PathBuf,
+    conn: Connection,
+    owner: Pubkey,
+}
+
+/// Opens the database at `path` for writing, creating it if need be. Other grinds can
+/// write to it at the same time: WAL lets readers and the writer overlap, and a writer
+/// waits for the lock rather than failing.
+fn open(path: &Path) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(Duration::from_secs(10))?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.execute_batch(SCHEMA)?;
+
+    // Databases from before `seed_hex` was recorded get the column, null for old rows
+    let has_seed_hex = conn
+        .prepare("SELECT 1 FROM pragma_table_info('pdas') WHERE name = 'seed_hex'")?
+        .exists([])?;
+    if !has_seed_hex {
+        conn.execute_batch("ALTER TABLE pdas ADD COLUMN seed_hex TEXT")?;
+    }
+    Ok(conn)
+}
+
+/// A grind that took a `--shard` of a job before this one
+pub(crate) struct Claim {
+    pub(crate) shard: Shard,
+    pub(crate) pid: u32,
+    pub(crate) claimed_at: u64,
+}
+
+impl ResultsDb {
+    pub(crate) fn open(path: &Path, owner: Pubkey) -> rusqlite::Result<Self> {
+        Ok(ResultsDb {
+            path: path.to_owned(),
+            conn: open(path)?,
+            owner,
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn add(&self, found: &Found) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO pdas (pubkey, seed, seed_hex, bump, owner, found_at, iterations)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                found.key.to_string(),
+                found.seed,
+                found.seed_hex,
+                found.bump,
+                self.owner.to_string(),
+                found.found_at,
+                found.iterations,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Records that this grind takes `shard` of `job`, returning the shards of it that
+    /// other grinds took before and that have seeds in common with it. It reads and adds
+    /// the claims in one transaction holding the write lock, so of two grinds claiming at
+    /// once the second sees the first.
+    pub(crate) fn claim(&mut self, job: &str, shard: Shard) -> rusqlite::Result<Vec<Claim>> {
+        let transaction = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let claims = transaction
+            .prepare("SELECT shard, shards, pid, claimed_at FROM shards WHERE job = ?1")?
+            .query_map([job], |row| {
+                Ok(Claim {
+                    shard: Shard {
+                        index: row.get(0)?,
+                        count: row.get(1)?,
+                    },
+                    pid: row.get(2)?,
+                    claimed_at: row.get(3)?,
+                })
+            })?
+            .filter(|claim| {
+                claim
+                    .as_ref()
+                    .map_or(true, |claim| claim.shard.overlaps(shard))
+            })
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        transaction.execute(
+            "INSERT INTO shards (job, shard, shards, pid, claimed_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                job,
+                shard.index,
+                shard.count,
+                std::process::id(),
+                unix_now()
+            ],
+        )?;
+        transaction.commit()?;
+        Ok(claims)
+    }
+}
+
+impl ResultSink for ResultsDb {
+    fn add(&mut self, found: &Found) {
+        if let Err(e) = ResultsDb::add(self, found) {
+            error!("failed to insert into {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Prints the matches in `args.db`, oldest first. Only reads the database, so it
+/// works on one it can't write to and leaves older ones as they are.
+pub fn list(args: ListArgs) {
+    let conn = Connection::open_with_flags(&args.db, OpenFlags::SQLITE_OPEN_READ_ONLY);
+    let rows = conn.and_then(|conn| {
+        conn.busy_timeout(Duration::from_secs(10))?;
+        // Databases from before `seed_hex` was recorded don't have the column
+        let has_seed_hex = conn
+            .prepare("SELECT 1 FROM pragma_table_info('pdas') WHERE name = 'seed_hex'")?
+            .exists([])?;
+        let seed_hex = if has_seed_hex { "seed_hex" } else { "NULL" };
+        // GLOB rather than LIKE, which is case insensitive and can't use the index
+        let mut statement = conn.prepare(&format!(
+            "SELECT pubkey, seed, {seed_hex}, bump, owner FROM pdas
+             WHERE (?1 IS NULL OR owner = ?1) AND (?2 IS NULL OR pubkey GLOB ?2)
+             ORDER BY found_at, rowid",
+        ))?;
+        let owner = args.owner.map(|owner| owner.to_string());
+        let pattern = args.prefix.map(|prefix| format!("{prefix}*"));
+        let rows = statement
+            .query_map(params![owner, pattern], |row| {
+                let seed_hex = row
+                    .get::<_, Option<String>>(2)?
+                    .map_or(String::new(), |seed_hex| format!(" hex {seed_hex}"));
+                Ok(format!(
+                    "{}: {}{seed_hex} bump {} owner {}",
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, u8>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    });
+    match rows {
+        Ok(rows) => {
+            for row in rows {
+                println!("{row}");
+            }
+        }
+        Err(e) => {
+            eprintln!("failed to read {}: {e}", args.db.display());
+            std::process::exit(1);
+        }
+    }
+}
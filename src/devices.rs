@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+
+use clap::ValueEnum;
+
+use crate::{
+    curve,
+    gpu::{self, Gpu},
+    sha256::Backend,
+};
+
+/// The CPU as `/proc/cpuinfo` has it, where there is one
+struct Cpu {
+    model: Option<String>,
+    sockets: usize,
+    cores: usize,
+    threads: usize,
+}
+
+impl Cpu {
+    fn read() -> Option<Cpu> {
+        let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+        let mut model = None;
+        let mut sockets = HashSet::new();
+        let mut cores = HashSet::new();
+        let mut threads = 0;
+        // One paragraph per hardware thread
+        for processor in cpuinfo.split("\n\n").filter(|p| !p.trim().is_empty()) {
+            let field = |name: &str| {
+                processor.lines().find_map(|line| {
+                    let (key, value) = line.split_once(':')?;
+                    (key.trim() == name).then(|| value.trim().to_string())
+                })
+            };
+            threads += 1;
+            model = model.or_else(|| field("model name"));
+            let socket = field("physical id");
+            cores.insert((
+                socket.clone(),
+                field("core id").unwrap_or(threads.to_string()),
+            ));
+            sockets.insert(socket);
+        }
+        Some(Cpu {
+            model,
+            sockets: sockets.len(),
+            cores: cores.len(),
+            threads,
+        })
+    }
+}
+
+/// Lists the CPU and the GPUs each `--gpu` backend finds, along with what `--threads`,
+/// `--hasher` and `--gpu-device` make of them
+pub fn devices() {
+    let max = std::thread::available_parallelism().map_or(1, |n| n.get());
+    match Cpu::read() {
+        Some(cpu) => {
+            let model = cpu.model.unwrap_or("unknown".to_string());
+            let sockets = match cpu.sockets {
+                0 | 1 => String::new(),
+                n => format!("{n} sockets, "),
+            };
+            let count = |n: usize, what: &str| match n {
+                1 => format!("1 {what}"),
+                n => format!("{n} {what}s"),
+            };
+            println!(
+                "CPU: {model}, {sockets}{}, {}",
+                count(cpu.cores, "core"),
+                count(cpu.threads, "thread")
+            );
+        }
+        None => println!("CPU:"),
+    }
+    println!("  --threads max: {max}");
+
+    let hashers: Vec<_> = Backend::value_variants()
+        .iter()
+        .filter(|hasher| hasher.supported())
+        .map(|hasher| hasher.to_possible_value().unwrap().get_name().to_string())
+        .collect();
+    let batched = curve::batched();
+    println!(
+        "  --hasher: {} (default {})",
+        hashers.join(", "),
+        Backend::detect(true)
+            .to_possible_value()
+            .unwrap()
+            .get_name()
+    );
+    println!(
+        "  curve checks: {}",
+        if batched {
+            "batched with AVX-512 IFMA"
+        } else {
+            "one at a time"
+        }
+    );
+
+    for &backend in Gpu::value_variants() {
+        println!("{}:", backend.flag());
+        match gpu::list(backend) {
+            Ok(gpus) if gpus.is_empty() => println!("  no GPU found"),
+            Ok(gpus) => {
+                for (i, gpu) in gpus.iter().enumerate() {
+                    println!("  --gpu-device {i}: {gpu}");
+                }
+            }
+            Err(e) => println!("  {e}"),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! The energy the CPU uses while grinding, for joules per million seeds next to the
+//! hashrate: from the RAPL counters under `/sys/class/powercap` on Linux, and from
+//! `powermetrics` on macOS, both of which only root can read on most machines. It is the
+//! whole package's energy, so whatever else is running counts too.
+
+/// Counts the energy used from when it was opened
+pub(crate) struct EnergyMeter(imp::Meter);
+
+impl EnergyMeter {
+    /// Why there is none, if there isn't
+    pub(crate) fn open() -> Result<EnergyMeter, String> {
+        imp::Meter::open().map(EnergyMeter)
+    }
+
+    /// Joules used since it was opened, if it can tell yet. Linux's counters wrap around
+    /// every few minutes under load, so this has to be called more often than that.
+    pub(crate) fn joules(&mut self) -> Option<f64> {
+        self.0.joules()
+    }
+}
+
+/// Joules per million of `searched` seeds and the average watts over `elapsed` seconds,
+/// e.g. `; 12.3 J per million seeds at 95 W`
+pub(crate) fn energy_line(joules: f64, searched: u64, elapsed: f64) -> String {
+    format!(
+        "; {:.1} J per million seeds at {:.0} W",
+        joules * 1e6 / searched.max(1) as f64,
+        joules / elapsed.max(f64::EPSILON)
+    )
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::{io::ErrorKind, path::PathBuf};
+
+    const POWERCAP: &str = "/sys/class/powercap";
+
+    /// A package's RAPL counter
+    struct Domain {
+        energy_uj: PathBuf,
+        /// Where the counter wraps around to 0
+        max: u64,
+        last: u64,
+    }
+
+    pub(super) struct Meter {
+        domains: Vec<Domain>,
+        microjoules: u64,
+    }
+
+    impl Meter {
+        pub(super) fn open() -> Result<Meter, String> {
+            let entries = std::fs::read_dir(POWERCAP)
+                .map_err(|e| format!("no RAPL counters in {POWERCAP}: {e}"))?;
+            let mut domains = vec![];
+            for entry in entries.flatten() {
+                // `intel-rapl:0` is a package (on AMD too), `intel-rapl:0:0` a part of it
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if !name.starts_with("intel-rapl:") || name.matches(':').count() != 1 {
+                    continue;
+                }
+                let read = |file: &str| {
+                    let path = entry.path().join(file);
+                    match std::fs::read_to_string(&path) {
+                        Ok(value) => value
+                            .trim()
+                            .parse::<u64>()
+                            .map_err(|e| format!("{}: {e}", path.display())),
+                        Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+                            Err(format!("reading {} needs root", path.display()))
+                        }
+                        Err(e) => Err(format!("{}: {e}", path.display())),
+                    }
+                };
+                domains.push(Domain {
+                    energy_uj: entry.path().join("energy_uj"),
+                    max: read("max_energy_range_uj")?,
+                    last: read("energy_uj")?,
+                });
+            }
+            if domains.is_empty() {
+                return Err(format!("no RAPL packages in {POWERCAP}"));
+            }
+            Ok(Meter {
+                domains,
+                microjoules: 0,
+            })
+        }
+
+        pub(super) fn joules(&mut self) -> Option<f64> {
+            for domain in &mut self.domains {
+                let Some(now) = std::fs::read_to_string(&domain.energy_uj)
+                    .ok()
+                    .and_then(|value| value.trim().parse::<u64>().ok())
+                else {
+                    continue;
+                };
+                self.microjoules += match now >= domain.last {
+                    true => now - domain.last,
+                    false => domain.max - domain.last + now,
+                };
+                domain.last = now;
+            }
+            Some(self.microjoules as f64 / 1e6)
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command, Stdio},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+    };
+
+    /// `powermetrics` sampling the CPU's power once a second, and the millijoules it
+    /// has reported
+    pub(super) struct Meter {
+        powermetrics: Child,
+        millijoules: Arc<AtomicU64>,
+        samples: Arc<AtomicU64>,
+    }
+
+    impl Meter {
+        pub(super) fn open() -> Result<Meter, String> {
+            let mut powermetrics = Command::new("powermetrics")
+                .args(["--samplers", "cpu_power", "-i", "1000"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|e| format!("failed to run powermetrics: {e}"))?;
+            let stdout = powermetrics.stdout.take().unwrap();
+            let millijoules = Arc::new(AtomicU64::new(0));
+            let samples = Arc::new(AtomicU64::new(0));
+            let (total, count) = (millijoules.clone(), samples.clone());
+            std::thread::spawn(move || {
+                // Each sample starts with a header saying how long it covers, e.g.
+                // `*** Sampled system activity (...) (1003.21ms elapsed) ***`
+                let mut millis = 1000.0;
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    if let Some(elapsed) = line
+                        .strip_suffix("ms elapsed) ***")
+                        .and_then(|line| line.rsplit_once('('))
+                        .and_then(|(_, elapsed)| elapsed.parse::<f64>().ok())
+                    {
+                        millis = elapsed;
+                    }
+                    // `CPU Power: 4567 mW` on Apple silicon, and `Intel energy model
+                    // derived package power (CPUs+GT+SA): 4.57W` on Intel
+                    let milliwatts = if let Some(power) = line.strip_prefix("CPU Power:") {
+                        power
+                            .trim()
+                            .trim_end_matches("mW")
+                            .trim()
+                            .parse::<f64>()
+                            .ok()
+                    } else if line.contains("package power") {
+                        line.rsplit_once(':')
+                            .and_then(|(_, power)| {
+                                power.trim().trim_end_matches('W').parse::<f64>().ok()
+                            })
+                            .map(|watts| watts * 1000.0)
+                    } else {
+                        None
+                    };
+                    if let Some(milliwatts) = milliwatts {
+                        total.fetch_add((milliwatts * millis / 1000.0) as u64, Ordering::Relaxed);
+                        count.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            });
+            Ok(Meter {
+                powermetrics,
+                millijoules,
+                samples,
+            })
+        }
+
+        /// `None` until the first sample, and for good if `powermetrics` wouldn't run (it
+        /// has to be root)
+        pub(super) fn joules(&mut self) -> Option<f64> {
+            (self.samples.load(Ordering::Relaxed) > 0)
+                .then(|| self.millijoules.load(Ordering::Relaxed) as f64 / 1000.0)
+        }
+    }
+
+    impl Drop for Meter {
+        fn drop(&mut self) {
+            let _ = self.powermetrics.kill();
+            let _ = self.powermetrics.wait();
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod imp {
+    pub(super) enum Meter {}
+
+    impl Meter {
+        pub(super) fn open() -> Result<Meter, String> {
+            Err("energy is only read on Linux and macOS".to_string())
+        }
+
+        pub(super) fn joules(&mut self) -> Option<f64> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! A C interface for driving a grind in-process (`--features cdylib`), for Python through
+//! ctypes, C++ services and anything else that can load a shared library, built with
+//! `cargo rustc --release --lib --features cdylib --crate-type cdylib`. `include/pda_grinder.h`
+//! declares it.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_int, CStr, CString},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread::JoinHandle,
+};
+
+use clap::{parser::ValueSource, CommandFactory, FromArgMatches};
+
+use crate::{
+    args::GrindArgs,
+    results::Found,
+    seeds::{parse_hex, HexBytes},
+    sinks::Channel,
+    CancellationToken, Grinder,
+};
+
+thread_local! {
+    /// Why the last call on this thread that failed did, for `pda_last_error`
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(e: String) {
+    let e = CString::new(e.replace('\0', "")).unwrap();
+    LAST_ERROR.with_borrow_mut(|last| *last = Some(e));
+}
+
+/// Why the last call on this thread that failed did, as a NUL-terminated string that
+/// stays valid until another call on the thread fails, or null if none has
+#[no_mangle]
+pub extern "C" fn pda_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|last| last.as_ref().map_or(std::ptr::null(), |e| e.as_ptr()))
+}
+
+/// A match, laid out as C lays out `PdaMatch`. Fields are only ever added at the end.
+#[repr(C)]
+pub struct PdaMatch {
+    pub pubkey: [u8; 32],
+    /// The ground seed's bytes, the first `seed_len` of them
+    pub seed: [u8; 32],
+    pub seed_len: u8,
+    pub bump: u8,
+    /// Seeds searched across all threads when this one was found
+    pub iterations: u64,
+    /// Unix seconds
+    pub found_at: u64,
+}
+
+impl From<Found> for PdaMatch {
+    fn from(found: Found) -> PdaMatch {
+        let bytes = parse_hex(&found.seed_hex)
+            .map(|HexBytes(bytes)| bytes)
+            .unwrap_or_default();
+        // PDA seeds are at most 32 bytes
+        let len = bytes.len().min(32);
+        let mut seed = [0; 32];
+        seed[..len].copy_from_slice(&bytes[..len]);
+        PdaMatch {
+            pubkey: found.key.to_bytes(),
+            seed,
+            seed_len: len as u8,
+            bump: found.bump,
+            iterations: found.iterations,
+            found_at: found.found_at,
+        }
+    }
+}
+
+/// A running grind, which C only ever sees behind a pointer
+pub struct PdaGrind {
+    matches: Receiver<Found>,
+    cancel: CancellationToken,
+    handle: JoinHandle<Result<(), String>>,
+}
+
+/// Starts a grind on threads of its own, given the `grind` command's flags (`"--owner"`,
+/// `"<PROGRAM_ID>"`, `"--target"`, `"TEMP"`, ...), which is only for `pda_grind_poll` and
+/// `pda_grind_stop`. Returns null, with `pda_last_error` saying why, if the flags don't
+/// parse or can't be ground for. Matches only go to `pda_grind_poll` unless the flags
+/// give an `--output` (or `--output-per-run`) or a `--manifest` to write to, and a
+/// `--template` or `--idl` grind needs a `--keypair-dir` rather than the current one.
+///
+/// # Safety
+///
+/// `argv` has to point to `argc` NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn pda_grind_start(argc: usize, argv: *const *const c_char) -> *mut PdaGrind {
+    let mut flags = vec!["grind".to_owned()];
+    for i in 0..argc {
+        match CStr::from_ptr(*argv.add(i)).to_str() {
+            Ok(flag) => flags.push(flag.to_owned()),
+            Err(e) => {
+                set_last_error(format!("flag {i} isn't UTF-8: {e}"));
+                return std::ptr::null_mut();
+            }
+        }
+    }
+    let parsed = GrindArgs::command()
+        .try_get_matches_from(flags)
+        .and_then(|matches| Ok((GrindArgs::from_arg_matches(&matches)?, matches)));
+    let (mut args, matches) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            set_last_error(e.to_string());
+            return std::ptr::null_mut();
+        }
+    };
+    // Results and the manifest only go where the flags say, and keypairs (which a
+    // `--template` or `--idl` grind can save) never go to a default directory
+    let defaulted = |id| matches.value_source(id) == Some(ValueSource::DefaultValue);
+    args.no_file |= defaulted("output") && !args.output_per_run;
+    args.no_manifest |= defaulted("manifest");
+    if (args.template.is_some() || args.idl.is_some()) && defaulted("keypair_dir") {
+        set_last_error(
+            "a --template or --idl grind needs a --keypair-dir to save keypairs in".into(),
+        );
+        return std::ptr::null_mut();
+    }
+
+    let (sender, matches) = mpsc::channel();
+    let cancel = CancellationToken::new();
+    let grinder = Grinder::new(args)
+        .sink(Channel(sender))
+        .cancellation_token(cancel.clone());
+    let handle = match grinder.start() {
+        Ok(handle) => handle,
+        Err(e) => {
+            set_last_error(e);
+            return std::ptr::null_mut();
+        }
+    };
+    Box::into_raw(Box::new(PdaGrind {
+        matches,
+        cancel,
+        handle,
+    }))
+}
+
+/// Takes the next match the grind found into `out`, returning 1, or 0 if there is none
+/// yet. Returns -1 once the grind is over and every match has been taken.
+///
+/// # Safety
+///
+/// `grind` has to come from `pda_grind_start`, and not be stopped yet, and `out` has to
+/// point to a `PdaMatch`.
+#[no_mangle]
+pub unsafe extern "C" fn pda_grind_poll(grind: *mut PdaGrind, out: *mut PdaMatch) -> c_int {
+    match (*grind).matches.try_recv() {
+        Ok(found) => {
+            out.write(found.into());
+            1
+        }
+        Err(TryRecvError::Empty) => 0,
+        Err(TryRecvError::Disconnected) => -1,
+    }
+}
+
+/// Stops the grind, as a Ctrl-C stops the command, waits for it to write out its
+/// matches and frees it. Matches that weren't polled are dropped. Returns 0, or -1 with
+/// `pda_last_error` saying why if the grind failed partway (a GPU failing). Does nothing
+/// given null.
+///
+/// # Safety
+///
+/// `grind` has to be null or come from `pda_grind_start`, and not be stopped already.
+#[no_mangle]
+pub unsafe extern "C" fn pda_grind_stop(grind: *mut PdaGrind) -> c_int {
+    if grind.is_null() {
+        return 0;
+    }
+    let grind = Box::from_raw(grind);
+    grind.cancel.cancel();
+    match grind.handle.join() {
+        Ok(Ok(())) => 0,
+        Ok(Err(e)) => {
+            set_last_error(e);
+            -1
+        }
+        Err(_) => {
+            set_last_error("the grind panicked".into());
+            -1
+        }
+    }
+}
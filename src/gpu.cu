@@ -0,0 +1,304 @@
+// `--gpu cuda`'s kernel, compiled by NVRTC when the grind starts. Everything about the
+// preimage's layout is #defined by `gpu.rs`, so that the compiler can fold the seed and
+// bump writes into the message words:
+//
+// TAIL_BLOCKS   blocks of the padded preimage from the one holding the ground seed on
+// COUNTER_POS   where the counter bytes start in those blocks
+// COUNTER_LEN   how many counter bytes there are (up to 8)
+// BIG_ENDIAN    whether they are written most significant first
+// BUMP_POS      where the bump byte is in those blocks
+// LOOKAHEAD     how many bumps, from 255 down, are hashed per seed
+
+__constant__ unsigned int K[64] = {
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+};
+
+__device__ __forceinline__ unsigned int rotr(unsigned int x, unsigned int n) {
+    return (x >> n) | (x << (32 - n));
+}
+
+__device__ __forceinline__ void compress(unsigned int state[8], const unsigned int block[16]) {
+    unsigned int w[64];
+#pragma unroll
+    for (int i = 0; i < 16; i++) {
+        w[i] = block[i];
+    }
+#pragma unroll
+    for (int i = 16; i < 64; i++) {
+        unsigned int s0 = rotr(w[i - 15], 7) ^ rotr(w[i - 15], 18) ^ (w[i - 15] >> 3);
+        unsigned int s1 = rotr(w[i - 2], 17) ^ rotr(w[i - 2], 19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16] + s0 + w[i - 7] + s1;
+    }
+    unsigned int a = state[0], b = state[1], c = state[2], d = state[3];
+    unsigned int e = state[4], f = state[5], g = state[6], h = state[7];
+#pragma unroll
+    for (int i = 0; i < 64; i++) {
+        unsigned int t1 = h + (rotr(e, 6) ^ rotr(e, 11) ^ rotr(e, 25)) + ((e & f) ^ (~e & g)) + K[i] + w[i];
+        unsigned int t2 = (rotr(a, 2) ^ rotr(a, 13) ^ rotr(a, 22)) + ((a & b) ^ (a & c) ^ (b & c));
+        h = g;
+        g = f;
+        f = e;
+        e = d + t1;
+        d = c;
+        c = b;
+        b = a;
+        a = t1 + t2;
+    }
+    state[0] += a;
+    state[1] += b;
+    state[2] += c;
+    state[3] += d;
+    state[4] += e;
+    state[5] += f;
+    state[6] += g;
+    state[7] += h;
+}
+
+// Sets byte `pos` of the big-endian message words
+__device__ __forceinline__ void set_byte(unsigned int *words, unsigned int pos, unsigned int byte) {
+    unsigned int shift = 24 - 8 * (pos % 4);
+    words[pos / 4] = (words[pos / 4] & ~(0xffu << shift)) | (byte << shift);
+}
+
+// Whether the hash is in one of the inclusive, big-endian `ranges` (a low and a high
+// hash to each)
+__device__ __forceinline__ bool in_ranges(const unsigned int hash[8], const unsigned int *ranges, unsigned int n_ranges) {
+    for (unsigned int r = 0; r < n_ranges; r++) {
+        const unsigned int *lo = ranges + 16 * r;
+        const unsigned int *hi = lo + 8;
+        bool above_lo = true, below_hi = true;
+        for (int i = 0; i < 8; i++) {
+            if (hash[i] != lo[i]) {
+                above_lo = hash[i] > lo[i];
+                break;
+            }
+        }
+        for (int i = 0; i < 8; i++) {
+            if (hash[i] != hi[i]) {
+                below_hi = hash[i] < hi[i];
+                break;
+            }
+        }
+        if (above_lo && below_hi) {
+            return true;
+        }
+    }
+    return false;
+}
+
+// Field elements mod p = 2^255 - 19 for the curve check, as eight little-endian words.
+// They are kept below 2^255, though not always below p. Only candidates in range get
+// curve checked, so this goes for short code over fast code.
+
+// d = -121665/121666, the curve constant
+__constant__ unsigned int D[8] = {
+    0x135978a3, 0x75eb4dca, 0x4141d8ab, 0x00700a4d, 0x7779e898, 0x8cc74079, 0x2b6ffe73, 0x52036cee,
+};
+
+// p - 1, which is also -1
+__constant__ unsigned int MINUS_ONE[8] = {
+    0xffffffec, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0x7fffffff,
+};
+
+// Takes `x` (with `top` over its 256 bits) back below 2^255, as 2^255 = 19
+__device__ __forceinline__ void fe_fold(unsigned int x[8], unsigned int top) {
+    for (int pass = 0; pass < 2; pass++) {
+        unsigned long long carry = 19ull * (top << 1 | x[7] >> 31);
+        x[7] &= 0x7fffffff;
+        for (int i = 0; i < 8; i++) {
+            carry += x[i];
+            x[i] = (unsigned int)carry;
+            carry >>= 32;
+        }
+        top = 0;
+    }
+}
+
+__device__ __forceinline__ void fe_add(unsigned int out[8], const unsigned int a[8], const unsigned int b[8]) {
+    unsigned long long carry = 0;
+    for (int i = 0; i < 8; i++) {
+        carry += (unsigned long long)a[i] + b[i];
+        out[i] = (unsigned int)carry;
+        carry >>= 32;
+    }
+    fe_fold(out, (unsigned int)carry);
+}
+
+__device__ __forceinline__ void fe_mul(unsigned int out[8], const unsigned int a[8], const unsigned int b[8]) {
+    unsigned int t[16] = {0};
+    for (int i = 0; i < 8; i++) {
+        unsigned long long carry = 0;
+        for (int j = 0; j < 8; j++) {
+            carry += (unsigned long long)a[i] * b[j] + t[i + j];
+            t[i + j] = (unsigned int)carry;
+            carry >>= 32;
+        }
+        t[i + 8] = (unsigned int)carry;
+    }
+    // 2^256 = 38
+    unsigned long long carry = 0;
+    for (int i = 0; i < 8; i++) {
+        carry += (unsigned long long)t[i + 8] * 38 + t[i];
+        out[i] = (unsigned int)carry;
+        carry >>= 32;
+    }
+    fe_fold(out, (unsigned int)carry);
+}
+
+// x^(2^k)
+__device__ __forceinline__ void fe_pow2k(unsigned int out[8], const unsigned int x[8], int k) {
+    fe_mul(out, x, x);
+    for (int i = 1; i < k; i++) {
+        fe_mul(out, out, out);
+    }
+}
+
+// Whether the candidate `hash` is off the curve, i.e. a valid PDA: whether
+// `(y² - 1)·(d·y² + 1)` is a non-square, by Euler's criterion, as `curve.rs` does for
+// AVX-512
+__device__ __noinline__ bool off_curve(const unsigned int hash[8]) {
+    // The candidate's bytes are the hash words big-endian, y is them little-endian
+    // without the top bit
+    unsigned int y[8];
+    for (int i = 0; i < 8; i++) {
+        unsigned int w = hash[i];
+        y[i] = w >> 24 | (w >> 8 & 0xff00) | (w << 8 & 0xff0000) | w << 24;
+    }
+    y[7] &= 0x7fffffff;
+    unsigned int one[8] = {1, 0, 0, 0, 0, 0, 0, 0};
+    unsigned int yy[8], u[8], v[8], x[8];
+    fe_mul(yy, y, y);
+    fe_add(u, yy, MINUS_ONE);
+    fe_mul(v, yy, D);
+    fe_add(v, v, one);
+    fe_mul(x, u, v);
+
+    // x^((p - 1) / 2) = x^(2^254 - 10), by the usual inversion chain up to x^(2^250 - 1)
+    unsigned int x2[8], x9[8], x11[8], e5[8], e10[8], e20[8], e40[8], e50[8], e100[8], t[8];
+    fe_mul(x2, x, x);
+    fe_pow2k(t, x2, 2);
+    fe_mul(x9, x, t);
+    fe_mul(x11, x2, x9);
+    fe_mul(t, x11, x11);
+    fe_mul(e5, x9, t);
+    fe_pow2k(t, e5, 5);
+    fe_mul(e10, t, e5);
+    fe_pow2k(t, e10, 10);
+    fe_mul(e20, t, e10);
+    fe_pow2k(t, e20, 20);
+    fe_mul(e40, t, e20);
+    fe_pow2k(t, e40, 10);
+    fe_mul(e50, t, e10);
+    fe_pow2k(t, e50, 50);
+    fe_mul(e100, t, e50);
+    fe_pow2k(t, e100, 100);
+    fe_mul(t, t, e100);
+    fe_pow2k(t, t, 50);
+    fe_mul(t, t, e50);
+    // x^(2^254 - 16) * x^6
+    fe_pow2k(t, t, 4);
+    fe_mul(x, x2, x2);
+    fe_mul(x, x, x2);
+    fe_mul(t, t, x);
+
+    // Below 2^255, -1 has no other form
+    for (int i = 0; i < 8; i++) {
+        if (t[i] != MINUS_ONE[i]) {
+            return false;
+        }
+    }
+    return true;
+}
+
+// The hash of the candidate `offset` bumps down from 255
+__device__ __forceinline__ void candidate(unsigned int state[8], const unsigned int *preimage, unsigned int *words, unsigned int offset) {
+    set_byte(words, BUMP_POS, 255 - offset);
+#pragma unroll
+    for (int i = 0; i < 8; i++) {
+        state[i] = preimage[i];
+    }
+#pragma unroll
+    for (int b = 0; b < TAIL_BLOCKS; b++) {
+        compress(state, words + 16 * b);
+    }
+}
+
+// `preimage` is the hash state after the blocks before the counter, then the rest of the
+// padded preimage as big-endian words.
+//
+// Hashes the candidates of `chunks` chunks of seeds, `chunk_len` to a chunk, one seed a
+// thread. The seeds of chunk `c` follow `chunk_starts[c]`, and there are
+// `chunk_lens[c]` of them. A seed whose canonical bump is among its candidates and has
+// its candidate in `ranges` goes into `hits` as `(c * chunk_len + k) << 8 | bump offset`
+// for the `k`th seed of chunk `c`; `hit_count` counts them all, including any past
+// `max_hits`.
+extern "C" __global__ void grind(
+    const unsigned int *preimage,
+    const unsigned long long *chunk_starts,
+    const unsigned int *chunk_lens,
+    unsigned int chunk_len,
+    unsigned int chunks,
+    const unsigned int *ranges,
+    unsigned int n_ranges,
+    unsigned long long *hits,
+    unsigned int *hit_count,
+    unsigned int max_hits
+) {
+    unsigned long long total = (unsigned long long)chunks * chunk_len;
+    unsigned long long stride = (unsigned long long)gridDim.x * blockDim.x;
+    for (unsigned long long g = (unsigned long long)blockIdx.x * blockDim.x + threadIdx.x; g < total; g += stride) {
+        unsigned int c = g / chunk_len;
+        unsigned int k = g % chunk_len;
+        if (k >= chunk_lens[c]) {
+            continue;
+        }
+        unsigned long long seed = chunk_starts[c] + 1 + k;
+
+        unsigned int words[16 * TAIL_BLOCKS];
+#pragma unroll
+        for (int i = 0; i < 16 * TAIL_BLOCKS; i++) {
+            words[i] = preimage[8 + i];
+        }
+#pragma unroll
+        for (int j = 0; j < COUNTER_LEN; j++) {
+            int shift = BIG_ENDIAN ? 8 * (COUNTER_LEN - 1 - j) : 8 * j;
+            set_byte(words, COUNTER_POS + j, (seed >> shift) & 0xff);
+        }
+
+        // A candidate in range is canonical if it is off the curve and the ones before it
+        // aren't. Those are only curve checked (and hashed again) then, and only once.
+        unsigned int on_curve = 0;
+        for (unsigned int offset = 0; offset < LOOKAHEAD; offset++) {
+            unsigned int state[8];
+            candidate(state, preimage, words, offset);
+            if (!in_ranges(state, ranges, n_ranges)) {
+                continue;
+            }
+            for (; on_curve < offset; on_curve++) {
+                unsigned int before[8];
+                candidate(before, preimage, words, on_curve);
+                if (off_curve(before)) {
+                    break;
+                }
+            }
+            if (on_curve < offset) {
+                break;
+            }
+            if (off_curve(state)) {
+                unsigned int slot = atomicAdd(hit_count, 1);
+                if (slot < max_hits) {
+                    hits[slot] = g << 8 | offset;
+                }
+                break;
+            }
+            on_curve = offset + 1;
+        }
+    }
+}
@@ -0,0 +1,1025 @@
+//! Grinding on a GPU (`--gpu`). The GPU hashes every seed's candidates, curve checks the
+//! few whose hash is in one of the target's raw ranges (and the ones before them) just as
+//! a `hash-first` grind on the CPU does, and only sends back those at their seed's
+//! canonical bump, so that nothing but those has to cross the bus. The CPU then derives
+//! them again, in case the kernel got one wrong, and encodes and matches them.
+//!
+//! Each backend is behind a feature of its own. `cuda` loads the CUDA driver and NVRTC at
+//! runtime, so a build with it still runs on machines without them (only `--gpu cuda`
+//! fails there). `wgpu` runs a WGSL port of the kernel on whatever Vulkan, Metal, DX12 or
+//! OpenGL the machine has, without any vendor SDK, and `vulkan` builds wgpu with Vulkan
+//! alone for `--gpu vulkan`. A GPU whose device is lost under wgpu (to a driver reset,
+//! say) is set up again and given the same seeds over.
+
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use sha2::digest::generic_array::GenericArray;
+use tracing::warn;
+
+use crate::sha256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Gpu {
+    /// NVIDIA GPUs, through the CUDA driver
+    Cuda,
+    /// Any GPU with Vulkan, Metal, DX12 or OpenGL compute shaders
+    Wgpu,
+    /// GPUs with a Vulkan driver, through Vulkan alone
+    Vulkan,
+}
+
+impl Gpu {
+    /// `--gpu` with this backend, for messages
+    pub(crate) fn flag(self) -> String {
+        format!("--gpu {}", self.to_possible_value().unwrap().get_name())
+    }
+
+    fn unsupported(self) -> String {
+        let name = self.to_possible_value().unwrap();
+        let name = name.get_name();
+        format!("this build can't grind with --gpu {name}, rebuild it with `--features {name}`")
+    }
+}
+
+/// Which of the GPUs `--gpu` finds to hash on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpuDevice {
+    /// Every one of them (but software renderers), each grinding seeds of its own
+    All,
+    Index(usize),
+}
+
+impl GpuDevice {
+    /// The indices of the GPUs of the `gpu` backend this picks
+    pub(crate) fn indices(self, gpu: Gpu) -> Result<Vec<usize>, String> {
+        match (self, gpu) {
+            (GpuDevice::Index(index), _) => Ok(vec![index]),
+            #[cfg(feature = "cuda")]
+            (GpuDevice::All, Gpu::Cuda) => cuda::devices(),
+            #[cfg(feature = "wgpu")]
+            (GpuDevice::All, Gpu::Wgpu) => webgpu::devices(gpu),
+            #[cfg(any(feature = "wgpu", feature = "vulkan"))]
+            (GpuDevice::All, Gpu::Vulkan) => webgpu::devices(gpu),
+            #[allow(unreachable_patterns)]
+            _ => Err(gpu.unsupported()),
+        }
+    }
+}
+
+/// A line about each of the GPUs the `gpu` backend finds, in `--gpu-device` order
+pub(crate) fn list(gpu: Gpu) -> Result<Vec<String>, String> {
+    match gpu {
+        #[cfg(feature = "cuda")]
+        Gpu::Cuda => cuda::list(),
+        #[cfg(feature = "wgpu")]
+        Gpu::Wgpu => Ok(webgpu::list(gpu)),
+        #[cfg(any(feature = "wgpu", feature = "vulkan"))]
+        Gpu::Vulkan => Ok(webgpu::list(gpu)),
+        #[allow(unreachable_patterns)]
+        _ => Err(gpu.unsupported()),
+    }
+}
+
+/// Most blocks of the padded preimage the kernel hashes per candidate, from the one
+/// holding the ground seed on (the ones before it are hashed once, up front)
+const MAX_TAIL_BLOCKS: usize = 4;
+
+/// Threads per block (or workgroup) `tune` tries, those the GPU can launch the kernel
+/// with anyway
+#[cfg_attr(
+    not(any(feature = "cuda", feature = "wgpu", feature = "vulkan")),
+    allow(dead_code)
+)]
+const BLOCK_SIZES: [u32; 5] = [64, 128, 256, 512, 1024];
+
+/// Threads per block before `tune` picks
+#[cfg_attr(
+    not(any(feature = "cuda", feature = "wgpu", feature = "vulkan")),
+    allow(dead_code)
+)]
+const DEFAULT_BLOCK_SIZE: u32 = 256;
+
+/// How long `tune` makes launches take: long enough that launching costs next to
+/// nothing, and short enough that Ctrl-C and `--duration` aren't kept waiting
+const LAUNCH_TIME: Duration = Duration::from_millis(250);
+
+/// Most seeds in a launch, so that their indices fit in a `u32`
+const MAX_LAUNCH_SEEDS: u64 = 1 << 31;
+
+/// How many times in a row a lost GPU is set up again before giving up on it, waiting
+/// `RECOVERY_WAIT` longer each time for its driver to come back
+const MAX_RECOVERIES: u64 = 5;
+const RECOVERY_WAIT: Duration = Duration::from_secs(2);
+
+/// Where a run's preimages change from seed to seed, which is all the kernel needs to
+/// know about them
+#[derive(Clone)]
+pub(crate) struct Layout {
+    /// The first seed's preimage, at bump 255
+    pub(crate) preimage: Vec<u8>,
+    /// Where the (up to) 8 counter bytes of the ground seed are
+    pub(crate) counter_pos: usize,
+    pub(crate) counter_len: usize,
+    pub(crate) big_endian: bool,
+    pub(crate) bump_pos: usize,
+    /// How many bumps (from 255 down) are hashed per seed
+    pub(crate) lookahead: usize,
+}
+
+impl Layout {
+    /// Writes `seed`'s counter bytes and the bump `offset` down from 255 into `preimage`
+    pub(crate) fn write(&self, preimage: &mut [u8], seed: u64, offset: u8) {
+        let counter = &mut preimage[self.counter_pos..self.counter_pos + self.counter_len];
+        if self.big_endian {
+            counter.copy_from_slice(&seed.to_be_bytes()[8 - self.counter_len..]);
+        } else {
+            counter.copy_from_slice(&seed.to_le_bytes()[..self.counter_len]);
+        }
+        preimage[self.bump_pos] = u8::MAX - offset;
+    }
+
+    /// The hash state after the blocks before the counter, and the rest of the padded
+    /// preimage as big-endian words, or `None` if the rest is more than
+    /// `MAX_TAIL_BLOCKS` long
+    #[cfg_attr(
+        not(any(feature = "cuda", feature = "wgpu", feature = "vulkan")),
+        allow(dead_code)
+    )]
+    fn split(&self) -> Option<([u32; 8], Vec<u32>)> {
+        let len = self.preimage.len();
+        let mut padded = self.preimage.clone();
+        padded.resize(sha256::padded_len(len), 0);
+        padded[len] = 0x80;
+        let bit_len = (len as u64 * 8).to_be_bytes();
+        let padded_len = padded.len();
+        padded[padded_len - 8..].copy_from_slice(&bit_len);
+
+        let (head, tail) = padded.split_at(self.counter_pos / 64 * 64);
+        if tail.len() > MAX_TAIL_BLOCKS * 64 {
+            return None;
+        }
+        let mut midstate = sha256::H;
+        for block in head.chunks_exact(64) {
+            sha2::compress256(&mut midstate, &[*GenericArray::from_slice(block)]);
+        }
+        let words = tail
+            .chunks_exact(4)
+            .map(|word| u32::from_be_bytes(word.try_into().unwrap()))
+            .collect();
+        Some((midstate, words))
+    }
+
+    /// `#define`s for the kernel, see `gpu.cu`
+    #[cfg_attr(
+        not(any(feature = "cuda", feature = "wgpu", feature = "vulkan")),
+        allow(dead_code)
+    )]
+    fn defines(&self, tail_words: usize) -> Vec<String> {
+        let skip = self.counter_pos / 64 * 64;
+        [
+            ("TAIL_BLOCKS", tail_words / 16),
+            ("COUNTER_POS", self.counter_pos - skip),
+            ("COUNTER_LEN", self.counter_len),
+            ("BIG_ENDIAN", self.big_endian as usize),
+            ("BUMP_POS", self.bump_pos - skip),
+            ("LOOKAHEAD", self.lookahead),
+        ]
+        .iter()
+        .map(|(name, value)| format!("-D{name}={value}"))
+        .collect()
+    }
+}
+
+/// Raw ranges (inclusive, big-endian) as the kernel takes them: the words of the low
+/// end, then of the high end
+#[cfg_attr(
+    not(any(feature = "cuda", feature = "wgpu", feature = "vulkan")),
+    allow(dead_code)
+)]
+fn range_words(ranges: &[([u8; 32], [u8; 32])]) -> Vec<u32> {
+    ranges
+        .iter()
+        .flat_map(|(lo, hi)| lo.chunks_exact(4).chain(hi.chunks_exact(4)))
+        .map(|word| u32::from_be_bytes(word.try_into().unwrap()))
+        .collect()
+}
+
+/// A GPU backend, hashing the candidates of chunks of seeds
+trait Backend: Send {
+    /// Which GPU this is
+    fn name(&self) -> &str;
+
+    /// The block sizes worth timing, of `BLOCK_SIZES`
+    fn block_sizes(&self) -> Vec<u32>;
+
+    /// Launches blocks of `block_size` threads from now on
+    fn set_block_size(&mut self, block_size: u32);
+
+    /// Whether the GPU was lost (reset by its driver, say) since it was set up, so that
+    /// it has to be set up again
+    fn lost(&self) -> bool;
+
+    /// The index (the `k`th seed of the `c`th chunk being `c * chunk_len + k`) and bump
+    /// offset of each canonical candidate of `chunks` in range
+    fn grind(&mut self, chunks: &[(u64, u64)]) -> Result<Vec<(u32, u8)>, String>;
+}
+
+/// What `GpuGrinder::tune` settled on
+pub(crate) struct Tuning {
+    pub(crate) block_size: u32,
+    /// Chunks of seeds to a launch
+    pub(crate) chunks: usize,
+}
+
+/// Sets up a backend from scratch, as when a GPU is first picked or comes back after being
+/// lost
+type Setup = Box<dyn Fn() -> Result<Box<dyn Backend>, String> + Send>;
+
+/// Hashes the candidates of chunks of seeds on the GPU `--gpu` picked
+pub(crate) struct GpuGrinder {
+    backend: Box<dyn Backend>,
+    setup: Setup,
+    block_size: Option<u32>,
+    chunk_len: u64,
+}
+
+impl GpuGrinder {
+    /// Sets up the `device`th GPU of the `gpu` backend to grind seeds laid out as in
+    /// `layout`, `chunk_len` to a chunk, for candidates in `ranges`
+    #[cfg_attr(
+        not(any(feature = "cuda", feature = "wgpu", feature = "vulkan")),
+        allow(unused_variables)
+    )]
+    pub(crate) fn new(
+        gpu: Gpu,
+        device: usize,
+        layout: &Layout,
+        ranges: &[([u8; 32], [u8; 32])],
+        chunk_len: u64,
+    ) -> Result<GpuGrinder, String> {
+        let (midstate, tail) = layout
+            .split()
+            .ok_or("the seeds after the ground one are too long for --gpu")?;
+        let mut preimage = midstate.to_vec();
+        preimage.extend(&tail);
+        let ranges = range_words(ranges);
+        let layout = layout.clone();
+        let setup: Setup = Box::new(move || match gpu {
+            #[cfg(feature = "cuda")]
+            Gpu::Cuda => cuda::CudaGrinder::new(device, &layout, &preimage, &ranges, chunk_len)
+                .map(|grinder| Box::new(grinder) as _),
+            #[cfg(feature = "wgpu")]
+            Gpu::Wgpu => {
+                webgpu::WgpuGrinder::new(gpu, device, &layout, &preimage, &ranges, chunk_len)
+                    .map(|grinder| Box::new(grinder) as _)
+            }
+            #[cfg(any(feature = "wgpu", feature = "vulkan"))]
+            Gpu::Vulkan => {
+                webgpu::WgpuGrinder::new(gpu, device, &layout, &preimage, &ranges, chunk_len)
+                    .map(|grinder| Box::new(grinder) as _)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(gpu.unsupported()),
+        });
+        Ok(GpuGrinder {
+            backend: setup()?,
+            setup,
+            block_size: None,
+            chunk_len,
+        })
+    }
+
+    /// Which GPU this is
+    pub(crate) fn name(&self) -> &str {
+        self.backend.name()
+    }
+
+    /// Times launches of throwaway seeds to pick the block size the GPU hashes fastest
+    /// with, since that differs a lot from one GPU to the next, and (unless `chunks`
+    /// fixes it) how many chunks to launch at once for a launch to take `LAUNCH_TIME`
+    pub(crate) fn tune(&mut self, chunks: Option<usize>) -> Result<Tuning, String> {
+        let max_chunks = (MAX_LAUNCH_SEEDS / self.chunk_len) as usize;
+
+        // Enough chunks for a launch to take long enough to tell block sizes apart
+        let mut trial = 1;
+        let mut elapsed = self.time(trial)?;
+        while elapsed < LAUNCH_TIME / 8 && trial < max_chunks {
+            trial = (trial * 2).min(max_chunks);
+            elapsed = self.time(trial)?;
+        }
+
+        let block_sizes = self.backend.block_sizes();
+        let mut fastest = (block_sizes[0], elapsed);
+        if block_sizes.len() > 1 {
+            fastest.1 = Duration::MAX;
+            for size in block_sizes {
+                self.backend.set_block_size(size);
+                // The better of two, as the first launch after a change can be slow
+                let time = self.time(trial)?.min(self.time(trial)?);
+                if time < fastest.1 {
+                    fastest = (size, time);
+                }
+            }
+        }
+        let (block_size, elapsed) = fastest;
+        self.backend.set_block_size(block_size);
+        self.block_size = Some(block_size);
+
+        let chunks = chunks.unwrap_or_else(|| {
+            let scale = LAUNCH_TIME.as_secs_f64() / elapsed.as_secs_f64();
+            (trial as f64 * scale) as usize
+        });
+        Ok(Tuning {
+            block_size,
+            chunks: chunks.clamp(1, max_chunks),
+        })
+    }
+
+    /// How long a launch of `chunks` chunks of throwaway seeds takes
+    fn time(&mut self, chunks: usize) -> Result<Duration, String> {
+        let chunks: Vec<_> = (0..chunks as u64)
+            .map(|chunk| (chunk * self.chunk_len, self.chunk_len))
+            .collect();
+        let started = Instant::now();
+        self.backend.grind(&chunks)?;
+        Ok(started.elapsed())
+    }
+
+    /// Hashes the candidates of `chunks` (each one before its first seed and how many
+    /// seeds it has, as the grind's cursor hands them out) and returns the seeds and
+    /// bump offsets of the canonical ones in range
+    pub(crate) fn grind(&mut self, chunks: &[(u64, u64)]) -> Result<Vec<(u64, u8)>, String> {
+        let mut recoveries = 0;
+        let hits = loop {
+            let mut error = match self.backend.grind(chunks) {
+                Ok(hits) => break hits,
+                Err(e) if self.backend.lost() => e,
+                Err(e) => return Err(e),
+            };
+            // `chunks` are still this thread's to grind, so they are launched again once
+            // the GPU is back, and the cursor doesn't skip a seed
+            loop {
+                if recoveries == MAX_RECOVERIES {
+                    return Err(format!("{} was lost: {error}", self.name()));
+                }
+                recoveries += 1;
+                warn!(
+                    "{} was lost ({error}), setting it up again in {}s",
+                    self.name(),
+                    RECOVERY_WAIT.as_secs() * recoveries
+                );
+                std::thread::sleep(RECOVERY_WAIT * recoveries as u32);
+                match (self.setup)() {
+                    Ok(backend) => {
+                        self.backend = backend;
+                        if let Some(block_size) = self.block_size {
+                            self.backend.set_block_size(block_size);
+                        }
+                        break;
+                    }
+                    Err(e) => error = e,
+                }
+            }
+        };
+        Ok(hits
+            .into_iter()
+            .map(|(index, offset)| {
+                let (chunk, k) = (index as u64 / self.chunk_len, index as u64 % self.chunk_len);
+                (chunks[chunk as usize].0.wrapping_add(1 + k), offset)
+            })
+            .collect())
+    }
+}
+
+#[cfg(feature = "cuda")]
+mod cuda {
+    use std::sync::Arc;
+
+    use cudarc::{
+        driver::{CudaContext, CudaFunction, CudaSlice, CudaStream, LaunchConfig, PushKernelArg},
+        nvrtc::{compile_ptx_with_opts, CompileOptions},
+    };
+
+    use super::{Backend, Layout, BLOCK_SIZES, DEFAULT_BLOCK_SIZE};
+
+    /// Loading a missing library would panic, so this checks for them first
+    fn check_libraries() -> Result<(), String> {
+        // SAFETY: only loads and unloads the libraries
+        if !unsafe { cudarc::driver::sys::is_culib_present() } {
+            return Err("--gpu cuda needs the CUDA driver, which isn't installed".into());
+        }
+        // SAFETY: as above
+        if !unsafe { cudarc::nvrtc::sys::is_culib_present() } {
+            return Err(
+                "--gpu cuda needs NVRTC (from the CUDA toolkit) to compile its kernel, which \
+                 isn't installed"
+                    .into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Every CUDA GPU there is
+    pub(crate) fn devices() -> Result<Vec<usize>, String> {
+        check_libraries()?;
+        let count = CudaContext::device_count()
+            .map_err(|e| format!("failed to count the CUDA GPUs: {e}"))?;
+        if count == 0 {
+            return Err("--gpu cuda found no GPU".into());
+        }
+        Ok((0..count as usize).collect())
+    }
+
+    /// The name, memory and compute capability of every CUDA GPU there is
+    pub(crate) fn list() -> Result<Vec<String>, String> {
+        devices()?
+            .into_iter()
+            .map(|device| {
+                let error = |e| format!("failed to query CUDA GPU {device}: {e}");
+                let context = CudaContext::new(device).map_err(error)?;
+                let name = context.name().map_err(error)?;
+                let (major, minor) = context.compute_capability().map_err(error)?;
+                // SAFETY: the context's device came from the driver
+                let memory =
+                    unsafe { cudarc::driver::result::device::total_mem(context.cu_device()) }
+                        .map_err(error)?;
+                Ok(format!(
+                    "{name}, {:.1} GiB, compute capability {major}.{minor}",
+                    memory as f64 / (1 << 30) as f64
+                ))
+            })
+            .collect()
+    }
+
+    pub(crate) struct CudaGrinder {
+        name: String,
+        chunk_len: u32,
+        stream: Arc<CudaStream>,
+        kernel: CudaFunction,
+        /// Threads per block, and the most the kernel can have
+        block_size: u32,
+        max_block_size: u32,
+        preimage: CudaSlice<u32>,
+        ranges: CudaSlice<u32>,
+        n_ranges: u32,
+        /// Room for this many hits per launch, grown whenever a launch has more
+        max_hits: u32,
+        hits: CudaSlice<u64>,
+        hit_count: CudaSlice<u32>,
+    }
+
+    impl CudaGrinder {
+        /// Compiles the kernel for `layout` and uploads what stays the same between
+        /// launches. Fails if there is no CUDA driver or such GPU, or NVRTC is missing.
+        pub(crate) fn new(
+            device: usize,
+            layout: &Layout,
+            preimage: &[u32],
+            ranges: &[u32],
+            chunk_len: u64,
+        ) -> Result<CudaGrinder, String> {
+            check_libraries()?;
+            let context = CudaContext::new(device)
+                .map_err(|e| format!("no CUDA GPU {device} to use: {e}"))?;
+            let name = context
+                .name()
+                .map_err(|e| format!("failed to get the name of CUDA GPU {device}: {e}"))?;
+            let options = CompileOptions {
+                options: layout.defines(preimage.len() - 8),
+                ..Default::default()
+            };
+            let ptx = compile_ptx_with_opts(include_str!("gpu.cu"), options)
+                .map_err(|e| format!("failed to compile the CUDA kernel: {e}"))?;
+            let kernel = context
+                .load_module(ptx)
+                .and_then(|module| module.load_function("grind"))
+                .map_err(|e| format!("failed to load the CUDA kernel: {e}"))?;
+            let max_block_size = kernel
+                .max_threads_per_block()
+                .map_err(|e| format!("failed to load the CUDA kernel: {e}"))?
+                as u32;
+
+            let stream = context.default_stream();
+            let upload_error = |e| format!("failed to upload to the GPU: {e}");
+            let max_hits = 1024;
+            Ok(CudaGrinder {
+                name,
+                chunk_len: chunk_len as u32,
+                preimage: stream.memcpy_stod(preimage).map_err(upload_error)?,
+                ranges: stream.memcpy_stod(ranges).map_err(upload_error)?,
+                n_ranges: (ranges.len() / 16) as u32,
+                max_hits,
+                hits: stream
+                    .alloc_zeros(max_hits as usize)
+                    .map_err(upload_error)?,
+                hit_count: stream.alloc_zeros(1).map_err(upload_error)?,
+                stream,
+                kernel,
+                block_size: DEFAULT_BLOCK_SIZE.min(max_block_size),
+                max_block_size,
+            })
+        }
+    }
+
+    impl Backend for CudaGrinder {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn block_sizes(&self) -> Vec<u32> {
+            BLOCK_SIZES
+                .into_iter()
+                .filter(|size| *size <= self.max_block_size)
+                .collect()
+        }
+
+        fn set_block_size(&mut self, block_size: u32) {
+            self.block_size = block_size;
+        }
+
+        fn lost(&self) -> bool {
+            // Errors that leave the context unusable end the grind
+            false
+        }
+
+        fn grind(&mut self, chunks: &[(u64, u64)]) -> Result<Vec<(u32, u8)>, String> {
+            let error = |e| format!("CUDA error: {e}");
+            let starts: Vec<u64> = chunks.iter().map(|(start, _)| *start).collect();
+            let lens: Vec<u32> = chunks.iter().map(|(_, len)| *len as u32).collect();
+            let chunk_starts = self.stream.memcpy_stod(&starts).map_err(error)?;
+            let chunk_lens = self.stream.memcpy_stod(&lens).map_err(error)?;
+            let n_chunks = chunks.len() as u32;
+            let threads = n_chunks as u64 * self.chunk_len as u64;
+            let config = LaunchConfig {
+                grid_dim: (threads.div_ceil(self.block_size as u64) as u32, 1, 1),
+                block_dim: (self.block_size, 1, 1),
+                shared_mem_bytes: 0,
+            };
+
+            loop {
+                self.stream
+                    .memset_zeros(&mut self.hit_count)
+                    .map_err(error)?;
+                let mut launch = self.stream.launch_builder(&self.kernel);
+                launch
+                    .arg(&self.preimage)
+                    .arg(&chunk_starts)
+                    .arg(&chunk_lens)
+                    .arg(&self.chunk_len)
+                    .arg(&n_chunks)
+                    .arg(&self.ranges)
+                    .arg(&self.n_ranges)
+                    .arg(&mut self.hits)
+                    .arg(&mut self.hit_count)
+                    .arg(&self.max_hits);
+                // SAFETY: the arguments are the kernel's, in its order, and every buffer
+                // is as long as it reads or writes
+                unsafe { launch.launch(config) }.map_err(error)?;
+
+                let hit_count = self.stream.memcpy_dtov(&self.hit_count).map_err(error)?[0];
+                // Too many to keep, so grow the buffer and hash them again. Only short
+                // targets (which find plenty on the CPU) ever get here.
+                if hit_count > self.max_hits {
+                    self.max_hits = hit_count.next_power_of_two();
+                    self.hits = self
+                        .stream
+                        .alloc_zeros(self.max_hits as usize)
+                        .map_err(error)?;
+                    continue;
+                }
+                let hits = self
+                    .stream
+                    .memcpy_dtov(&self.hits.slice(..hit_count as usize))
+                    .map_err(error)?;
+                return Ok(hits
+                    .into_iter()
+                    .map(|hit| ((hit >> 8) as u32, hit as u8))
+                    .collect());
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "wgpu", feature = "vulkan"))]
+mod webgpu {
+    use std::sync::{Arc, Mutex};
+
+    use super::{Backend, Gpu, Layout, BLOCK_SIZES, DEFAULT_BLOCK_SIZE};
+
+    pub(crate) struct WgpuGrinder {
+        name: String,
+        chunk_len: u32,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        /// The shader, but for its workgroup size
+        source: String,
+        pipeline: wgpu::ComputePipeline,
+        workgroup_size: u32,
+        max_workgroup_size: u32,
+        /// Whether this is really the CPU, where compiling the shader takes a long time
+        software: bool,
+        preimage: wgpu::Buffer,
+        ranges: wgpu::Buffer,
+        n_ranges: u32,
+        /// Room for this many hits per dispatch, grown whenever a dispatch has more
+        max_hits: u32,
+        hits: wgpu::Buffer,
+        hit_count: wgpu::Buffer,
+        /// Why the device was lost, once it has been
+        lost: Arc<Mutex<Option<String>>>,
+        /// The first error wgpu raised since the last dispatch, which it would otherwise
+        /// panic on
+        error: Arc<Mutex<Option<String>>>,
+    }
+
+    /// The GPUs `gpu` can run compute shaders on, the discrete ones first and any that are
+    /// really the CPU last. `--gpu vulkan` only looks for them through Vulkan.
+    fn adapters(gpu: Gpu) -> Vec<wgpu::Adapter> {
+        let backends = match gpu {
+            Gpu::Vulkan => wgpu::Backends::VULKAN,
+            _ => wgpu::Backends::all(),
+        };
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends,
+            ..Default::default()
+        });
+        let mut adapters: Vec<_> = instance
+            .enumerate_adapters(backends)
+            .into_iter()
+            .filter(|adapter| {
+                let capabilities = adapter.get_downlevel_capabilities();
+                capabilities
+                    .flags
+                    .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            })
+            .collect();
+        // OpenGL mostly finds the same GPUs over again, so it is left for when there is
+        // nothing else
+        let gl = |adapter: &wgpu::Adapter| adapter.get_info().backend == wgpu::Backend::Gl;
+        if !adapters.iter().all(gl) {
+            adapters.retain(|adapter| !gl(adapter));
+        }
+        adapters.sort_by_key(|adapter| match adapter.get_info().device_type {
+            wgpu::DeviceType::DiscreteGpu => 0,
+            wgpu::DeviceType::IntegratedGpu => 1,
+            wgpu::DeviceType::VirtualGpu | wgpu::DeviceType::Other => 2,
+            wgpu::DeviceType::Cpu => 3,
+        });
+        adapters
+    }
+
+    /// The adapters that are real GPUs, rather than software renderers on the CPU
+    pub(crate) fn devices(gpu: Gpu) -> Result<Vec<usize>, String> {
+        let adapters = adapters(gpu);
+        let devices: Vec<_> = (0..adapters.len())
+            .filter(|&i| adapters[i].get_info().device_type != wgpu::DeviceType::Cpu)
+            .collect();
+        if devices.is_empty() {
+            return Err(match adapters.first() {
+                Some(adapter) => format!(
+                    "{} found no GPU but {}, pick it with --gpu-device 0",
+                    gpu.flag(),
+                    describe(adapter)
+                ),
+                None => format!("{} found no GPU to run compute shaders on", gpu.flag()),
+            });
+        }
+        Ok(devices)
+    }
+
+    /// The name, API, kind and driver of every adapter `--gpu-device` can pick
+    pub(crate) fn list(gpu: Gpu) -> Vec<String> {
+        adapters(gpu)
+            .iter()
+            .map(|adapter| {
+                let info = adapter.get_info();
+                let kind = match info.device_type {
+                    wgpu::DeviceType::DiscreteGpu => "discrete GPU",
+                    wgpu::DeviceType::IntegratedGpu => "integrated GPU",
+                    wgpu::DeviceType::VirtualGpu => "virtual GPU",
+                    wgpu::DeviceType::Other => "GPU",
+                    wgpu::DeviceType::Cpu => "software renderer, left out of --gpu-device all",
+                };
+                let mut line = format!("{}, {kind}", describe(adapter));
+                if !info.driver.is_empty() {
+                    line += &format!(", driver {} {}", info.driver, info.driver_info);
+                }
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    fn describe(adapter: &wgpu::Adapter) -> String {
+        let info = adapter.get_info();
+        format!("{} ({:?})", info.name, info.backend)
+    }
+
+    /// Reads `len` bytes of `buffer` back from the GPU, by way of `staging`
+    fn read_back(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        buffer: &wgpu::Buffer,
+        len: u64,
+    ) -> Result<Vec<u8>, String> {
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("read back"),
+            size: len,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&Default::default());
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, len);
+        queue.submit([encoder.finish()]);
+        let slice = staging.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device
+            .poll(wgpu::PollType::Wait)
+            .map_err(|e| format!("wgpu error: {e}"))?;
+        receiver
+            .recv()
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("failed to read back from the GPU: {e}"))?;
+        let bytes = slice.get_mapped_range().to_vec();
+        Ok(bytes)
+    }
+
+    /// Compiles `source` with `workgroup_size` threads to a workgroup
+    fn pipeline(device: &wgpu::Device, source: &str, workgroup_size: u32) -> wgpu::ComputePipeline {
+        let source = source.replace("{WORKGROUP_SIZE}", &workgroup_size.to_string());
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("gpu.wgsl"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("grind"),
+            layout: None,
+            module: &module,
+            entry_point: Some("grind"),
+            compilation_options: Default::default(),
+            cache: None,
+        })
+    }
+
+    /// A buffer holding `contents`. Unlike `create_buffer_init`, this doesn't panic if the
+    /// device was lost, which the next dispatch then reports.
+    fn upload(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        contents: &[u8],
+        usage: wgpu::BufferUsages,
+    ) -> wgpu::Buffer {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: contents.len() as u64,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&buffer, 0, contents);
+        buffer
+    }
+
+    fn to_bytes(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    impl WgpuGrinder {
+        /// Builds the shader for `layout` on the `device`th GPU and uploads what stays
+        /// the same between dispatches
+        pub(crate) fn new(
+            gpu: Gpu,
+            device: usize,
+            layout: &Layout,
+            preimage: &[u32],
+            ranges: &[u32],
+            chunk_len: u64,
+        ) -> Result<WgpuGrinder, String> {
+            let mut adapters = adapters(gpu);
+            if device >= adapters.len() {
+                let found = adapters
+                    .iter()
+                    .enumerate()
+                    .map(|(i, adapter)| format!("\n  {i}: {}", describe(adapter)))
+                    .collect::<String>();
+                return Err(match found.is_empty() {
+                    true => format!("{} found no GPU to run compute shaders on", gpu.flag()),
+                    false => format!(
+                        "there is no --gpu-device {device}, {} found{found}",
+                        gpu.flag()
+                    ),
+                });
+            }
+            let adapter = adapters.swap_remove(device);
+            let name = describe(&adapter);
+            let software = adapter.get_info().device_type == wgpu::DeviceType::Cpu;
+            let (device, queue) =
+                pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+                    label: Some("pda-grinder"),
+                    required_limits: adapter.limits(),
+                    ..Default::default()
+                }))
+                .map_err(|e| format!("failed to open {name}: {e}"))?;
+            let lost = Arc::new(Mutex::new(None));
+            device.set_device_lost_callback({
+                let lost = lost.clone();
+                move |reason, message| {
+                    let reason = match message.is_empty() {
+                        true => format!("{reason:?}"),
+                        false => format!("{reason:?}: {message}"),
+                    };
+                    *lost.lock().unwrap() = Some(reason);
+                }
+            });
+            let error = Arc::new(Mutex::new(None));
+            device.on_uncaptured_error({
+                let error = error.clone();
+                Box::new(move |e| {
+                    error.lock().unwrap().get_or_insert(e.to_string());
+                })
+            });
+
+            let limits = device.limits();
+            let max_workgroup_size = limits
+                .max_compute_invocations_per_workgroup
+                .min(limits.max_compute_workgroup_size_x);
+            let workgroup_size = DEFAULT_BLOCK_SIZE.min(max_workgroup_size);
+            let mut source = include_str!("gpu.wgsl").to_string();
+            for define in layout.defines(preimage.len() - 8) {
+                let (name, value) = define[2..].split_once('=').unwrap();
+                let value = match name {
+                    "BIG_ENDIAN" => (value == "1").to_string(),
+                    _ => value.to_string(),
+                };
+                source = source.replace(&format!("{{{name}}}"), &value);
+            }
+            let pipeline = pipeline(&device, &source, workgroup_size);
+
+            let storage = |label, contents: &[u8], usage| {
+                upload(
+                    &device,
+                    &queue,
+                    label,
+                    contents,
+                    wgpu::BufferUsages::STORAGE | usage,
+                )
+            };
+            let max_hits = 1024;
+            Ok(WgpuGrinder {
+                name,
+                chunk_len: chunk_len as u32,
+                preimage: storage("preimage", &to_bytes(preimage), wgpu::BufferUsages::empty()),
+                ranges: storage("ranges", &to_bytes(ranges), wgpu::BufferUsages::empty()),
+                n_ranges: (ranges.len() / 16) as u32,
+                max_hits,
+                hits: storage(
+                    "hits",
+                    &vec![0; max_hits as usize * 8],
+                    wgpu::BufferUsages::COPY_SRC,
+                ),
+                hit_count: storage(
+                    "hit count",
+                    &[0; 4],
+                    wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+                ),
+                source,
+                pipeline,
+                workgroup_size,
+                max_workgroup_size,
+                software,
+                device,
+                queue,
+                lost,
+                error,
+            })
+        }
+
+        /// `Backend::grind`, but for the errors wgpu raises on its own
+        fn dispatch(&mut self, chunks: &[(u64, u64)]) -> Result<Vec<(u32, u8)>, String> {
+            let starts: Vec<u32> = chunks
+                .iter()
+                .flat_map(|(start, _)| [*start as u32, (*start >> 32) as u32])
+                .collect();
+            let lens: Vec<u32> = chunks.iter().map(|(_, len)| *len as u32).collect();
+            let buffer = |label, contents: &[u8], usage| {
+                upload(&self.device, &self.queue, label, contents, usage)
+            };
+            let chunk_starts = buffer(
+                "chunk starts",
+                &to_bytes(&starts),
+                wgpu::BufferUsages::STORAGE,
+            );
+            let chunk_lens = buffer("chunk lens", &to_bytes(&lens), wgpu::BufferUsages::STORAGE);
+            let threads = chunks.len() as u32 * self.chunk_len;
+            let workgroups = threads
+                .div_ceil(self.workgroup_size)
+                .min(self.device.limits().max_compute_workgroups_per_dimension);
+
+            loop {
+                let params = [
+                    self.chunk_len,
+                    chunks.len() as u32,
+                    self.n_ranges,
+                    self.max_hits,
+                ];
+                let params = buffer("params", &to_bytes(&params), wgpu::BufferUsages::UNIFORM);
+                let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("grind"),
+                    layout: &self.pipeline.get_bind_group_layout(0),
+                    entries: &[
+                        &self.preimage,
+                        &self.ranges,
+                        &chunk_starts,
+                        &chunk_lens,
+                        &params,
+                        &self.hits,
+                        &self.hit_count,
+                    ]
+                    .iter()
+                    .enumerate()
+                    .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                        binding: binding as u32,
+                        resource: buffer.as_entire_binding(),
+                    })
+                    .collect::<Vec<_>>(),
+                });
+                self.queue.write_buffer(&self.hit_count, 0, &[0; 4]);
+                let mut encoder = self.device.create_command_encoder(&Default::default());
+                {
+                    let mut pass = encoder.begin_compute_pass(&Default::default());
+                    pass.set_pipeline(&self.pipeline);
+                    pass.set_bind_group(0, &bind_group, &[]);
+                    pass.dispatch_workgroups(workgroups, 1, 1);
+                }
+                self.queue.submit([encoder.finish()]);
+
+                let hit_count = read_back(&self.device, &self.queue, &self.hit_count, 4)?;
+                let hit_count = u32::from_le_bytes(hit_count.try_into().unwrap());
+                // Too many to keep, so grow the buffer and hash them again, as for CUDA
+                if hit_count > self.max_hits {
+                    self.max_hits = hit_count.next_power_of_two();
+                    self.hits = self.device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("hits"),
+                        size: self.max_hits as u64 * 8,
+                        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+                        mapped_at_creation: false,
+                    });
+                    continue;
+                }
+                if hit_count == 0 {
+                    return Ok(vec![]);
+                }
+                let hits = read_back(&self.device, &self.queue, &self.hits, hit_count as u64 * 8)?;
+                return Ok(hits
+                    .chunks_exact(8)
+                    .map(|hit| {
+                        let index = u32::from_le_bytes(hit[..4].try_into().unwrap());
+                        (index, hit[4])
+                    })
+                    .collect());
+            }
+        }
+    }
+
+    impl Backend for WgpuGrinder {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn block_sizes(&self) -> Vec<u32> {
+            // Each size is compiled for, which takes too long to try them all on the CPU
+            if self.software {
+                return vec![self.workgroup_size];
+            }
+            BLOCK_SIZES
+                .into_iter()
+                .filter(|size| *size <= self.max_workgroup_size)
+                .collect()
+        }
+
+        fn set_block_size(&mut self, block_size: u32) {
+            if block_size != self.workgroup_size {
+                self.pipeline = pipeline(&self.device, &self.source, block_size);
+                self.workgroup_size = block_size;
+            }
+        }
+
+        fn lost(&self) -> bool {
+            self.lost.lock().unwrap().is_some()
+        }
+
+        fn grind(&mut self, chunks: &[(u64, u64)]) -> Result<Vec<(u32, u8)>, String> {
+            let hits = self.dispatch(chunks);
+            if let Some(reason) = self.lost.lock().unwrap().clone() {
+                return Err(format!("device lost, {reason}"));
+            }
+            if let Some(e) = self.error.lock().unwrap().take() {
+                return Err(format!("wgpu error: {e}"));
+            }
+            hits
+        }
+    }
+}
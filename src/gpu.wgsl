@@ -0,0 +1,317 @@
+// `--gpu wgpu`'s shader, the WGSL twin of `gpu.cu`. WGSL has no preprocessor, so
+// `gpu.rs` fills in the layout constants below for each run (see `gpu.cu` for what they
+// are), and no 64 bit integers, so seeds and hits are pairs of 32 bit words and the
+// curve check's products are put together from 16 bit halves.
+
+const TAIL_BLOCKS: u32 = {TAIL_BLOCKS}u;
+const COUNTER_POS: u32 = {COUNTER_POS}u;
+const COUNTER_LEN: u32 = {COUNTER_LEN}u;
+const BIG_ENDIAN: bool = {BIG_ENDIAN};
+const BUMP_POS: u32 = {BUMP_POS}u;
+const LOOKAHEAD: u32 = {LOOKAHEAD}u;
+const WORKGROUP_SIZE: u32 = {WORKGROUP_SIZE}u;
+
+const TAIL_WORDS: u32 = 16u * TAIL_BLOCKS;
+
+const K = array<u32, 64>(
+    0x428a2f98u, 0x71374491u, 0xb5c0fbcfu, 0xe9b5dba5u, 0x3956c25bu, 0x59f111f1u, 0x923f82a4u, 0xab1c5ed5u,
+    0xd807aa98u, 0x12835b01u, 0x243185beu, 0x550c7dc3u, 0x72be5d74u, 0x80deb1feu, 0x9bdc06a7u, 0xc19bf174u,
+    0xe49b69c1u, 0xefbe4786u, 0x0fc19dc6u, 0x240ca1ccu, 0x2de92c6fu, 0x4a7484aau, 0x5cb0a9dcu, 0x76f988dau,
+    0x983e5152u, 0xa831c66du, 0xb00327c8u, 0xbf597fc7u, 0xc6e00bf3u, 0xd5a79147u, 0x06ca6351u, 0x14292967u,
+    0x27b70a85u, 0x2e1b2138u, 0x4d2c6dfcu, 0x53380d13u, 0x650a7354u, 0x766a0abbu, 0x81c2c92eu, 0x92722c85u,
+    0xa2bfe8a1u, 0xa81a664bu, 0xc24b8b70u, 0xc76c51a3u, 0xd192e819u, 0xd6990624u, 0xf40e3585u, 0x106aa070u,
+    0x19a4c116u, 0x1e376c08u, 0x2748774cu, 0x34b0bcb5u, 0x391c0cb3u, 0x4ed8aa4au, 0x5b9cca4fu, 0x682e6ff3u,
+    0x748f82eeu, 0x78a5636fu, 0x84c87814u, 0x8cc70208u, 0x90befffau, 0xa4506cebu, 0xbef9a3f7u, 0xc67178f2u,
+);
+
+struct Params {
+    chunk_len: u32,
+    chunks: u32,
+    n_ranges: u32,
+    max_hits: u32,
+}
+
+// The hash state after the blocks before the counter, then the rest of the padded
+// preimage as big-endian words
+@group(0) @binding(0) var<storage, read> preimage: array<u32>;
+// Inclusive, big-endian ranges: the words of the low end, then of the high end
+@group(0) @binding(1) var<storage, read> ranges: array<u32>;
+// The seed before each chunk's first, as low and high words
+@group(0) @binding(2) var<storage, read> chunk_starts: array<vec2<u32>>;
+@group(0) @binding(3) var<storage, read> chunk_lens: array<u32>;
+@group(0) @binding(4) var<uniform> params: Params;
+// `c * chunk_len + k` and the bump offset of each canonical candidate in range, for the
+// `k`th seed of chunk `c`
+@group(0) @binding(5) var<storage, read_write> hits: array<vec2<u32>>;
+// All of the candidates in range, including any past `max_hits`
+@group(0) @binding(6) var<storage, read_write> hit_count: atomic<u32>;
+
+fn rotr(x: u32, n: u32) -> u32 {
+    return (x >> n) | (x << (32u - n));
+}
+
+fn compress(state: ptr<function, array<u32, 8>>, words: ptr<function, array<u32, TAIL_WORDS>>, block: u32) {
+    var w: array<u32, 64>;
+    for (var i = 0u; i < 16u; i++) {
+        w[i] = (*words)[16u * block + i];
+    }
+    for (var i = 16u; i < 64u; i++) {
+        let s0 = rotr(w[i - 15u], 7u) ^ rotr(w[i - 15u], 18u) ^ (w[i - 15u] >> 3u);
+        let s1 = rotr(w[i - 2u], 17u) ^ rotr(w[i - 2u], 19u) ^ (w[i - 2u] >> 10u);
+        w[i] = w[i - 16u] + s0 + w[i - 7u] + s1;
+    }
+    var a = (*state)[0];
+    var b = (*state)[1];
+    var c = (*state)[2];
+    var d = (*state)[3];
+    var e = (*state)[4];
+    var f = (*state)[5];
+    var g = (*state)[6];
+    var h = (*state)[7];
+    for (var i = 0u; i < 64u; i++) {
+        let t1 = h + (rotr(e, 6u) ^ rotr(e, 11u) ^ rotr(e, 25u)) + ((e & f) ^ (~e & g)) + K[i] + w[i];
+        let t2 = (rotr(a, 2u) ^ rotr(a, 13u) ^ rotr(a, 22u)) + ((a & b) ^ (a & c) ^ (b & c));
+        h = g;
+        g = f;
+        f = e;
+        e = d + t1;
+        d = c;
+        c = b;
+        b = a;
+        a = t1 + t2;
+    }
+    (*state)[0] += a;
+    (*state)[1] += b;
+    (*state)[2] += c;
+    (*state)[3] += d;
+    (*state)[4] += e;
+    (*state)[5] += f;
+    (*state)[6] += g;
+    (*state)[7] += h;
+}
+
+// Sets byte `pos` of the big-endian message words
+fn set_byte(words: ptr<function, array<u32, TAIL_WORDS>>, pos: u32, byte: u32) {
+    let shift = 24u - 8u * (pos % 4u);
+    (*words)[pos / 4u] = ((*words)[pos / 4u] & ~(0xffu << shift)) | (byte << shift);
+}
+
+// Whether the hash is in one of the ranges
+fn in_ranges(hash: ptr<function, array<u32, 8>>) -> bool {
+    for (var r = 0u; r < params.n_ranges; r++) {
+        var above_lo = true;
+        var below_hi = true;
+        for (var i = 0u; i < 8u; i++) {
+            let lo = ranges[16u * r + i];
+            if (*hash)[i] != lo {
+                above_lo = (*hash)[i] > lo;
+                break;
+            }
+        }
+        for (var i = 0u; i < 8u; i++) {
+            let hi = ranges[16u * r + 8u + i];
+            if (*hash)[i] != hi {
+                below_hi = (*hash)[i] < hi;
+                break;
+            }
+        }
+        if above_lo && below_hi {
+            return true;
+        }
+    }
+    return false;
+}
+
+// Field elements mod p = 2^255 - 19 for the curve check, as in `gpu.cu`
+alias Fe = array<u32, 8>;
+
+// d = -121665/121666, the curve constant
+const D = Fe(0x135978a3u, 0x75eb4dcau, 0x4141d8abu, 0x00700a4du, 0x7779e898u, 0x8cc74079u, 0x2b6ffe73u, 0x52036ceeu);
+
+// p - 1, which is also -1
+const MINUS_ONE = Fe(0xffffffecu, 0xffffffffu, 0xffffffffu, 0xffffffffu, 0xffffffffu, 0xffffffffu, 0xffffffffu, 0x7fffffffu);
+
+// `a * b + c + d` as its low and high words, which it always fits
+fn mul_add(a: u32, b: u32, c: u32, d: u32) -> vec2<u32> {
+    let a0 = a & 0xffffu;
+    let a1 = a >> 16u;
+    let b0 = b & 0xffffu;
+    let b1 = b >> 16u;
+    let mid = a0 * b1 + a1 * b0;
+    let mid_carry = select(0u, 0x10000u, mid < a0 * b1);
+    let lo = a0 * b0 + (mid << 16u);
+    var hi = a1 * b1 + (mid >> 16u) + mid_carry + select(0u, 1u, lo < (mid << 16u));
+    let lo_c = lo + c;
+    hi += select(0u, 1u, lo_c < c);
+    let lo_cd = lo_c + d;
+    hi += select(0u, 1u, lo_cd < d);
+    return vec2<u32>(lo_cd, hi);
+}
+
+// Takes `x` (with `top` over its 256 bits) back below 2^255, as 2^255 = 19
+fn fe_fold(x: ptr<function, Fe>, top: u32) {
+    var over = top;
+    for (var times = 0; times < 2; times++) {
+        var carry = 19u * (over << 1u | (*x)[7] >> 31u);
+        (*x)[7] &= 0x7fffffffu;
+        for (var i = 0u; i < 8u; i++) {
+            let sum = (*x)[i] + carry;
+            carry = select(0u, 1u, sum < carry);
+            (*x)[i] = sum;
+        }
+        over = 0u;
+    }
+}
+
+fn fe_add(a_: Fe, b_: Fe) -> Fe {
+    var a = a_;
+    var b = b_;
+    var out: Fe;
+    var carry = 0u;
+    for (var i = 0u; i < 8u; i++) {
+        let sum = a[i] + b[i];
+        let total = sum + carry;
+        carry = select(0u, 1u, sum < a[i]) + select(0u, 1u, total < sum);
+        out[i] = total;
+    }
+    fe_fold(&out, carry);
+    return out;
+}
+
+fn fe_mul(a_: Fe, b_: Fe) -> Fe {
+    var a = a_;
+    var b = b_;
+    var t: array<u32, 16>;
+    for (var i = 0u; i < 8u; i++) {
+        var carry = 0u;
+        for (var j = 0u; j < 8u; j++) {
+            let product = mul_add(a[i], b[j], t[i + j], carry);
+            t[i + j] = product.x;
+            carry = product.y;
+        }
+        t[i + 8u] = carry;
+    }
+    // 2^256 = 38
+    var out: Fe;
+    var carry = 0u;
+    for (var i = 0u; i < 8u; i++) {
+        let product = mul_add(t[i + 8u], 38u, t[i], carry);
+        out[i] = product.x;
+        carry = product.y;
+    }
+    fe_fold(&out, carry);
+    return out;
+}
+
+// x^(2^k)
+fn fe_pow2k(x: Fe, k: u32) -> Fe {
+    var out = fe_mul(x, x);
+    for (var i = 1u; i < k; i++) {
+        out = fe_mul(out, out);
+    }
+    return out;
+}
+
+// Whether the candidate `hash` is off the curve, i.e. a valid PDA, by Euler's criterion
+fn off_curve(hash: ptr<function, array<u32, 8>>) -> bool {
+    // The candidate's bytes are the hash words big-endian, y is them little-endian
+    // without the top bit
+    var y: Fe;
+    for (var i = 0u; i < 8u; i++) {
+        let w = (*hash)[i];
+        y[i] = w >> 24u | (w >> 8u & 0xff00u) | (w << 8u & 0xff0000u) | w << 24u;
+    }
+    y[7] &= 0x7fffffffu;
+    let yy = fe_mul(y, y);
+    let x = fe_mul(fe_add(yy, MINUS_ONE), fe_add(fe_mul(yy, D), Fe(1u, 0u, 0u, 0u, 0u, 0u, 0u, 0u)));
+
+    // x^((p - 1) / 2) = x^(2^254 - 10), by the usual inversion chain up to x^(2^250 - 1)
+    let x2 = fe_mul(x, x);
+    let x9 = fe_mul(x, fe_pow2k(x2, 2u));
+    let x11 = fe_mul(x2, x9);
+    let e5 = fe_mul(x9, fe_mul(x11, x11));
+    let e10 = fe_mul(fe_pow2k(e5, 5u), e5);
+    let e20 = fe_mul(fe_pow2k(e10, 10u), e10);
+    let e40 = fe_mul(fe_pow2k(e20, 20u), e20);
+    let e50 = fe_mul(fe_pow2k(e40, 10u), e10);
+    let e100 = fe_mul(fe_pow2k(e50, 50u), e50);
+    let e200 = fe_mul(fe_pow2k(e100, 100u), e100);
+    let e250 = fe_mul(fe_pow2k(e200, 50u), e50);
+    // x^(2^254 - 16) * x^6
+    var symbol = fe_mul(fe_pow2k(e250, 4u), fe_mul(x2, fe_mul(x2, x2)));
+
+    // Below 2^255, -1 has no other form
+    var minus_one = MINUS_ONE;
+    for (var i = 0u; i < 8u; i++) {
+        if symbol[i] != minus_one[i] {
+            return false;
+        }
+    }
+    return true;
+}
+
+// The hash of the candidate `offset` bumps down from 255
+fn candidate(state: ptr<function, array<u32, 8>>, words: ptr<function, array<u32, TAIL_WORDS>>, offset: u32) {
+    set_byte(words, BUMP_POS, 255u - offset);
+    for (var i = 0u; i < 8u; i++) {
+        (*state)[i] = preimage[i];
+    }
+    for (var b = 0u; b < TAIL_BLOCKS; b++) {
+        compress(state, words, b);
+    }
+}
+
+// One seed a thread, going around in strides of the whole dispatch
+@compute @workgroup_size(WORKGROUP_SIZE)
+fn grind(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    let total = params.chunks * params.chunk_len;
+    let stride = groups.x * WORKGROUP_SIZE;
+    for (var g = id.x; g < total; g += stride) {
+        let c = g / params.chunk_len;
+        let k = g % params.chunk_len;
+        if k >= chunk_lens[c] {
+            continue;
+        }
+        let start = chunk_starts[c];
+        let lo = start.x + 1u + k;
+        let hi = start.y + select(0u, 1u, lo < start.x);
+
+        var words: array<u32, TAIL_WORDS>;
+        for (var i = 0u; i < TAIL_WORDS; i++) {
+            words[i] = preimage[8u + i];
+        }
+        for (var j = 0u; j < COUNTER_LEN; j++) {
+            let shift = select(8u * j, 8u * (COUNTER_LEN - 1u - j), BIG_ENDIAN);
+            let byte = select((hi >> (shift - 32u)), (lo >> shift), shift < 32u) & 0xffu;
+            set_byte(&words, COUNTER_POS + j, byte);
+        }
+
+        // As in `gpu.cu`, only canonical candidates in range are sent back
+        var on_curve = 0u;
+        for (var offset = 0u; offset < LOOKAHEAD; offset++) {
+            var state: array<u32, 8>;
+            candidate(&state, &words, offset);
+            if !in_ranges(&state) {
+                continue;
+            }
+            for (; on_curve < offset; on_curve++) {
+                var before: array<u32, 8>;
+                candidate(&before, &words, on_curve);
+                if off_curve(&before) {
+                    break;
+                }
+            }
+            if on_curve < offset {
+                break;
+            }
+            if off_curve(&state) {
+                let slot = atomicAdd(&hit_count, 1u);
+                if slot < params.max_hits {
+                    hits[slot] = vec2<u32>(g, offset);
+                }
+                break;
+            }
+            on_curve = offset + 1u;
+        }
+    }
+}
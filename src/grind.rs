@@ -0,0 +1,2531 @@
+use std::{
+    cell::Cell,
+    collections::{HashSet, VecDeque},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Sender},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use clap::ValueEnum;
+use regex::bytes::Regex;
+use sha2::{Digest, Sha256};
+use solana_pubkey::Pubkey;
+use tracing::{error, info, info_span, warn};
+
+use crate::{
+    args::{BumpMode, CheckArgs, CheckOrder, Emit, GrindArgs, MAX_LOOKAHEAD},
+    base58,
+    bucket::Bucket,
+    bump,
+    checkpoint::{Checkpoint, Cursor},
+    curve,
+    db::ResultsDb,
+    energy::{energy_line, EnergyMeter},
+    gpu::{GpuDevice, GpuGrinder, Layout},
+    idl, logging,
+    matcher::{target_probability, validate_target, MatchKind, TargetMatcher},
+    matchers::Matcher,
+    queue::{Queue, RENEW_EVERY},
+    results::{
+        format_utc, per_run_path, unix_now, write_manifest, Found, ResultsFile, ResultsWriter,
+    },
+    rpc::Rpc,
+    seeds::{
+        format_seed, to_hex, Endianness, HexBytes, SeedCharset, SeedKind, SeedTemplate, Wordlist,
+    },
+    sha256::{Backend, MultiHasher},
+    sinks::{Callback, Channel, ResultSink},
+    snippet::snippet,
+    template,
+    tui::{Command, Dashboard, Snapshot},
+    webhook::Webhook,
+};
+
+pub(crate) const PDA_MARKER: &[u8; 21] = b"ProgramDerivedAddress";
+
+/// What a grind's threads, stats and reporters share: what it has counted so far, and
+/// whether it is stopping or paused. Each grind has its own.
+struct State {
+    matches: AtomicU64,
+    partial_matches: AtomicU64,
+    /// `searched` as of the last match, for how long the next one took
+    last_match_at: AtomicU64,
+    searched: AtomicU64,
+    /// Candidates a GPU sent back that deriving them again on the CPU showed weren't at
+    /// their seed's canonical bump, or weren't what the GPU hashed
+    gpu_false_positives: AtomicU64,
+    /// `--bump-mode any` seeds by how many bumps down from 255 their canonical one is
+    canonical_bumps: [AtomicU64; 255],
+    /// Nanoseconds the CPU threads have spent in each `Stage`, as far as `--profile` can
+    /// tell
+    stage_nanos: [AtomicU64; 4],
+    /// Set by the `--tui` dashboard's stop or by a GPU failing, so threads stop after
+    /// their current batch, as they do once the grind's `CancellationToken` is cancelled
+    stop: AtomicBool,
+    /// Threads sleep between batches while it is set, by the `--tui` dashboard or (for
+    /// the commands) by SIGUSR1
+    paused: Arc<AtomicBool>,
+}
+
+impl State {
+    fn new(paused: Arc<AtomicBool>) -> State {
+        State {
+            matches: AtomicU64::new(0),
+            partial_matches: AtomicU64::new(0),
+            last_match_at: AtomicU64::new(0),
+            searched: AtomicU64::new(0),
+            gpu_false_positives: AtomicU64::new(0),
+            canonical_bumps: [const { AtomicU64::new(0) }; 255],
+            stage_nanos: [const { AtomicU64::new(0) }; 4],
+            stop: AtomicBool::new(false),
+            paused,
+        }
+    }
+
+    fn paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+}
+
+/// The most seeds a thread takes at once
+const ITER_BATCH_SIZE: u64 = 65536;
+
+/// Seeds each thread searches between looking at the clock and the stop flag and reporting
+/// its progress, small enough that slow grinds still do so often
+const CHECK_EVERY: u64 = 4096;
+
+/// Roughly how many times longer a curve check takes than base58 encoding and matching
+/// a candidate, going by `cargo bench --features bench`
+const CURVE_CHECK_COST: f64 = 20.0;
+
+/// Whether curve checking a seed's candidates before matching them is cheaper than the
+/// other way around, given the share of candidates that match
+fn curve_first_is_cheaper(match_rate: f64, lookahead: usize) -> bool {
+    // Matching first matches every candidate, and curve checks those that match
+    let hash_first = lookahead as f64 * (1.0 + match_rate * CURVE_CHECK_COST);
+    // Curve checking first goes until a candidate is off the curve (each one is with
+    // probability 1/2), and only matches that one
+    let all_on_curve = 0.5_f64.powi(lookahead as i32);
+    let curve_first = 2.0 * (1.0 - all_on_curve) * CURVE_CHECK_COST + (1.0 - all_on_curve);
+    curve_first < hash_first
+}
+
+/// `--profile` times one in every this many runs of each stage on average, and counts it
+/// this many times over, so that the clock is rarely read
+const PROFILE_EVERY: u64 = 64;
+
+/// What a CPU thread spends its time on, as far as `--profile` breaks it down
+#[derive(Clone, Copy)]
+enum Stage {
+    Hash,
+    Encode,
+    Match,
+    Curve,
+}
+
+/// `Stage`s as `--profile` names them
+const STAGE_NAMES: [&str; 4] = ["hash", "bs58", "match", "curve"];
+
+/// A CPU thread's `--profile` timings since they were last flushed to the grind's
+/// `State`. The closures that match and curve check a seed's candidates both time
+/// themselves, hence the cells.
+struct Profiler {
+    enabled: bool,
+    /// Runs of each stage until the next one to time. The gaps are random, as runs of a
+    /// stage take turns (`--bump-mode any` curve checks fewer candidates at each bump) and
+    /// timing every 64th would keep timing the same turn.
+    until_next: [Cell<u64>; 4],
+    nanos: [Cell<u64>; 4],
+    /// xorshift64 state for the gaps
+    rng: Cell<u64>,
+}
+
+impl Profiler {
+    fn new(enabled: bool) -> Profiler {
+        let profiler = Profiler {
+            enabled,
+            until_next: Default::default(),
+            nanos: Default::default(),
+            rng: Cell::new(rand::random::<u64>() | 1),
+        };
+        for until_next in &profiler.until_next {
+            until_next.set(profiler.gap());
+        }
+        profiler
+    }
+
+    /// The time now, if this run of `stage` is one to time
+    #[inline(always)]
+    fn start(&self, stage: Stage) -> Option<Instant> {
+        if !self.enabled {
+            return None;
+        }
+        let until_next = &self.until_next[stage as usize];
+        until_next.set(until_next.get() - 1);
+        if until_next.get() > 0 {
+            return None;
+        }
+        until_next.set(self.gap());
+        Some(Instant::now())
+    }
+
+    /// Between 1 and `2 * PROFILE_EVERY - 1` runs, `PROFILE_EVERY` on average
+    fn gap(&self) -> u64 {
+        let mut x = self.rng.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng.set(x);
+        1 + x % (2 * PROFILE_EVERY - 1)
+    }
+
+    #[inline(always)]
+    fn stop(&self, stage: Stage, started: Option<Instant>) {
+        if let Some(started) = started {
+            let nanos = &self.nanos[stage as usize];
+            nanos.set(nanos.get() + started.elapsed().as_nanos() as u64 * PROFILE_EVERY);
+        }
+    }
+
+    fn flush(&self, state: &State) {
+        for (total, nanos) in state.stage_nanos.iter().zip(&self.nanos) {
+            if nanos.get() > 0 {
+                total.fetch_add(nanos.take(), Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Where `--profile` found the CPU threads' time going, out of `busy` seconds of it, and
+/// what each stage cost for each of the `searched` seeds they searched
+fn profile_line(state: &State, busy: f64, searched: u64) -> String {
+    let nanos = state
+        .stage_nanos
+        .each_ref()
+        .map(|nanos| nanos.load(Ordering::Relaxed) as f64);
+    let share = |nanos: f64| nanos / 1e9 * 100.0 / busy.max(f64::EPSILON);
+    let stages: Vec<_> = STAGE_NAMES
+        .iter()
+        .zip(nanos)
+        .map(|(name, nanos)| {
+            format!(
+                "{name} {:.1}% ({:.1}ns/seed)",
+                share(nanos),
+                nanos / searched.max(1) as f64
+            )
+        })
+        .collect();
+    format!(
+        "profile: {}, other {:.1}%",
+        stages.join(", "),
+        (100.0 - share(nanos.iter().sum())).max(0.0)
+    )
+}
+
+/// Cancels `cancel` on the first Ctrl-C (or SIGTERM), stopping the grinds given it
+/// gracefully, and exits right away on the second; on unix, `kill -USR1 <pid>` pauses
+/// the grinds given `paused` and a second one resumes them. Only the commands install
+/// these, a `Grinder` of another program's only stops once its token is cancelled.
+pub(crate) fn install_handlers(cancel: CancellationToken, paused: Arc<AtomicBool>, quiet: bool) {
+    if let Err(e) = ctrlc::set_handler(move || {
+        if cancel.is_cancelled() {
+            std::process::exit(130);
+        }
+        cancel.cancel();
+    }) {
+        error!("failed to install the Ctrl-C handler: {e}");
+    }
+
+    #[cfg(unix)]
+    match signal_hook::iterator::Signals::new([signal_hook::consts::SIGUSR1]) {
+        Ok(mut signals) => {
+            std::thread::spawn(move || {
+                for _ in signals.forever() {
+                    let paused = !paused.fetch_xor(true, Ordering::Relaxed);
+                    if !quiet {
+                        info!("{}", if paused { "paused" } else { "resumed" });
+                    }
+                }
+            });
+        }
+        Err(e) => error!("failed to install the SIGUSR1 handler: {e}"),
+    }
+}
+
+/// How many candidates the GPUs got wrong, for the stats. Empty without any GPU.
+fn gpu_false_positives(state: &State, gpus: &[(String, Arc<AtomicU64>)]) -> String {
+    match gpus.is_empty() {
+        true => String::new(),
+        false => format!(
+            "; GPU false positives {}",
+            state.gpu_false_positives.load(Ordering::Relaxed)
+        ),
+    }
+}
+
+/// How fast the CPU threads (if there are any) and each GPU (named in `gpus`, with the
+/// seeds it searched) have been grinding, the CPU threads having searched the rest of
+/// `searched`. Empty without any GPU.
+fn device_rates(
+    searched: u64,
+    cpu_threads: u64,
+    gpus: &[(String, Arc<AtomicU64>)],
+    elapsed: f64,
+) -> String {
+    if gpus.is_empty() {
+        return String::new();
+    }
+    let on_gpus: Vec<_> = gpus
+        .iter()
+        .map(|(name, searched)| (name.as_str(), searched.load(Ordering::Relaxed)))
+        .collect();
+    let on_cpu = searched - on_gpus.iter().map(|(_, searched)| searched).sum::<u64>();
+    let rates: Vec<_> = (cpu_threads > 0)
+        .then_some(("CPU", on_cpu))
+        .into_iter()
+        .chain(on_gpus)
+        .map(|(name, searched)| format!("{name} {:.0}/s", searched as f64 / elapsed))
+        .collect();
+    format!(": {}", rates.join(", "))
+}
+
+/// The slowest, median and fastest of the CPU threads' `rates`, which should be close
+/// unless some are throttled or share a core with something else
+fn thread_rates(rates: &[f64]) -> String {
+    let mut sorted: Vec<_> = rates.iter().copied().enumerate().collect();
+    sorted.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    let (slowest, fastest) = (sorted[0], sorted[sorted.len() - 1]);
+    let middle = sorted.len() / 2;
+    let median = if sorted.len() % 2 == 0 {
+        (sorted[middle - 1].1 + sorted[middle].1) / 2.0
+    } else {
+        sorted[middle].1
+    };
+    format!(
+        "per thread: min {:.0}/s (thread {}), median {median:.0}/s, max {:.0}/s (thread {})",
+        slowest.1, slowest.0, fastest.1, fastest.0
+    )
+}
+
+/// A `--stats-json` record of the grind `elapsed` seconds in: the seeds searched and the
+/// hashrate, the matches (and how many to expect, if that can be told), the energy used,
+/// the share of a seed range searched, and the seeds each CPU thread (with its hashrate
+/// in `threads`) and each GPU has searched
+fn stats_record(
+    state: &State,
+    elapsed: f64,
+    seed_match_rate: Option<f64>,
+    joules: Option<f64>,
+    range_searched: Option<f64>,
+    threads: &[(u64, f64)],
+    gpus: &[(String, Arc<AtomicU64>)],
+) -> serde_json::Value {
+    let searched = state.searched.load(Ordering::Relaxed);
+    let matches = state.matches.load(Ordering::Relaxed);
+    let mut record = serde_json::json!({
+        "elapsed_secs": elapsed,
+        "iterations": searched,
+        "hashrate": searched as f64 / elapsed,
+        "matches": matches,
+        "expected_matches": seed_match_rate.map(|rate| searched as f64 * rate),
+        "luck": seed_match_rate
+            .filter(|_| matches > 0)
+            .map(|rate| searched as f64 * rate / matches as f64),
+        "partial_matches": state.partial_matches.load(Ordering::Relaxed),
+        "range_searched": range_searched,
+        "joules": joules,
+        "threads": threads
+            .iter()
+            .enumerate()
+            .map(|(thread, (searched, rate))| {
+                serde_json::json!({ "thread": thread, "iterations": searched, "hashrate": rate })
+            })
+            .collect::<Vec<_>>(),
+        "gpus": gpus
+            .iter()
+            .map(|(name, searched)| {
+                let searched = searched.load(Ordering::Relaxed);
+                serde_json::json!({
+                    "name": name,
+                    "iterations": searched,
+                    "hashrate": searched as f64 / elapsed,
+                })
+            })
+            .collect::<Vec<_>>(),
+    });
+    if !gpus.is_empty() {
+        record["gpu_false_positives"] = state.gpu_false_positives.load(Ordering::Relaxed).into();
+    }
+    record
+}
+
+/// How far back the moving average hashrate goes
+const MOVING_AVERAGE: Duration = Duration::from_secs(10);
+
+/// The hashrate over the last `MOVING_AVERAGE`, from the seeds searched as of each tick of
+/// the stats thread, and the fastest it has been, so that a grind slowing down (a CPU
+/// throttling, something else taking cores) shows rather than being averaged away
+struct Hashrate {
+    /// The seeds searched as of each tick, oldest first, the first of them at least
+    /// `MOVING_AVERAGE` old once the grind is that old itself
+    samples: VecDeque<(Instant, u64)>,
+    peak: Option<f64>,
+}
+
+impl Hashrate {
+    fn new(start_time: Instant) -> Hashrate {
+        Hashrate {
+            samples: VecDeque::from([(start_time, 0)]),
+            peak: None,
+        }
+    }
+
+    fn sample(&mut self, searched: u64) {
+        let now = Instant::now();
+        self.samples.push_back((now, searched));
+        while self.samples.len() > 2 && now - self.samples[1].0 >= MOVING_AVERAGE {
+            self.samples.pop_front();
+        }
+        if let Some(rate) = self.moving_average() {
+            self.peak = Some(self.peak.map_or(rate, |peak| peak.max(rate)));
+        }
+    }
+
+    /// Seeds searched a second over the last `MOVING_AVERAGE`, or as much of it as there
+    /// has been if that is at least a second
+    fn moving_average(&self) -> Option<f64> {
+        let (first, last) = (self.samples.front()?, self.samples.back()?);
+        let secs = (last.0 - first.0).as_secs_f64();
+        (secs >= 1.0).then(|| (last.1 - first.1) as f64 / secs)
+    }
+}
+
+/// `secs` in the largest unit it is at least one of, e.g. `40.0s`, `2.5h` or `3.1y`
+pub(crate) fn format_eta(secs: f64) -> String {
+    const UNITS: [(&str, f64); 6] = [
+        ("y", 365.25 * 24.0 * 60.0 * 60.0),
+        ("d", 24.0 * 60.0 * 60.0),
+        ("h", 60.0 * 60.0),
+        ("m", 60.0),
+        ("s", 1.0),
+        ("ms", 0.001),
+    ];
+    let (unit, size) = UNITS
+        .iter()
+        .find(|(_, size)| secs >= *size)
+        .unwrap_or(&UNITS[5]);
+    format!("{:.1}{unit}", secs / size)
+}
+
+/// Tells a `Grinder::progress` callback how far the grind has got, `last` being when it
+/// was last told and the seeds searched by then
+fn report_progress(
+    state: &State,
+    on_progress: &mut dyn FnMut(Progress),
+    start_time: Instant,
+    last: &mut (Instant, u64),
+) {
+    let searched = state.searched.load(Ordering::Relaxed);
+    let (last_time, last_searched) = std::mem::replace(last, (Instant::now(), searched));
+    on_progress(Progress {
+        iterations: searched,
+        hashrate: (searched - last_searched) as f64 / last_time.elapsed().as_secs_f64(),
+        matches: state.matches.load(Ordering::Relaxed),
+        elapsed: start_time.elapsed(),
+    });
+}
+
+/// Where `--bump-mode any` seeds' canonical bumps fell, next to where they are expected to
+/// (each bump is off the curve with probability 1/2, whatever the owner), and what
+/// `--bump-depth` saved by giving up on the rest
+fn print_bump_stats(state: &State, searched: u64, bump_depth: u8) {
+    let counts = state
+        .canonical_bumps
+        .each_ref()
+        .map(|count| count.load(Ordering::Relaxed));
+    if searched == 0 {
+        return;
+    }
+    let gave_up = searched - counts.iter().sum::<u64>();
+    let hashes = counts
+        .iter()
+        .enumerate()
+        .map(|(offset, count)| (offset as u64 + 1) * count)
+        .sum::<u64>()
+        + gave_up * bump_depth as u64;
+    let share = |count: u64| count as f64 * 100.0 / searched as f64;
+    info!(
+        "canonical bumps 255 {:.2}%, 254 {:.2}%, 253 {:.2}%, lower {:.2}% (expected 50%, 25%, \
+         12.5%, 12.5%); {:.3} hashes per seed",
+        share(counts[0]),
+        share(counts[1]),
+        share(counts[2]),
+        share(counts[3..].iter().sum()),
+        hashes as f64 / searched as f64,
+    );
+    if gave_up > 0 {
+        // Had they gone on, each would have taken another 2 hashes on average for its
+        // canonical bump, so as many candidates are given up as hashes are saved
+        let saved = 2.0 * gave_up as f64;
+        info!(
+            "gave up on {gave_up} seeds ({:.2}%) after {bump_depth} bumps, saving ~{:.2}% of \
+             the hashes",
+            gave_up as f64 * 100.0 / searched as f64,
+            saved * 100.0 / (hashes as f64 + saved),
+        );
+    }
+}
+
+/// Prints what a thread finds, sending full matches on to be saved
+struct Reporter {
+    state: Arc<State>,
+    results: Option<Sender<Found>>,
+    seed_kind: SeedKind,
+    quiet: bool,
+    /// Length of `--target`, for partial matches
+    target_len: usize,
+    /// Whether the `--tui` dashboard shows matches, so none are printed
+    tui: bool,
+    /// The chance a seed gives a match, if it can be told, for how lucky each one was
+    seed_match_rate: Option<f64>,
+    /// Where a `--template` grind saves the keypairs of its matches
+    keypair_dir: Option<PathBuf>,
+    /// The languages `--emit` prints each match's derivation in
+    emit: Vec<Emit>,
+    /// The seeds and program matches are derived from, for `--emit`
+    template: SeedTemplate,
+    owner: Pubkey,
+}
+
+impl Reporter {
+    fn report(&self, key: &Pubkey, bump: u8, match_kind: MatchKind, seed_bytes: &[u8]) {
+        match match_kind {
+            MatchKind::Full => {
+                // We have a match!
+                let seed = format_seed(seed_bytes, self.seed_kind);
+                // The seeds searched since the last match, next to how many a match takes
+                // on average: below 1 was lucky. Threads only count their seeds a batch at
+                // a time, so this is only as close as a batch on each of them.
+                let searched = self.state.searched.load(Ordering::Relaxed);
+                let since_last = searched
+                    .saturating_sub(self.state.last_match_at.swap(searched, Ordering::Relaxed));
+                let work = self.seed_match_rate.map(|rate| since_last as f64 * rate);
+                match work {
+                    _ if self.tui => {}
+                    Some(work) if !self.quiet => info!(
+                        pubkey = %key,
+                        seed,
+                        bump,
+                        work,
+                        "found {key} with seed {seed} after {work:.2}x the expected work"
+                    ),
+                    _ => info!(pubkey = %key, seed, bump, "found {key} with seed {seed}"),
+                }
+                for &emit in self.emit.iter().filter(|_| !self.tui) {
+                    let snippet = snippet(
+                        emit,
+                        &self.template,
+                        self.seed_kind,
+                        seed_bytes,
+                        bump,
+                        &self.owner,
+                    );
+                    info!(pubkey = %key, "{snippet}");
+                }
+                if let Some(results) = &self.results {
+                    // Only fails once the writer is gone, which it isn't until every
+                    // thread is
+                    let _ = results.send(Found {
+                        key: *key,
+                        seed,
+                        seed_hex: to_hex(seed_bytes),
+                        bump,
+                        iterations: searched,
+                        found_at: unix_now(),
+                    });
+                }
+                self.state.matches.fetch_add(1, Ordering::Relaxed);
+            }
+            MatchKind::Partial(len) => {
+                if !self.quiet && !self.tui {
+                    info!(
+                        pubkey = %key,
+                        len,
+                        "partial {len}/{} {key} with seed {}",
+                        self.target_len,
+                        format_seed(seed_bytes, self.seed_kind)
+                    );
+                }
+                self.state.partial_matches.fetch_add(1, Ordering::Relaxed);
+            }
+            MatchKind::Miss => {}
+        }
+    }
+
+    /// Saves the keypair of a match's ground key, the only record of its secret key
+    fn save_keypair(&self, secret: &[u8; 32]) {
+        let Some(dir) = &self.keypair_dir else {
+            return;
+        };
+        match template::save_keypair(dir, secret) {
+            Ok(path) if !self.quiet && !self.tui => {
+                info!("saved its keypair to {}", path.display())
+            }
+            Ok(_) => {}
+            Err(e) => error!("{e}"),
+        }
+    }
+}
+
+/// Spreads the low bits of `index` over the set bits of `free`, lowest first, so that
+/// consecutive indices step through the seeds `--seed-mask` leaves free
+fn deposit(mut index: u64, mut free: u64) -> u64 {
+    let mut seed = 0;
+    while index != 0 && free != 0 {
+        let bit = free & free.wrapping_neg();
+        if index & 1 == 1 {
+            seed |= bit;
+        }
+        index >>= 1;
+        free &= free - 1;
+    }
+    seed
+}
+
+/// Derives the PDA for a single seed, using `Pubkey::find_program_address` directly
+pub fn check(
+    CheckArgs {
+        owner,
+        seed,
+        seed_type,
+        endianness,
+        seed_hex,
+        seed_str,
+        seed_prefix,
+        seeds,
+    }: CheckArgs,
+) {
+    let template = SeedTemplate::from_args(seeds, seed_prefix);
+    let (seed_bytes, seed_str) = match (seed, seed_hex, seed_str) {
+        (Some(seed), _, _) => {
+            let width = template.seed_type(seed_type).unwrap_or_else(|e| {
+                eprintln!("{e}");
+                std::process::exit(1);
+            });
+            if seed.leading_zeros() < 128 - 8 * width.width() as u32 {
+                eprintln!("seed {seed} doesn't fit in a {}", width.name());
+                std::process::exit(1);
+            }
+            let bytes = match endianness {
+                Endianness::Le => seed.to_le_bytes()[..width.width()].to_vec(),
+                Endianness::Be => seed.to_be_bytes()[16 - width.width()..].to_vec(),
+            };
+            (bytes, seed.to_string())
+        }
+        (None, Some(HexBytes(seed_hex)), _) => {
+            let seed_str = format!("0x{}", to_hex(&seed_hex));
+            (seed_hex, seed_str)
+        }
+        (None, None, Some(seed_str)) => (seed_str.clone().into_bytes(), format!("{seed_str:?}")),
+        (None, None, None) => unreachable!("clap requires seed, seed-hex or seed-str"),
+    };
+    let key = Pubkey::find_program_address(&template.seeds(&seed_bytes), &owner).0;
+    println!("seed {seed_str} for owner {owner} gives key {key}");
+}
+
+/// The address `seeds` give with `bump` through `Pubkey::create_program_address`, if it
+/// is their canonical bump
+fn derive_canonical(seeds: &[&[u8]], bump: u8, owner: &Pubkey) -> Option<Pubkey> {
+    let derive = |bump: u8| {
+        let bump = [bump];
+        let mut seeds = seeds.to_vec();
+        seeds.push(&bump);
+        Pubkey::create_program_address(&seeds, owner).ok()
+    };
+    if (u16::from(bump) + 1..=255).any(|higher| derive(higher as u8).is_some()) {
+        return None;
+    }
+    derive(bump)
+}
+
+/// What a file is called in the `--sync-url` bucket
+fn synced(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Grinds seeds for PDAs that match `args`, printing (and saving) each one found
+pub fn grind(args: GrindArgs) {
+    if let Err(e) = logging::init(args.log_format, args.log_file.as_deref()) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+    let (cancel, paused) = (CancellationToken::new(), Arc::new(AtomicBool::new(false)));
+    install_handlers(cancel.clone(), paused.clone(), args.quiet || args.tui);
+    let grinder = Grinder::new(args)
+        .cancellation_token(cancel)
+        .pause_flag(paused);
+    if let Err(e) = run(grinder) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+/// A grind for other programs to run, which hands them each match as it is found. It is
+/// configured with the same `GrindArgs` the `grind` command parses, e.g.
+///
+/// ```no_run
+/// use clap::Parser;
+/// use pda_grinder::{args::GrindArgs, Grinder};
+///
+/// let mut args = GrindArgs::parse_from([
+///     "grind",
+///     "--owner",
+///     "11111111111111111111111111111111",
+///     "--target",
+///     "ab",
+///     "--seed-start",
+///     "0",
+///     "--seed-end",
+///     "1000000",
+/// ]);
+/// args.no_file = true;
+/// args.no_manifest = true;
+/// if let Err(e) = Grinder::new(args).run(|found| println!("{} {}", found.key, found.seed_hex)) {
+///     eprintln!("{e}");
+/// }
+/// ```
+///
+/// Unlike the command, it installs no signal handlers: it stops at the end of a range,
+/// after a `--duration`, or once its `CancellationToken` is cancelled.
+pub struct Grinder {
+    args: GrindArgs,
+    sinks: Vec<Box<dyn ResultSink>>,
+    matcher: Option<Arc<dyn Matcher>>,
+    cancel: CancellationToken,
+    /// Set by the commands' SIGUSR1 handler to pause the grind
+    paused: Arc<AtomicBool>,
+    /// How often to call the progress callback, and the callback
+    progress: Option<(Duration, ProgressCallback)>,
+    /// Called once the grind is set up and its threads are about to start
+    on_start: Option<Box<dyn FnOnce() + Send>>,
+}
+
+type ProgressCallback = Box<dyn FnMut(Progress) + Send>;
+
+/// Stops every `Grinder` it is given, as a Ctrl-C stops the command, once it is
+/// cancelled from another thread. Clones cancel each other.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How far a grind has got, as its progress callback is told
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+    /// Seeds searched across all threads
+    pub iterations: u64,
+    /// Seeds searched per second since the last report
+    pub hashrate: f64,
+    pub matches: u64,
+    pub elapsed: Duration,
+}
+
+impl Grinder {
+    pub fn new(args: GrindArgs) -> Grinder {
+        Grinder {
+            args,
+            sinks: vec![],
+            matcher: None,
+            cancel: CancellationToken::new(),
+            paused: Arc::new(AtomicBool::new(false)),
+            progress: None,
+            on_start: None,
+        }
+    }
+
+    /// Stops the grind once `token` is cancelled
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Grinder {
+        self.cancel = token;
+        self
+    }
+
+    /// Pauses the grind while `paused` is set
+    pub(crate) fn pause_flag(mut self, paused: Arc<AtomicBool>) -> Grinder {
+        self.paused = paused;
+        self
+    }
+
+    /// Calls `on_progress` every `every` while the grind runs, whether or not the stats
+    /// are printed, and once more when it is over with the hashrate over the whole grind
+    pub fn progress(
+        mut self,
+        every: Duration,
+        on_progress: impl FnMut(Progress) + Send + 'static,
+    ) -> Grinder {
+        self.progress = Some((every, Box::new(on_progress)));
+        self
+    }
+
+    /// Also delivers each match to `sink`, e.g. a `sinks::Channel` to another thread
+    pub fn sink(mut self, sink: impl ResultSink + 'static) -> Grinder {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Only keeps addresses `matcher` accepts as well as the `--target`, `--suffix` and
+    /// `--regex` (any of which can be `None` here, unlike on the command line)
+    pub fn matcher(mut self, matcher: impl Matcher + 'static) -> Grinder {
+        self.matcher = Some(Arc::new(matcher));
+        self
+    }
+
+    /// Grinds until the grind stops (at the end of a range, or see `--duration`),
+    /// calling `on_match` with every address found, once each, as well as logging and
+    /// saving them as the arguments say and passing them to the sinks. The log is
+    /// `tracing` events, which go nowhere unless the program installs a subscriber.
+    /// Returns an error, before grinding anything, if the arguments can't be ground for
+    /// or something they ask for (a results file, a checkpoint, a GPU) can't be set up,
+    /// or once the grind stops if a GPU failed partway.
+    pub fn run(mut self, on_match: impl FnMut(&Found) + Send + 'static) -> Result<(), String> {
+        self.sinks.push(Box::new(Callback(on_match)));
+        run(self).map(|_| ())
+    }
+
+    /// Runs the grind on a thread of its own for the language bindings, once it is set
+    /// up, or returns the error it stopped at before grinding anything. The thread gives
+    /// back any error it stops with later on, such as a GPU failing.
+    #[cfg(any(feature = "cdylib", feature = "napi", feature = "pyo3"))]
+    pub(crate) fn start(mut self) -> Result<std::thread::JoinHandle<Result<(), String>>, String> {
+        let (started_sender, started) = mpsc::channel();
+        self.on_start = Some(Box::new(move || {
+            let _ = started_sender.send(());
+        }));
+        let handle = std::thread::spawn(move || self.run(|_| {}));
+        match started.recv() {
+            Ok(()) => Ok(handle),
+            // `on_start` was dropped without being called, as the grind returned early
+            Err(_) => match handle.join() {
+                Ok(result) => Err(result.expect_err("a grind only returns early with an error")),
+                Err(_) => Err("the grind panicked".into()),
+            },
+        }
+    }
+}
+
+/// `grind`, returning how many seeds each thread searched and how long it took
+pub(crate) fn run(grinder: Grinder) -> Result<Vec<(u64, Duration)>, String> {
+    let Grinder {
+        mut args,
+        sinks,
+        matcher: custom,
+        cancel,
+        paused,
+        mut progress,
+        on_start,
+    } = grinder;
+    let state = Arc::new(State::new(paused));
+    // By the `CancellationToken` (which the commands' Ctrl-C cancels), the dashboard or a
+    // failed GPU
+    let stopped = {
+        let state = state.clone();
+        move || cancel.is_cancelled() || state.stop.load(Ordering::Relaxed)
+    };
+    validate_target(&args)?;
+    if args.bump_depth.is_some() && args.bump_mode != BumpMode::Any {
+        return Err("--bump-depth only applies to --bump-mode any".into());
+    }
+    let bump_depth = args.bump_depth.unwrap_or(u8::MAX);
+    // A `--template` or `--idl` sets the owner and seeds, and a key among them is ground
+    // as new keypairs
+    let ground_key = match (args.template, &args.idl) {
+        (Some(_), _) => template::apply(&mut args).map(|key| Some(key.to_string())),
+        (None, Some(_)) => idl::apply(&mut args),
+        (None, None) => Ok(None),
+    }?;
+    let grind_keys = ground_key.is_some();
+    if grind_keys {
+        let unsupported = if args.bump_mode != BumpMode::First {
+            Some("--bump-mode any")
+        } else if args.seed_start.is_some() || args.seed_end.is_some() || args.exhaustive {
+            Some("a seed range")
+        } else if args.shard.is_some() || args.queue.is_some() {
+            Some("--shard or --queue")
+        } else if args.checkpoint.is_some() || args.resume.is_some() {
+            Some("checkpoints")
+        } else if args.seed_mask != 0 {
+            Some("--seed-mask")
+        } else if args.gpu.is_some() {
+            Some("--gpu")
+        } else {
+            None
+        };
+        if let Some(unsupported) = unsupported {
+            return Err(format!("grinding new keypairs can't take {unsupported}"));
+        }
+    }
+    if args.job.is_some() && args.shard.is_none() && args.queue.is_none() {
+        return Err("--job only applies to --shard and --queue".into());
+    }
+    if let Some(backend) = args.hasher.filter(|backend| !backend.supported()) {
+        return Err(format!("this CPU can't hash with {}", backend.name()));
+    }
+    if args.gpu.is_some() {
+        let unsupported = if args.bump_mode != BumpMode::First {
+            Some("--gpu only grinds --bump-mode first, it leaves curve checks to the CPU")
+        } else if args.target.is_none() || args.anywhere {
+            Some("--gpu needs a --target (without --anywhere) to rule candidates out by")
+        } else if args.seed_charset.is_some() || args.wordlist.is_some() {
+            Some("--gpu only grinds integer and --seed-len seeds")
+        } else if args.seed_mask != 0 {
+            Some("--gpu can't grind with --seed-mask")
+        } else {
+            None
+        };
+        if let Some(e) = unsupported {
+            return Err(e.into());
+        }
+    }
+
+    let template = SeedTemplate::from_args(args.seeds.clone(), args.seed_prefix.clone());
+    if template.0.len() > 1 && !args.quiet {
+        info!("deriving with seeds {template}");
+    }
+    let (fixed_before, fixed_after) = template.fixed_bytes();
+    let seed_type = template.seed_type(args.seed_type)?;
+
+    let wordlist = args
+        .wordlist
+        .as_deref()
+        .map(|path| Wordlist::load(path, args.words, args.word_digits).map(Arc::new))
+        .transpose()?;
+
+    // Word seeds vary in length, and are written in full for every candidate
+    let (seed_len, seed_kind) = match (args.seed_charset, args.seed_len) {
+        _ if wordlist.is_some() => (0, SeedKind::Str),
+        _ if grind_keys => (32, SeedKind::Key),
+        (Some(_), seed_len) => (seed_len.map_or(8, usize::from), SeedKind::Str),
+        (None, Some(seed_len)) => (seed_len as usize, SeedKind::Bytes),
+        (None, None) => (seed_type.width(), SeedKind::Int(args.endianness)),
+    };
+    let seed_desc = match (args.seed_charset, seed_kind) {
+        _ if wordlist.is_some() => match args.word_digits {
+            0 => format!("{}-word", args.words),
+            digits => format!("{}-word (+{digits} digit)", args.words),
+        },
+        (Some(charset), _) => format!(
+            "{seed_len}-character {}",
+            charset.to_possible_value().unwrap().get_name()
+        ),
+        (_, SeedKind::Key) => format!("{} key", ground_key.as_deref().unwrap_or_default()),
+        (None, SeedKind::Int(Endianness::Le)) => seed_type.name().to_string(),
+        (None, SeedKind::Int(Endianness::Be)) => format!("big-endian {}", seed_type.name()),
+        (None, _) => format!("{seed_len}-byte"),
+    };
+
+    // Number of distinct ground seeds, if small enough to count in a u64
+    let charset = args.seed_charset.map(SeedCharset::chars);
+    let seed_space = match charset {
+        _ if wordlist.is_some() => wordlist.as_ref().map(|wordlist| wordlist.space),
+        Some(charset) => (charset.len() as u64).checked_pow(seed_len as u32),
+        None => 256_u64.checked_pow(seed_len as u32),
+    };
+    // The chance a seed gives a match: that its canonical bump is among the ones tried
+    // (each bump is off the curve with probability 1/2), and that its address matches
+    let bumps_tried = match args.bump_mode {
+        BumpMode::First => args.lookahead,
+        BumpMode::Any => bump_depth,
+    };
+    let seed_match_rate = target_probability(&args)
+        .filter(|_| custom.is_none())
+        .map(|probability| (1.0 - 0.5_f64.powi(bumps_tried.into())) * probability);
+    if let Some(space) = seed_space.filter(|space| *space < 1 << 40 && !args.quiet) {
+        let expected = seed_match_rate
+            .map(|rate| format!(", expect ~{:.2} matches in all of it", space as f64 * rate))
+            .unwrap_or_default();
+        if !args.exhaustive {
+            warn!(
+                "there are only {space} {seed_desc} seeds, which can be exhausted{expected} \
+                 (--exhaustive searches each of them once)"
+            );
+        } else if !expected.is_empty() {
+            info!("searching {space} {seed_desc} seeds{expected}");
+        }
+    }
+    if args.exhaustive && seed_space.is_none() {
+        return Err(format!(
+            "there are too many {seed_desc} seeds to search exhaustively, \
+             use a smaller --seed-type or --seed-len"
+        ));
+    }
+
+    // Seeds to search once, in order, split across threads
+    let seed_range = match (args.seed_start, args.seed_end) {
+        _ if args.exhaustive => seed_space.map(|space| (0, space)),
+        (None, None) => None,
+        (start, end) => {
+            let start = start.unwrap_or(0);
+            let end = end.or(seed_space).unwrap_or(u64::MAX);
+            if start >= end {
+                return Err(format!(
+                    "--seed-start {start} must come before --seed-end {end}"
+                ));
+            }
+            if let Some(space) = seed_space.filter(|space| end > *space) {
+                return Err(format!(
+                    "--seed-end {end} is past the last of the {space} {seed_desc} seeds"
+                ));
+            }
+            Some((start, end))
+        }
+    };
+
+    if args.seed_value & !args.seed_mask != 0 {
+        return Err("--seed-value sets bits outside of --seed-mask".into());
+    }
+    let counter_bits = match seed_len {
+        8.. => u64::MAX,
+        seed_len => (1 << (8 * seed_len)) - 1,
+    };
+    if args.seed_mask != 0 && !args.seed_mask & counter_bits == 0 {
+        return Err(format!(
+            "--seed-mask leaves no bits of the {seed_desc} seed to grind"
+        ));
+    }
+
+    let pattern = match (&args.target, &args.suffix) {
+        (Some(target), Some(suffix)) if args.anywhere => Some(format!("...{target}...{suffix}")),
+        (Some(target), Some(suffix)) => Some(format!("{target}...{suffix}")),
+        (Some(target), None) if args.anywhere => Some(format!("...{target}...")),
+        (Some(target), None) => Some(format!("{target}...")),
+        (None, Some(suffix)) => Some(format!("...{suffix}")),
+        (None, None) => None,
+    };
+    // Made before grinding starts, so that a build without it fails right away
+    let mut dashboard = args
+        .tui
+        .then(|| {
+            let goal = match (&pattern, &args.regex) {
+                (Some(pattern), _) => format!("give {pattern}"),
+                (None, Some(regex)) => format!("match /{regex}/"),
+                (None, None) => "the matcher accepts".to_string(),
+            };
+            let title = format!("{seed_desc} seeds that {goal} for program {}", args.owner);
+            Dashboard::new(title)
+        })
+        .transpose()?;
+    match pattern {
+        Some(_) if args.quiet => {}
+        Some(pattern) => {
+            info!(
+                "looking for {seed_desc} seeds that give {pattern} for program {}",
+                args.owner
+            );
+            if let Some(rate) = seed_match_rate {
+                info!("expect a match every ~{:.0} seeds", 1.0 / rate);
+            }
+        }
+        None => match (&args.regex, &custom) {
+            (Some(pattern), _) => {
+                if let Err(e) = Regex::new(pattern) {
+                    return Err(format!("invalid regex {pattern:?}: {e}"));
+                }
+                if !args.quiet {
+                    info!(
+                        "looking for {seed_desc} seeds that match /{pattern}/ for program {} \
+                         (regex matching runs on every candidate, expect a lower hashrate)",
+                        args.owner
+                    );
+                }
+            }
+            // Only a `Grinder` can get here, clap requires one of the others
+            (None, Some(_)) if args.quiet => {}
+            (None, Some(_)) => info!(
+                "looking for {seed_desc} seeds whose addresses the matcher accepts for program {}",
+                args.owner
+            ),
+            (None, None) => {
+                return Err("nothing to grind for: give a target, suffix, regex or matcher".into());
+            }
+        },
+    }
+
+    let start_time = Instant::now();
+    let deadline = match (args.duration, args.deadline) {
+        (Some(duration), _) => Some(start_time + duration),
+        (None, Some(deadline)) => match deadline.duration_since(SystemTime::now()) {
+            Ok(left) => Some(start_time + left),
+            Err(_) => return Err("--deadline has already passed".into()),
+        },
+        (None, None) => None,
+    };
+
+    // Seeds there are to grind, fewer than the seed space if `--seed-mask` fixes some of
+    // the counter bits; `None` if all 2^64 counter values are distinct seeds
+    let free_bits = !args.seed_mask & counter_bits;
+    let grind_space = match args.seed_mask {
+        0 => seed_space,
+        _ => seed_space
+            .into_iter()
+            .chain(1_u64.checked_shl(free_bits.count_ones()))
+            .min(),
+    };
+    let (seed_mask, seed_value) = (args.seed_mask, args.seed_value);
+    let index_to_seed = move |index: u64| match seed_mask {
+        0 => index,
+        mask => deposit(index, free_bits) & !mask | seed_value,
+    };
+
+    // What a `--checkpoint` is good for: the seeds and what they are ground for
+    let mut search = serde_json::json!({
+        "owner": args.owner.to_string(),
+        "target": args.target,
+        "suffix": args.suffix,
+        "anywhere": args.anywhere,
+        "regex": args.regex,
+        "fuzzy": args.fuzzy,
+        "min_len": args.min_len,
+        "bump_mode": args.bump_mode.to_possible_value().unwrap().get_name(),
+        "lookahead": args.lookahead,
+        "bump_depth": args.bump_depth,
+        "seeds": template.to_string(),
+        "seed_kind": seed_desc,
+        "wordlist": args.wordlist,
+        "seed_mask": args.seed_mask,
+        "seed_value": args.seed_value,
+        "seed_range": seed_range,
+    });
+
+    // Shards and `--queue` workers of a job start from a point made from its name, so
+    // that they agree on which seeds are where
+    let job = (args.shard.is_some() || args.queue.is_some()).then(|| {
+        args.job
+            .clone()
+            .unwrap_or_else(|| to_hex(&Sha256::digest(search.to_string())[..8]))
+    });
+    let job_hash = job.as_deref().map(Sha256::digest);
+    if let Some(job) = &job {
+        search["job"] = job.clone().into();
+    }
+    if let (Some(shard), Some(job)) = (args.shard, &job) {
+        search["shard"] = shard.to_string().into();
+        if !args.quiet {
+            info!("grinding shard {shard} of job {job}");
+        }
+    }
+    let queue = args
+        .queue
+        .as_deref()
+        .zip(job.as_deref())
+        .map(|(url, job)| {
+            if !args.quiet {
+                info!("grinding job {job} from --queue");
+            }
+            Queue::connect(url, job, args.owner).map(Arc::new)
+        })
+        .transpose()?;
+    // A shard of a range grinds its slice of the range, and otherwise its slice of the
+    // positions the threads go around all of the seeds from
+    let no_seeds = |shard| format!("--shard {shard} leaves no seeds to grind");
+    let seed_range = match (args.shard, seed_range) {
+        (Some(shard), Some((range_start, range_end))) => {
+            let (first, end) = shard.slice((range_end - range_start).into());
+            if first == end {
+                return Err(no_seeds(shard));
+            }
+            Some((range_start + first as u64, range_start + end as u64))
+        }
+        _ => seed_range,
+    };
+    let (first_position, end_position) = match (args.shard, seed_range) {
+        (Some(shard), None) => {
+            let (first, end) = shard.slice(grind_space.map_or(1 << 64, u128::from));
+            if first == end {
+                return Err(no_seeds(shard));
+            }
+            (first as u64, end)
+        }
+        _ => (0, u128::MAX),
+    };
+
+    // A `--sync-url` grind starts from the checkpoint and results the last one to stop
+    // uploaded there, if any, the results only on a machine that has none of its own
+    let bucket = args.sync_url.as_deref().map(Bucket::new).transpose()?;
+    let mut resume = args.resume.clone();
+    if let (Some(bucket), Some(path)) = (&bucket, &args.checkpoint) {
+        let download = |path: &Path| {
+            let contents = bucket.get(&synced(path))?;
+            if let Some(contents) = &contents {
+                std::fs::write(path, contents)
+                    .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+                if !args.quiet {
+                    info!("downloaded {}", bucket.object_url(&synced(path)));
+                }
+            }
+            Ok::<_, String>(contents.is_some())
+        };
+        if download(path)? {
+            resume = Some(path.clone());
+        }
+        if !args.output.exists() {
+            download(&args.output)?;
+        }
+    }
+
+    // A `--resume`d grind goes on through the same seeds, chunked the same way
+    let resumed = resume.as_deref().map(Checkpoint::read).transpose()?;
+
+    // Rotates where the seeds start, so that separate runs grind different seeds
+    let offset = match (&resumed, job_hash) {
+        (Some(checkpoint), _) => checkpoint.offset,
+        (None, Some(hash)) => u64::from_le_bytes(hash[..8].try_into().unwrap()),
+        (None, None) => rand::random(),
+    };
+
+    // Seeds are handed out a chunk at a time from a cursor the threads share, so that the
+    // ones that grind faster (on P-cores, say) take on more of them rather than idling
+    // while the slower ones finish an equal share. A range of seeds is searched once, in
+    // order. Otherwise the threads go around all of the seeds together, from a random
+    // point so that separate runs differ, with chunks small enough that no two threads
+    // ever grind the same seed at once. Workers sharing a `--queue` have to chunk the
+    // seeds the same way, whatever their `--threads`.
+    let chunk_len = match (&resumed, seed_range, grind_space) {
+        (Some(checkpoint), _, _) => checkpoint.chunk_len,
+        (None, None, Some(space)) if queue.is_some() => ITER_BATCH_SIZE.min(space),
+        (None, None, Some(space)) => ITER_BATCH_SIZE.min((space / args.threads).max(1)),
+        (None, Some((range_start, range_end)), _) if queue.is_none() => {
+            ITER_BATCH_SIZE.min(((range_end - range_start) / args.threads).max(1))
+        }
+        _ => ITER_BATCH_SIZE,
+    };
+    // The chunk `position` seeds along: one before its first seed (since seeds are
+    // incremented before use) and how many seeds it has, or `None` past the range
+    let chunk_at = move |position: u64| match seed_range {
+        Some((range_start, range_end)) => {
+            let start = range_start
+                .checked_add(position)
+                .filter(|start| *start < range_end)?;
+            let seed = match seed_space {
+                Some(space) => (start + space - 1) % space,
+                None => start.wrapping_sub(1),
+            };
+            Some((
+                seed & !seed_mask | seed_value,
+                chunk_len.min(range_end - start),
+            ))
+        }
+        None => {
+            let left = end_position
+                .checked_sub(position.into())
+                .filter(|left| *left > 0)?;
+            let start = match grind_space {
+                Some(space) => {
+                    let start = ((offset % space) as u128 + position as u128) % space as u128;
+                    (start as u64).checked_sub(1).unwrap_or(space - 1)
+                }
+                None => offset.wrapping_add(position).wrapping_sub(1),
+            };
+            Some((
+                index_to_seed(start),
+                chunk_len.min(left.try_into().unwrap_or(u64::MAX)),
+            ))
+        }
+    };
+    let cursor = Arc::new(match &resumed {
+        Some(checkpoint) => Cursor::new(checkpoint.next, checkpoint.unfinished.clone(), None),
+        None => Cursor::new(first_position, vec![], queue.clone()),
+    });
+    let next_chunk =
+        move |cursor: &Cursor, thread: usize| cursor.take(thread, chunk_len).and_then(chunk_at);
+    // The first seed each thread searches, as shown to the user
+    let first_seed = |seed: u64| {
+        let first = (seed | args.seed_mask).wrapping_add(1) & !args.seed_mask | args.seed_value;
+        seed_space.map_or(first, |space| first % space)
+    };
+
+    // Filler for `--seed-len` seed bytes past the 8 counter bytes. A search over a
+    // range of seeds starts every charset seed from the first character instead.
+    let seed_tail = match &resumed {
+        Some(checkpoint) => checkpoint.seed_tail,
+        None => {
+            let mut seed_tail = match job_hash {
+                _ if seed_range.is_some() => [0; 32],
+                Some(hash) => Sha256::digest(hash).into(),
+                None => rand::random::<[u8; 32]>(),
+            };
+            if let Some(charset) = charset {
+                for b in seed_tail.iter_mut() {
+                    *b = charset[*b as usize % charset.len()];
+                }
+            }
+            seed_tail
+        }
+    };
+
+    let results = (!args.no_file)
+        .then(|| {
+            let output = if args.output_per_run {
+                per_run_path(&args.output)
+            } else {
+                args.output.clone()
+            };
+            if args.output_per_run && !args.quiet {
+                info!("writing matches to {}", output.display());
+            }
+            ResultsFile::open(&output, args.format, args.owner, args.fsync)
+                .map_err(|e| format!("failed to open {}: {e}", output.display()))
+        })
+        .transpose()?;
+    let mut db = args
+        .db
+        .as_ref()
+        .map(|path| {
+            ResultsDb::open(path, args.owner)
+                .map_err(|e| format!("failed to open {}: {e}", path.display()))
+        })
+        .transpose()?;
+    // A `--resume`d shard was claimed by the grind that started it
+    if let (Some(db), Some(shard), Some(job), None) = (&mut db, args.shard, &job, &resumed) {
+        match db.claim(job, shard) {
+            Ok(claims) => {
+                for claim in claims {
+                    warn!(
+                        "shard {} of job {job}, taken by pid {} at {}, has seeds in \
+                         common with shard {shard}",
+                        claim.shard,
+                        claim.pid,
+                        format_utc(claim.claimed_at)
+                    );
+                }
+            }
+            Err(e) => error!("failed to claim the shard in {}: {e}", db.path().display()),
+        }
+    }
+    let output = results.as_ref().map(|results| results.path().to_owned());
+    let seen = results
+        .as_ref()
+        .map_or_else(HashSet::new, ResultsFile::read_found);
+    let webhook = args
+        .webhook
+        .clone()
+        .map(|url| Webhook::spawn(url, args.owner, start_time));
+    let (dashboard_sender, dashboard_matches) = mpsc::channel();
+    let sinks: Vec<Box<dyn ResultSink>> = [
+        results.map(|results| Box::new(results) as _),
+        db.map(|db| Box::new(db) as _),
+        queue.clone().map(|queue| Box::new(queue) as _),
+        webhook.map(|webhook| Box::new(webhook) as _),
+        args.tui.then(|| Box::new(Channel(dashboard_sender)) as _),
+    ]
+    .into_iter()
+    .flatten()
+    .chain(sinks)
+    .collect();
+    // `--check-unused` looks matches up on the writer's thread, so it runs even with
+    // nothing to write them to
+    let rpc = args
+        .check_unused
+        .then(|| Rpc::new(args.rpc.clone().unwrap()));
+    let writer =
+        (!sinks.is_empty() || rpc.is_some()).then(|| ResultsWriter::spawn(seen, sinks, rpc));
+
+    if let (Some(path), Some(checkpoint)) = (&resume, &resumed) {
+        checkpoint.check(path, &search)?;
+        if !args.quiet {
+            info!("resuming from {}", path.display());
+        }
+    }
+    let checkpoint_path = args.checkpoint.as_ref().or(resume.as_ref());
+    let checkpoint = || {
+        let (next, unfinished) = cursor.position();
+        Checkpoint {
+            search: search.clone(),
+            offset,
+            seed_tail,
+            chunk_len,
+            next,
+            unfinished,
+        }
+    };
+
+    // Enough to audit the run, or to pick a range back up where it stopped
+    let mut manifest = (!args.no_manifest).then(|| {
+        let bump_mode = args.bump_mode.to_possible_value().unwrap();
+        let check_order = args.check_order.to_possible_value().unwrap();
+        let hasher = args
+            .hasher
+            .map(|hasher| hasher.to_possible_value().unwrap());
+        let first = chunk_at(first_position).map(|(seed, _)| first_seed(seed));
+        serde_json::json!({
+            "owner": args.owner.to_string(),
+            "target": args.target,
+            "suffix": args.suffix,
+            "anywhere": args.anywhere,
+            "regex": args.regex,
+            "fuzzy": args.fuzzy,
+            "min_len": args.min_len,
+            "bump_mode": bump_mode.get_name(),
+            "batch_size": args.batch_size,
+            "lookahead": args.lookahead,
+            "check_order": check_order.get_name(),
+            "bump_depth": args.bump_depth,
+            "hasher": hasher.map(|hasher| hasher.get_name().to_owned()),
+            "gpu": args.gpu.map(|gpu| gpu.to_possible_value().unwrap().get_name().to_owned()),
+            "gpu_device": args.gpu.map(|_| match args.gpu_device {
+                GpuDevice::All => "all".into(),
+                GpuDevice::Index(index) => serde_json::Value::from(index),
+            }),
+            "gpu_batch": args.gpu_batch,
+            "seeds": template.to_string(),
+            "seed_kind": seed_desc,
+            "seed_mask": args.seed_mask,
+            "seed_value": args.seed_value,
+            "seed_range": seed_range,
+            "shard": args.shard.map(|shard| shard.to_string()),
+            "job": job,
+            "offset": offset,
+            "threads": args.threads,
+            "first_seed": first,
+            "chunk_len": chunk_len,
+            "output": output,
+            "db": args.db,
+            "pid": std::process::id(),
+            "started_at": format_utc(unix_now()),
+        })
+    });
+    if let Some(manifest) = &manifest {
+        if let Err(e) = write_manifest(&args.manifest, manifest) {
+            error!("failed to write {}: {e}", args.manifest.display());
+        }
+    }
+
+    // `--bump-mode any` takes a batch of seeds through their bumps together when curve
+    // checks can be batched too, and otherwise one seed at a time. Word seeds are always
+    // hashed one at a time, see below.
+    let batch_any = args.bump_mode == BumpMode::Any && curve::batched();
+    let backend = args.hasher.unwrap_or_else(|| {
+        Backend::detect((args.bump_mode == BumpMode::First || batch_any) && wordlist.is_none())
+    });
+
+    // `--gpu` hashes on the GPU too, from one thread that hands it chunks of seeds from
+    // the same cursor as the others and finishes off the candidates it sends back
+    let gpu = args
+        .gpu
+        .map(|gpu| {
+            let ground_pos = fixed_before.len();
+            let counter_len = seed_len.min(8);
+            let big_endian = seed_kind == SeedKind::Int(Endianness::Be);
+            let layout = Layout {
+                preimage: [
+                    &fixed_before[..],
+                    &seed_tail[..seed_len],
+                    &fixed_after[..],
+                    &[u8::MAX],
+                    args.owner.as_ref(),
+                    PDA_MARKER,
+                ]
+                .concat(),
+                counter_pos: if big_endian {
+                    ground_pos + seed_len - counter_len
+                } else {
+                    ground_pos
+                },
+                counter_len,
+                big_endian,
+                bump_pos: ground_pos + seed_len + fixed_after.len(),
+                lookahead: args.lookahead as usize,
+            };
+            let matcher = TargetMatcher::new(&args, custom.clone());
+            let ranges = matcher.raw_ranges().expect("--gpu requires a --target");
+            let grinders = args.gpu_device.indices(gpu).and_then(|devices| {
+                devices
+                    .into_iter()
+                    .map(|device| {
+                        GpuGrinder::new(gpu, device, &layout, ranges, chunk_len)
+                            .map(|grinder| (device, grinder))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })?;
+            Ok::<_, String>((grinders, layout))
+        })
+        .transpose()?;
+    // A thread feeds each GPU, and the rest grind alongside them
+    let gpu_count = gpu.as_ref().map_or(0, |(grinders, _)| grinders.len());
+    let cpu_threads = args.threads.saturating_sub(gpu_count as u64);
+    if !args.quiet {
+        for (device, grinder) in gpu.iter().flat_map(|(grinders, _)| grinders) {
+            info!("hashing on GPU {device}, {}", grinder.name());
+        }
+        if gpu_count == 0 {
+            info!("hashing with {}", backend.name());
+        } else if cpu_threads > 0 {
+            let plural = if cpu_threads == 1 { "" } else { "s" };
+            info!(
+                "hashing with {} on {cpu_threads} more thread{plural}",
+                backend.name()
+            );
+        }
+    }
+
+    // Before the grind starts, as a named pipe only opens once something reads from it
+    let mut stats_json = args
+        .stats_json
+        .as_deref()
+        .map(logging::stats_json_writer)
+        .transpose()?;
+
+    // The energy the CPU uses from here on, where it can be read and there is somewhere
+    // to report it
+    let mut energy = (!args.quiet || manifest.is_some() || stats_json.is_some())
+        .then(|| {
+            EnergyMeter::open()
+                .inspect_err(|e| {
+                    if args.verbose {
+                        info!("no energy readings: {e}");
+                    }
+                })
+                .ok()
+        })
+        .flatten();
+    let mut joules = None;
+
+    if let Some(on_start) = on_start {
+        on_start();
+    }
+    // Seeds each CPU thread has searched, for the stats to compare them
+    let thread_searched: Arc<Vec<AtomicU64>> =
+        Arc::new((0..cpu_threads).map(|_| AtomicU64::new(0)).collect());
+    let mut handles = (0..cpu_threads)
+        .map(|i| {
+            let matcher = TargetMatcher::new(&args, custom.clone());
+            let thread_searched = thread_searched.clone();
+            let fixed_before = fixed_before.clone();
+            let fixed_after = fixed_after.clone();
+            let wordlist = wordlist.clone();
+            let reporter = Reporter {
+                state: state.clone(),
+                results: writer.as_ref().map(ResultsWriter::sender),
+                seed_kind,
+                quiet: args.quiet,
+                target_len: args.target.as_ref().map_or(0, String::len),
+                tui: args.tui,
+                seed_match_rate,
+                keypair_dir: grind_keys.then(|| args.keypair_dir.clone()),
+                emit: args.emit.clone(),
+                template: template.clone(),
+                owner: args.owner,
+            };
+            let cursor = cursor.clone();
+            let state = state.clone();
+            let stopped = stopped.clone();
+            let (mut seed, mut remaining) = next_chunk(&cursor, i as usize).unwrap_or_default();
+            let first = first_seed(seed);
+            std::thread::Builder::new()
+                // Release builds need next to none, but unoptimized ones (which tests run
+                // grinds in) keep far more of the loop's state on the stack
+                .stack_size(if cfg!(debug_assertions) { 8 << 20 } else { 512 })
+                .spawn(move || {
+                    let _span = info_span!("cpu", thread = i).entered();
+                    let started = Instant::now();
+                    let mut searched = 0;
+
+                    // 8-byte aligned buffer, large enough for 15 seeds of 32 bytes
+                    //
+                    // Note: we only use `preimage_len` bytes!
+                    // [fixed seeds][seed_len byte seed][fixed seeds][u8 bump][32 byte owner key][21 byte PDA_MARKER]
+                    // fixed + seed_len + 1 + 32 + 21 = fixed + seed_len + 54 (62 for a lone u64)
+                    let ground_pos = fixed_before.len();
+                    let mut ground_len = seed_len;
+                    let mut bump_pos = ground_pos + seed_len + fixed_after.len();
+                    let mut preimage_len = bump_pos + 54;
+                    let mut buffer = [0_u64; 68];
+                    let buffer_ptr: *mut u8 = buffer.as_mut_ptr().cast();
+
+                    // Write in fixed seeds, seed filler, owner, and pda marker
+                    unsafe {
+                        core::ptr::copy_nonoverlapping(
+                            fixed_before.as_ptr(),
+                            buffer_ptr,
+                            fixed_before.len(),
+                        );
+                        core::ptr::copy_nonoverlapping(
+                            seed_tail.as_ptr(),
+                            buffer_ptr.add(ground_pos),
+                            seed_len,
+                        );
+                        core::ptr::copy_nonoverlapping(
+                            fixed_after.as_ptr(),
+                            buffer_ptr.add(ground_pos + seed_len),
+                            fixed_after.len(),
+                        );
+
+                        let owner_ptr: *mut Pubkey = buffer_ptr.add(bump_pos + 1).cast();
+                        *owner_ptr = args.owner;
+
+                        let marker_ptr: *mut [u8; 21] = buffer_ptr.add(bump_pos + 33).cast();
+                        *marker_ptr = *PDA_MARKER;
+                    }
+
+                    let set_bump = {
+                        #[inline(always)]
+                        |buffer_ptr: *mut u8, bump_pos: usize, offset: u8| unsafe {
+                            let pda_ptr: *mut u8 = buffer_ptr.add(bump_pos);
+                            *pda_ptr = u8::MAX - offset;
+                        }
+                    };
+
+                    // Only the (up to) 8 counter bytes change between seeds. They are the
+                    // low-order bytes: first for little-endian, last for big-endian.
+                    let counter_len = seed_len.min(8);
+                    let big_endian = seed_kind == SeedKind::Int(Endianness::Be);
+                    let counter_pos = if big_endian {
+                        ground_pos + seed_len - counter_len
+                    } else {
+                        ground_pos
+                    };
+                    let set_seed = {
+                        #[inline(always)]
+                        |buffer_ptr: *mut u8, seed: u64| unsafe {
+                            let (bytes, skip) = if big_endian {
+                                (seed.to_be_bytes(), 8 - counter_len)
+                            } else {
+                                (seed.to_le_bytes(), 0)
+                            };
+                            core::ptr::copy_nonoverlapping(
+                                bytes.as_ptr().add(skip),
+                                buffer_ptr.add(counter_pos),
+                                counter_len,
+                            );
+                        }
+                    };
+
+                    // With a charset, the seed is an odometer over charset indices
+                    // (least significant first) instead of the little-endian counter.
+                    // The filler counts as a number in base charset.len() and the
+                    // counter is added to it across every digit, so no two counters
+                    // give the same seed
+                    let load_charset_seed = {
+                        |buffer_ptr: *mut u8, digits: &mut [u8; 32], charset: &[u8], seed: u64| {
+                            let base = charset.len() as u64;
+                            let mut value = seed;
+                            let mut carry = 0;
+                            for (j, digit) in digits[..seed_len].iter_mut().enumerate() {
+                                let filler =
+                                    charset.iter().position(|c| *c == seed_tail[j]).unwrap();
+                                let sum = filler as u64 + value % base + carry;
+                                *digit = (sum % base) as u8;
+                                carry = sum / base;
+                                value /= base;
+                                unsafe {
+                                    *buffer_ptr.add(ground_pos + j) = charset[*digit as usize]
+                                };
+                            }
+                        }
+                    };
+                    let mut charset_digits = [0_u8; 32];
+                    if let Some(charset) = charset {
+                        load_charset_seed(buffer_ptr, &mut charset_digits, charset, seed);
+                    }
+                    let next_charset_seed = {
+                        #[inline(always)]
+                        |buffer_ptr: *mut u8, digits: &mut [u8; 32], charset: &[u8]| {
+                            for (j, digit) in digits[..seed_len].iter_mut().enumerate() {
+                                *digit += 1;
+                                let carry = *digit as usize == charset.len();
+                                if carry {
+                                    *digit = 0;
+                                }
+                                unsafe {
+                                    *buffer_ptr.add(ground_pos + j) = charset[*digit as usize]
+                                };
+                                if !carry {
+                                    break;
+                                }
+                            }
+                        }
+                    };
+
+                    let get_preimage = {
+                        #[inline(always)]
+                        |buffer_ptr: *mut u8, preimage_len: usize| -> &[u8] {
+                            unsafe { core::slice::from_raw_parts(buffer_ptr, preimage_len) }
+                        }
+                    };
+
+                    // With a `--template`, the secret keys of the staged seeds' keypairs
+                    let mut secrets: Vec<[u8; 32]> = vec![];
+
+                    // With a wordlist, everything after the seed moves with its length
+                    let mut word_seed = [0_u8; 32];
+                    let word_tail = get_preimage(buffer_ptr, preimage_len)[ground_pos..].to_vec();
+
+                    // Only for `--bump-mode first`, see the README
+                    let lookahead = args.lookahead as usize;
+
+                    // `--bump-mode first` candidates, laid out and hashed a batch at a
+                    // time. Word seeds vary in length, so they can't share a batch.
+                    let batch = match (&wordlist, args.batch_size) {
+                        (Some(_), _) => lookahead,
+                        (None, Some(seeds)) => seeds as usize * lookahead,
+                        // Half of a `--bump-mode any` batch drops out at each bump, so
+                        // start with enough for the lanes to stay busy for a few
+                        (None, None) if batch_any => 32 * backend.lanes().max(curve::LANES),
+                        (None, None) => backend.lanes().next_multiple_of(lookahead),
+                    };
+                    let mut off_curve = vec![false; batch];
+                    // With nothing to rule candidates out on the raw hash, they are all
+                    // encoded, a batch at a time
+                    let encode_all = matcher.encodes_everything() && base58::batched();
+                    let mut encoded: Vec<base58::Encoded> = vec![([0; 44], 0); batch];
+                    let mut hasher = MultiHasher::new(backend, batch);
+                    let mut staged = 0;
+
+                    // `auto` starts out matching first, then goes by how many of the
+                    // candidates matched so far
+                    let mut curve_first = args.check_order == CheckOrder::CurveFirst;
+                    let (mut checked, mut hits) = (0_u64, 0_u64);
+                    // Only for `--bump-mode any`, added to the `State`'s counts with the seeds searched
+                    let mut canonical_bumps = [0_u64; 255];
+
+                    if args.verbose && remaining > 0 {
+                        info!(
+                            "thread {i} starting at counter {first}, \
+                             hashing {preimage_len} byte preimages"
+                        );
+                    }
+
+                    let profile = Profiler::new(args.profile);
+
+                    let mut bs58_bytes = [0; 44];
+                    // How each of a seed's `--bump-mode first` candidates matched, as far as
+                    // they were matched
+                    let mut matches = [MatchKind::Miss; MAX_LOOKAHEAD];
+
+                    loop {
+                        // Done with this chunk (or there wasn't one left to start with),
+                        // on to the next
+                        if remaining == 0 {
+                            cursor.finish(i as usize);
+                            let Some(chunk) = next_chunk(&cursor, i as usize) else {
+                                break;
+                            };
+                            (seed, remaining) = chunk;
+                            if let Some(charset) = charset {
+                                load_charset_seed(buffer_ptr, &mut charset_digits, charset, seed);
+                            }
+                        }
+                        let batch_remaining = remaining;
+                        for n in 0..CHECK_EVERY {
+                            if remaining == 0 {
+                                break;
+                            }
+                            remaining -= 1;
+                            // Step through the free bits only, keeping the masked ones
+                            seed = (seed | args.seed_mask).wrapping_add(1) & !args.seed_mask
+                                | args.seed_value;
+                            if let Some(wordlist) = &wordlist {
+                                let Some(len) = wordlist.write(seed, &mut word_seed) else {
+                                    continue;
+                                };
+                                unsafe {
+                                    core::ptr::copy_nonoverlapping(
+                                        word_seed.as_ptr(),
+                                        buffer_ptr.add(ground_pos),
+                                        len,
+                                    );
+                                    core::ptr::copy_nonoverlapping(
+                                        word_tail.as_ptr(),
+                                        buffer_ptr.add(ground_pos + len),
+                                        word_tail.len(),
+                                    );
+                                }
+                                ground_len = len;
+                                bump_pos = ground_pos + len + fixed_after.len();
+                                preimage_len = bump_pos + 54;
+                            } else if grind_keys {
+                                let (secret, public) = template::new_keypair();
+                                unsafe {
+                                    core::ptr::copy_nonoverlapping(
+                                        public.as_ptr(),
+                                        buffer_ptr.add(ground_pos),
+                                        32,
+                                    );
+                                }
+                                secrets.push(secret);
+                            } else {
+                                match charset {
+                                    Some(charset) => {
+                                        next_charset_seed(buffer_ptr, &mut charset_digits, charset)
+                                    }
+                                    None => set_seed(buffer_ptr, seed),
+                                }
+                            }
+
+                            match args.bump_mode {
+                                BumpMode::First => {
+                                    // Stage this seed's candidates, and only hash once there
+                                    // are enough of them to fill the batch (or the iteration
+                                    // batch ends)
+                                    for bump_offset in 0..lookahead as u8 {
+                                        set_bump(buffer_ptr, bump_pos, bump_offset);
+                                        hasher.set_len(preimage_len);
+                                        hasher.msg_mut(staged).copy_from_slice(get_preimage(
+                                            buffer_ptr,
+                                            preimage_len,
+                                        ));
+                                        staged += 1;
+                                    }
+                                    if staged + lookahead <= batch
+                                        && remaining > 0
+                                        && n + 1 < CHECK_EVERY
+                                    {
+                                        continue;
+                                    }
+
+                                    // Calculate all staged candidate addresses at once
+                                    let hash_timer = profile.start(Stage::Hash);
+                                    hasher.hash(staged);
+                                    profile.stop(Stage::Hash, hash_timer);
+                                    let hashed = std::mem::take(&mut staged);
+
+                                    // Every candidate is about to be encoded, so do them all
+                                    // at once
+                                    if encode_all && !curve_first {
+                                        let bs58_timer = profile.start(Stage::Encode);
+                                        base58::encode_batch(
+                                            &hasher.hashes()[..hashed],
+                                            &mut encoded,
+                                        );
+                                        profile.stop(Stage::Encode, bs58_timer);
+                                    }
+
+                                    for first in (0..hashed).step_by(lookahead) {
+                                        let candidates = &hasher.hashes()[first..first + lookahead];
+                                        let seed_bytes = || {
+                                            &hasher.msg(first)[ground_pos..ground_pos + ground_len]
+                                        };
+
+                                        let mut match_candidate = |i: usize| {
+                                            let candidate = &candidates[i];
+                                            let bs58 = if encode_all && !curve_first {
+                                                let (bytes, len) = &encoded[first + i];
+                                                &bytes[..*len as usize]
+                                            } else {
+                                                // Most candidates are ruled out on the raw
+                                                // hash, leaving them as misses without
+                                                // encoding them
+                                                if !matcher.may_match(candidate) {
+                                                    return MatchKind::Miss;
+                                                }
+
+                                                // Encode hash
+                                                let bs58_timer = profile.start(Stage::Encode);
+                                                let len =
+                                                    five8::encode_32(candidate, &mut bs58_bytes);
+                                                profile.stop(Stage::Encode, bs58_timer);
+                                                &bs58_bytes[..len as usize]
+                                            };
+
+                                            // Check if we have target string
+                                            let match_timer = profile.start(Stage::Match);
+                                            let match_kind = matcher.check(candidate, bs58);
+                                            profile.stop(Stage::Match, match_timer);
+                                            match_kind
+                                        };
+
+                                        // Matching first matches every candidate, curve
+                                        // checking first only the canonical one
+                                        let bump = bump::canonical_bump_matches(
+                                            candidates,
+                                            curve_first,
+                                            |candidate| {
+                                                let offc_timer = profile.start(Stage::Curve);
+                                                let is_off_curve = curve::off_curve(candidate);
+                                                profile.stop(Stage::Curve, offc_timer);
+                                                is_off_curve
+                                            },
+                                            |i| {
+                                                matches[i] = match_candidate(i);
+                                                checked += 1;
+                                                hits += u64::from(matches[i] != MatchKind::Miss);
+                                                matches[i] != MatchKind::Miss
+                                            },
+                                        );
+                                        if let Some(bump) = bump {
+                                            let offset = (u8::MAX - bump) as usize;
+                                            let key: &Pubkey =
+                                                unsafe { &*candidates[offset].as_ptr().cast() };
+                                            reporter.report(
+                                                key,
+                                                bump,
+                                                matches[offset],
+                                                seed_bytes(),
+                                            );
+                                            if matches[offset] == MatchKind::Full {
+                                                if let Some(secret) = secrets.get(first / lookahead)
+                                                {
+                                                    reporter.save_keypair(secret);
+                                                }
+                                            }
+                                        }
+                                    }
+                                    secrets.clear();
+                                }
+                                BumpMode::Any if batch_any => {
+                                    // Stage this seed at the first bump, like above
+                                    set_bump(buffer_ptr, bump_pos, 0);
+                                    hasher.set_len(preimage_len);
+                                    hasher
+                                        .msg_mut(staged)
+                                        .copy_from_slice(get_preimage(buffer_ptr, preimage_len));
+                                    staged += 1;
+                                    if staged < batch && remaining > 0 && n + 1 < CHECK_EVERY {
+                                        continue;
+                                    }
+
+                                    // Take the staged seeds down their bumps together, hashing
+                                    // and curve checking the ones still on the curve at once
+                                    let mut active = std::mem::take(&mut staged);
+                                    for bump_offset in 0..bump_depth {
+                                        if active == 0 {
+                                            break;
+                                        }
+                                        let hash_timer = profile.start(Stage::Hash);
+                                        hasher.hash(active);
+                                        profile.stop(Stage::Hash, hash_timer);
+
+                                        let offc_timer = profile.start(Stage::Curve);
+                                        curve::off_curve_batch(
+                                            &hasher.hashes()[..active],
+                                            &mut off_curve,
+                                        );
+                                        profile.stop(Stage::Curve, offc_timer);
+
+                                        // Seeds with an address off the curve are done, the
+                                        // rest move up to the front for the next bump
+                                        let mut kept = 0;
+                                        for (slot, &off_curve) in
+                                            off_curve[..active].iter().enumerate()
+                                        {
+                                            if !off_curve {
+                                                hasher.copy_msg(slot, kept);
+                                                hasher.msg_mut(kept)[bump_pos] -= 1;
+                                                kept += 1;
+                                                continue;
+                                            }
+                                            canonical_bumps[bump_offset as usize] += 1;
+                                            let hash_bytes = hasher.hashes()[slot];
+                                            if !matcher.may_match(&hash_bytes) {
+                                                continue;
+                                            }
+
+                                            let bs58_timer = profile.start(Stage::Encode);
+                                            let len =
+                                                five8::encode_32(&hash_bytes, &mut bs58_bytes);
+                                            profile.stop(Stage::Encode, bs58_timer);
+
+                                            let match_timer = profile.start(Stage::Match);
+                                            let match_kind = matcher
+                                                .check(&hash_bytes, &bs58_bytes[..len as usize]);
+                                            profile.stop(Stage::Match, match_timer);
+                                            let key: &Pubkey =
+                                                unsafe { &*hash_bytes.as_ptr().cast() };
+                                            reporter.report(
+                                                key,
+                                                u8::MAX - bump_offset,
+                                                match_kind,
+                                                &hasher.msg(slot)
+                                                    [ground_pos..ground_pos + ground_len],
+                                            );
+                                        }
+                                        active = kept;
+                                    }
+                                }
+                                BumpMode::Any => {
+                                    // Only the bump changes from here on
+                                    hasher.set_len(preimage_len);
+                                    hasher
+                                        .msg_mut(0)
+                                        .copy_from_slice(get_preimage(buffer_ptr, preimage_len));
+                                    hasher.fix_all_but(bump_pos);
+
+                                    'bump: for bump_offset in 0..bump_depth {
+                                        // Hash to get candidate address
+                                        let hash_timer = profile.start(Stage::Hash);
+                                        hasher.hash_byte(u8::MAX - bump_offset);
+                                        let hash_bytes = hasher.hashes()[0];
+                                        profile.stop(Stage::Hash, hash_timer);
+
+                                        // Check if candidate address is off-curve
+                                        let offc_timer = profile.start(Stage::Curve);
+                                        let key: &Pubkey = unsafe { &*hash_bytes.as_ptr().cast() };
+                                        let is_off_curve = curve::off_curve(&hash_bytes);
+                                        profile.stop(Stage::Curve, offc_timer);
+
+                                        if is_off_curve {
+                                            canonical_bumps[bump_offset as usize] += 1;
+
+                                            // Most candidates are ruled out on the raw hash without
+                                            // encoding them
+                                            if !matcher.may_match(&hash_bytes) {
+                                                break 'bump;
+                                            }
+
+                                            // base58 encode
+                                            let bs58_timer = profile.start(Stage::Encode);
+                                            let len =
+                                                five8::encode_32(&hash_bytes, &mut bs58_bytes);
+                                            profile.stop(Stage::Encode, bs58_timer);
+
+                                            let match_timer = profile.start(Stage::Match);
+                                            let match_kind = matcher
+                                                .check(&hash_bytes, &bs58_bytes[..len as usize]);
+                                            profile.stop(Stage::Match, match_timer);
+                                            let preimage = get_preimage(buffer_ptr, preimage_len);
+                                            reporter.report(
+                                                key,
+                                                u8::MAX - bump_offset,
+                                                match_kind,
+                                                &preimage[ground_pos..ground_pos + ground_len],
+                                            );
+                                            break 'bump;
+                                        }
+                                    }
+                                }
+                            }
+                        }
+
+                        for (total, count) in state.canonical_bumps.iter().zip(&mut canonical_bumps)
+                        {
+                            if *count > 0 {
+                                total.fetch_add(std::mem::take(count), Ordering::Relaxed);
+                            }
+                        }
+                        profile.flush(&state);
+                        state
+                            .searched
+                            .fetch_add(batch_remaining - remaining, Ordering::Relaxed);
+                        thread_searched[i as usize]
+                            .fetch_add(batch_remaining - remaining, Ordering::Relaxed);
+                        searched += batch_remaining - remaining;
+
+                        if args.check_order == CheckOrder::Auto && checked > 0 {
+                            let match_rate = hits as f64 / checked as f64;
+                            let cheaper = curve_first_is_cheaper(match_rate, lookahead);
+                            if cheaper != curve_first && args.verbose {
+                                let order = if cheaper { "curve-first" } else { "hash-first" };
+                                info!(
+                                    "thread {i} switching to {order} checks, {:.2}% of \
+                                     candidates match",
+                                    match_rate * 100.0
+                                );
+                            }
+                            curve_first = cheaper;
+                        }
+
+                        while state.paused() && !stopped() {
+                            std::thread::sleep(Duration::from_millis(100));
+                        }
+                        if deadline.is_some_and(|d| Instant::now() >= d) || stopped() {
+                            break;
+                        }
+                    }
+                    // A thread stopped partway through a chunk leaves it unfinished, for a
+                    // checkpoint (or the `--queue`) to hand out again
+                    if remaining == 0 {
+                        cursor.finish(i as usize);
+                    }
+                    Ok((searched, started.elapsed()))
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    // Seeds searched on each GPU, for the stats
+    let mut gpus = Vec::new();
+    let gpu_threads = gpu.into_iter().flat_map(|(grinders, layout)| {
+        grinders
+            .into_iter()
+            .map(move |(device, grinder)| (device, grinder, layout.clone()))
+    });
+    for (thread, (device, mut grinder, layout)) in (cpu_threads as usize..).zip(gpu_threads) {
+        let matcher = TargetMatcher::new(&args, custom.clone());
+        let gpu_searched = Arc::new(AtomicU64::new(0));
+        gpus.push((format!("GPU {device}"), gpu_searched.clone()));
+        let reporter = Reporter {
+            state: state.clone(),
+            results: writer.as_ref().map(ResultsWriter::sender),
+            seed_kind,
+            quiet: args.quiet,
+            target_len: args.target.as_ref().map_or(0, String::len),
+            tui: args.tui,
+            seed_match_rate,
+            keypair_dir: None,
+            emit: args.emit.clone(),
+            template: template.clone(),
+            owner: args.owner,
+        };
+        let ground =
+            layout.bump_pos - fixed_after.len() - seed_len..layout.bump_pos - fixed_after.len();
+        let template = template.clone();
+        let owner = args.owner;
+        let cursor = cursor.clone();
+        let state = state.clone();
+        let stopped = stopped.clone();
+        let gpu_batch = args.gpu_batch.map(|seeds| (seeds / chunk_len) as usize);
+        let quiet = args.quiet;
+        handles.push(std::thread::spawn(move || {
+            let _span = info_span!("gpu", device).entered();
+            let started = Instant::now();
+            // A GPU that fails stops the whole grind, which returns its error
+            let failed = |e: String| {
+                state.stop.store(true, Ordering::Relaxed);
+                format!("GPU {device}: {e}")
+            };
+            let tuning = grinder.tune(gpu_batch).map_err(failed)?;
+            if !quiet {
+                info!(
+                    "GPU {device} launches {} seeds at a time, {} threads to a block",
+                    tuning.chunks as u64 * chunk_len,
+                    tuning.block_size
+                );
+            }
+
+            let mut searched = 0;
+            let mut preimage = layout.preimage.clone();
+            let mut bs58_bytes = [0; 44];
+            loop {
+                let chunks: Vec<_> = (0..tuning.chunks)
+                    .map_while(|_| next_chunk(&cursor, thread))
+                    .collect();
+                if chunks.is_empty() {
+                    break;
+                }
+                let mut hits = grinder.grind(&chunks).map_err(failed)?;
+
+                // The GPU only sends back canonical candidates, but rather than trust the
+                // kernel with the results, each one is derived again from its seeds
+                hits.sort_unstable();
+                for (seed, offset) in hits {
+                    layout.write(&mut preimage, seed, offset);
+                    let bump = u8::MAX - offset;
+                    let hashed: [u8; 32] = Sha256::digest(&preimage).into();
+                    let seeds = template.seeds(&preimage[ground.clone()]);
+                    let Some(key) = derive_canonical(&seeds, bump, &owner)
+                        .filter(|key| key.to_bytes() == hashed)
+                    else {
+                        state.gpu_false_positives.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    };
+                    if !matcher.may_match(&hashed) {
+                        continue;
+                    }
+                    let len = five8::encode_32(&hashed, &mut bs58_bytes);
+                    let match_kind = matcher.check(&hashed, &bs58_bytes[..len as usize]);
+                    if match_kind != MatchKind::Miss {
+                        reporter.report(&key, bump, match_kind, &preimage[ground.clone()]);
+                    }
+                }
+
+                cursor.finish(thread);
+                let hashed: u64 = chunks.iter().map(|(_, len)| len).sum();
+                state.searched.fetch_add(hashed, Ordering::Relaxed);
+                gpu_searched.fetch_add(hashed, Ordering::Relaxed);
+                searched += hashed;
+
+                while state.paused() && !stopped() {
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                if deadline.is_some_and(|d| Instant::now() >= d) || stopped() {
+                    break;
+                }
+            }
+            cursor.finish(thread);
+            Ok((searched, started.elapsed()))
+        }));
+    }
+
+    // A grind through a range (or `--exhaustive`) shows how far it got on a progress bar
+    // rather than in the stats lines, when they would go to a terminal
+    let range_len = seed_range
+        .filter(|_| queue.is_none() && !args.quiet && !args.tui && !args.no_progress)
+        .map(|(range_start, range_end)| range_end - range_start);
+    let progress_bar =
+        range_len.and_then(|len| logging::progress_bar(len, cursor.searched_below()));
+
+    // Stats, checkpoints and `--queue` leases come from this thread, so they keep to the
+    // clock however slow the grind is. A checkpoint is written a round late, by which time
+    // the matches in the chunks it counts as searched have been written out.
+    let mut next_stats = start_time + args.stats_every;
+    // Other `--queue` workers grind the range too, so this one can't tell how far it got
+    let range_searched = || {
+        seed_range
+            .filter(|_| queue.is_none())
+            .map(|(range_start, range_end)| {
+                // Threads take whole chunks, so the last can run past the end
+                (cursor.searched_below() as f64 / (range_end - range_start) as f64).min(1.0)
+            })
+    };
+    // When the stats were last printed, and what each CPU thread had searched by then
+    let mut last_stats = (start_time, vec![0; cpu_threads as usize]);
+    let mut next_checkpoint = start_time + args.checkpoint_every;
+    let mut last_checkpoint = None;
+    let mut next_renewal = start_time + RENEW_EVERY;
+    let mut last_progress = (start_time, 0);
+    let mut hashrate = Hashrate::new(start_time);
+    let tick = progress
+        .as_ref()
+        .map_or(args.stats_every, |(every, _)| args.stats_every.min(*every));
+    while !handles.iter().all(|handle| handle.is_finished()) {
+        std::thread::sleep(tick.min(Duration::from_millis(100)));
+        hashrate.sample(state.searched.load(Ordering::Relaxed));
+        // Every tick, so that RAPL counters can't wrap around more than once in between
+        joules = energy.as_mut().and_then(EnergyMeter::joules).or(joules);
+        if let Some(bar) = &progress_bar {
+            bar.set_position(cursor.searched_below());
+            let matches = state.matches.load(Ordering::Relaxed);
+            bar.set_message(match seed_match_rate {
+                _ if state.paused() => "paused".to_string(),
+                Some(rate) => format!(
+                    "matches {matches} (expected ~{:.1})",
+                    state.searched.load(Ordering::Relaxed) as f64 * rate
+                ),
+                None => format!("matches {matches}"),
+            });
+        }
+        if let Some(board) = &mut dashboard {
+            for found in dashboard_matches.try_iter() {
+                board.add(&found);
+            }
+            let devices = thread_searched
+                .iter()
+                .enumerate()
+                .map(|(i, searched)| (i.to_string(), searched.load(Ordering::Relaxed)))
+                .chain(
+                    gpus.iter()
+                        .map(|(name, searched)| (name.clone(), searched.load(Ordering::Relaxed))),
+                )
+                .collect();
+            let command = board.draw(&Snapshot {
+                searched: state.searched.load(Ordering::Relaxed),
+                elapsed: start_time.elapsed(),
+                matches: state.matches.load(Ordering::Relaxed),
+                partial: state.partial_matches.load(Ordering::Relaxed),
+                seed_match_rate,
+                range_searched: range_searched(),
+                paused: state.paused(),
+                devices,
+            });
+            match command {
+                Some(Command::Stop) => state.stop.store(true, Ordering::Relaxed),
+                Some(Command::TogglePause) => {
+                    state.paused.fetch_xor(true, Ordering::Relaxed);
+                }
+                None => {}
+            }
+        }
+        if Instant::now() >= next_renewal {
+            next_renewal = Instant::now() + RENEW_EVERY;
+            cursor.renew();
+        }
+        if let Some(path) = checkpoint_path.filter(|_| Instant::now() >= next_checkpoint) {
+            next_checkpoint = Instant::now() + args.checkpoint_every;
+            if let Some(last) = last_checkpoint.replace(checkpoint()) {
+                if let Err(e) = last.write(path) {
+                    error!("failed to write {}: {e}", path.display());
+                }
+            }
+        }
+        if let Some((every, on_progress)) = &mut progress {
+            if last_progress.0.elapsed() >= *every {
+                report_progress(&state, on_progress, start_time, &mut last_progress);
+            }
+        }
+        if Instant::now() < next_stats {
+            continue;
+        }
+        next_stats = Instant::now() + args.stats_every;
+
+        let searched = state.searched.load(Ordering::Relaxed);
+        let elapsed = start_time.elapsed().as_secs_f64();
+        // Each CPU thread's rate since the last stats
+        let counts: Vec<_> = thread_searched
+            .iter()
+            .map(|searched| searched.load(Ordering::Relaxed))
+            .collect();
+        let interval = last_stats.0.elapsed().as_secs_f64();
+        let rates: Vec<_> = counts
+            .iter()
+            .zip(&last_stats.1)
+            .map(|(now, before)| (now - before) as f64 / interval)
+            .collect();
+        let (moving_average, peak) = (hashrate.moving_average(), hashrate.peak);
+        if let Some(writer) = &mut stats_json {
+            let threads: Vec<_> = counts.iter().copied().zip(rates.iter().copied()).collect();
+            let mut record = stats_record(
+                &state,
+                elapsed,
+                seed_match_rate,
+                joules,
+                range_searched(),
+                &threads,
+                &gpus,
+            );
+            record["moving_average"] = moving_average.into();
+            record["peak_hashrate"] = peak.into();
+            record["paused"] = state.paused().into();
+            if let Err(e) = writeln!(writer, "{record}").and_then(|()| writer.flush()) {
+                error!("failed to write the JSON stats, so there will be no more: {e}");
+                stats_json = None;
+            }
+        }
+        last_stats = (Instant::now(), counts);
+        if args.quiet || args.tui || progress_bar.is_some() || state.paused() {
+            continue;
+        }
+
+        let progress = range_searched()
+            .map(|searched| format!("; {:.2}% of the seed range searched", searched * 100.0))
+            .unwrap_or_default();
+        let (average, matches, partial) = (
+            searched as f64 / elapsed,
+            state.matches.load(Ordering::Relaxed),
+            state.partial_matches.load(Ordering::Relaxed),
+        );
+        // How much work the matches so far took each, next to what a match takes on
+        // average, as for each match
+        let luck = seed_match_rate
+            .filter(|_| matches > 0)
+            .map(|rate| searched as f64 * rate / matches as f64);
+        // Seeds are equally likely to match wherever they are, so a match is as far off
+        // however long it has been since the last one
+        let expected = match seed_match_rate {
+            Some(rate) if searched > 0 => format!(
+                " (expected ~{:.1}, one every ~{} at this rate{})",
+                searched as f64 * rate,
+                format_eta(elapsed / (searched as f64 * rate)),
+                luck.map(|luck| format!(", {luck:.2}x the expected work each so far"))
+                    .unwrap_or_default()
+            ),
+            _ => String::new(),
+        };
+        let recent = match (moving_average, peak) {
+            (Some(rate), Some(peak)) => format!(
+                ", {rate:.0}/s over the last {:.0}s, peak {peak:.0}/s",
+                MOVING_AVERAGE.as_secs_f64().min(elapsed)
+            ),
+            _ => String::new(),
+        };
+        let power = joules
+            .map(|joules| energy_line(joules, searched, elapsed))
+            .unwrap_or_default();
+        info!(
+            searched,
+            hashrate = average,
+            moving_average,
+            peak,
+            matches,
+            partial,
+            luck,
+            joules,
+            "{searched} iters in {elapsed:.0}s ({average:.0}/s{recent}{}); matches \
+             {matches}{expected}; partial {partial}{}{power}{progress}",
+            device_rates(searched, cpu_threads, &gpus, elapsed),
+            gpu_false_positives(&state, &gpus),
+        );
+        if cpu_threads > 1 {
+            info!("{}", thread_rates(&rates));
+        }
+        if args.profile && cpu_threads > 0 {
+            let on_cpu = thread_searched
+                .iter()
+                .map(|searched| searched.load(Ordering::Relaxed))
+                .sum();
+            info!(
+                "{}",
+                profile_line(&state, elapsed * cpu_threads as f64, on_cpu)
+            );
+        }
+    }
+    if let Some(dashboard) = dashboard {
+        dashboard.finish();
+    }
+    if let Some(bar) = &progress_bar {
+        bar.set_position(cursor.searched_below());
+        logging::finish_progress_bar();
+    }
+    // Every thread is joined before any error is returned, as one that stopped early only
+    // stopped the others
+    let threads: Vec<Result<(u64, Duration), String>> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+    let joules = energy.as_mut().and_then(EnergyMeter::joules).or(joules);
+    if let Some(writer) = writer {
+        writer.finish();
+    }
+    if let Some((_, on_progress)) = &mut progress {
+        // With the hashrate over the whole grind
+        report_progress(&state, on_progress, start_time, &mut (start_time, 0));
+    }
+    cursor.leave();
+    if let Some(path) = checkpoint_path {
+        if let Err(e) = checkpoint().write(path) {
+            error!("failed to write {}: {e}", path.display());
+        }
+    }
+    let threads = threads.into_iter().collect::<Result<Vec<_>, _>>()?;
+    if let Some(bucket) = &bucket {
+        for path in checkpoint_path.into_iter().chain(&output) {
+            let uploaded = std::fs::read(path)
+                .map_err(|e| format!("failed to read {}: {e}", path.display()))
+                .and_then(|contents| bucket.put(&synced(path), &contents));
+            match uploaded {
+                Ok(()) if !args.quiet => {
+                    info!("uploaded {}", bucket.object_url(&synced(path)))
+                }
+                Ok(()) => {}
+                Err(e) => error!("{e}"),
+            }
+        }
+    }
+    if let Some(manifest) = &mut manifest {
+        let stopped_by = if stopped() {
+            "interrupt"
+        } else if deadline.is_some_and(|d| Instant::now() >= d) {
+            "deadline"
+        } else {
+            "done"
+        };
+        manifest["finished_at"] = format_utc(unix_now()).into();
+        manifest["stopped_by"] = stopped_by.into();
+        manifest["elapsed_secs"] = start_time.elapsed().as_secs_f64().into();
+        manifest["searched"] = state.searched.load(Ordering::Relaxed).into();
+        manifest["peak_hashrate"] = hashrate.peak.into();
+        manifest["joules"] = joules.into();
+        manifest["matches"] = state.matches.load(Ordering::Relaxed).into();
+        manifest["partial_matches"] = state.partial_matches.load(Ordering::Relaxed).into();
+        if args.gpu.is_some() {
+            manifest["gpu_false_positives"] =
+                state.gpu_false_positives.load(Ordering::Relaxed).into();
+        }
+        if let Some((range_start, range_end)) = seed_range.filter(|_| queue.is_none()) {
+            // A chunk a thread stopped partway through is still unfinished, so every seed
+            // before this one was searched (unless a `--resume` stopped before finishing the
+            // chunks it was left)
+            let next_seed = range_start.saturating_add(cursor.searched_below());
+            manifest["next_seed"] = next_seed.min(range_end).into();
+        }
+        if let Err(e) = write_manifest(&args.manifest, manifest) {
+            error!("failed to write {}: {e}", args.manifest.display());
+        }
+    }
+    if let Some(writer) = &mut stats_json {
+        // Each CPU thread's hashrate over the whole grind, as in the summary
+        let cpu: Vec<_> = threads[..cpu_threads as usize]
+            .iter()
+            .map(|(searched, elapsed)| (*searched, *searched as f64 / elapsed.as_secs_f64()))
+            .collect();
+        let mut record = stats_record(
+            &state,
+            start_time.elapsed().as_secs_f64(),
+            seed_match_rate,
+            joules,
+            range_searched(),
+            &cpu,
+            &gpus,
+        );
+        record["peak_hashrate"] = hashrate.peak.into();
+        record["finished"] = true.into();
+        if let Err(e) = writeln!(writer, "{record}").and_then(|()| writer.flush()) {
+            error!("failed to write the JSON stats: {e}");
+        }
+    }
+    if !args.quiet {
+        let searched = state.searched.load(Ordering::Relaxed);
+        let elapsed = start_time.elapsed().as_secs_f64();
+        let (average, matches, partial) = (
+            searched as f64 / elapsed,
+            state.matches.load(Ordering::Relaxed),
+            state.partial_matches.load(Ordering::Relaxed),
+        );
+        let peak = hashrate.peak;
+        let recent = peak
+            .map(|peak| format!(", peak {peak:.0}/s over {}s", MOVING_AVERAGE.as_secs()))
+            .unwrap_or_default();
+        let luck = seed_match_rate
+            .filter(|_| matches > 0)
+            .map(|rate| searched as f64 * rate / matches as f64);
+        let work = luck
+            .map(|luck| format!(" ({luck:.2}x the expected work each)"))
+            .unwrap_or_default();
+        // What the energy comes to for a match, which is what a grind is paid for
+        let power = match (joules, seed_match_rate) {
+            (Some(joules), Some(rate)) => {
+                let kwh = joules / (searched.max(1) as f64 * rate) / 3.6e6;
+                let per_match = match kwh >= 1.0 {
+                    true => format!("{kwh:.2} kWh"),
+                    false => format!("{:.2} Wh", kwh * 1000.0),
+                };
+                format!(
+                    "{}, {per_match} per expected match",
+                    energy_line(joules, searched, elapsed)
+                )
+            }
+            (Some(joules), None) => energy_line(joules, searched, elapsed),
+            (None, _) => String::new(),
+        };
+        info!(
+            searched,
+            hashrate = average,
+            peak,
+            matches,
+            partial,
+            luck,
+            joules,
+            finished = true,
+            "searched {searched} {seed_desc} seeds in {elapsed:.1}s ({average:.0} seeds/s\
+             {recent}{}); matches {matches}{work}; partial {partial}{}{power}",
+            device_rates(searched, cpu_threads, &gpus, elapsed),
+            gpu_false_positives(&state, &gpus),
+        );
+        if cpu_threads > 1 {
+            let rates: Vec<_> = threads[..cpu_threads as usize]
+                .iter()
+                .map(|(searched, elapsed)| *searched as f64 / elapsed.as_secs_f64())
+                .collect();
+            info!("{}", thread_rates(&rates));
+        }
+        if args.profile && cpu_threads > 0 {
+            let cpu = &threads[..cpu_threads as usize];
+            let busy = cpu.iter().map(|(_, elapsed)| elapsed.as_secs_f64()).sum();
+            info!(
+                "{}",
+                profile_line(&state, busy, cpu.iter().map(|(searched, _)| searched).sum())
+            );
+        }
+        if args.bump_mode == BumpMode::Any {
+            print_bump_stats(&state, searched, bump_depth);
+        }
+    }
+    Ok(threads)
+}
@@ -0,0 +1,403 @@
+//! `--idl`: the seeds (and program) an Anchor program derives one of its accounts from,
+//! read from the `pda` the account has in the program's IDL, so that they needn't be
+//! laid out by hand with `--seeds`. The seeds that come from other accounts or from
+//! instruction arguments get their values from `--idl-seed`, bar the one that is ground.
+//! Both the IDLs Anchor 0.30 and later write and the older ones are read.
+
+use serde_json::Value;
+use solana_pubkey::Pubkey;
+use tracing::warn;
+
+use crate::{
+    args::{parse_pubkey, GrindArgs},
+    seeds::{parse_hex, SeedCharset, SeedComponent, SeedTemplate, SeedType},
+};
+
+/// A seed's type, as far as its bytes go
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Type {
+    Int {
+        bytes: usize,
+        signed: bool,
+    },
+    Bool,
+    String,
+    Pubkey,
+    /// `bytes`, or `[u8; N]`
+    Bytes(Option<usize>),
+}
+
+/// One of an account's seeds: a constant, or the value at a path into an instruction's
+/// accounts or arguments
+enum Seed {
+    Const(Vec<u8>),
+    Value { path: String, ty: Type },
+}
+
+impl Seed {
+    fn path(&self) -> Option<&str> {
+        match self {
+            Seed::Const(_) => None,
+            Seed::Value { path, .. } => Some(path),
+        }
+    }
+}
+
+/// Sets `args`' owner and seeds to the ones the `--idl` derives its `--account` from,
+/// and returns the name of the seed that is ground if it is a key (whose keypairs are
+/// then ground, as for a `--template`)
+pub(crate) fn apply(args: &mut GrindArgs) -> Result<Option<String>, String> {
+    let (Some(path), Some(account)) = (args.idl.clone(), args.account.clone()) else {
+        unreachable!("only called for an --idl and its --account");
+    };
+    let idl: Value = std::fs::read_to_string(&path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))
+        .and_then(|contents| {
+            serde_json::from_str(&contents)
+                .map_err(|e| format!("{} isn't an Anchor IDL: {e}", path.display()))
+        })?;
+    let types = idl["types"].as_array().map_or(&[][..], Vec::as_slice);
+
+    // The same account can be in many instructions, which should all derive it alike
+    let mut found = vec![];
+    for instruction in idl["instructions"].as_array().into_iter().flatten() {
+        find_pdas(&instruction["accounts"], &account, &mut |pda| {
+            found.push((instruction, pda))
+        });
+    }
+    let Some(&(instruction, pda)) = found.first() else {
+        return Err(format!(
+            "no instruction in {} has a PDA account named {account}",
+            path.display()
+        ));
+    };
+    if let Some((other, _)) = found.iter().find(|(_, other)| other != &pda) {
+        warn!(
+            "{} derives {account} from other seeds than {} does, using {}'s",
+            other["name"], instruction["name"], instruction["name"]
+        );
+    }
+
+    let seeds = pda["seeds"]
+        .as_array()
+        .ok_or_else(|| format!("{account}'s PDA has no seeds"))?
+        .iter()
+        .map(|seed| parse_seed(seed, instruction, types))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // A PDA of another program names it, and the IDL's own address is the default
+    let program = pda
+        .get("program")
+        .or_else(|| pda.get("programId"))
+        .map(|program| parse_seed(program, instruction, types))
+        .transpose()?;
+    let paths: Vec<_> = seeds
+        .iter()
+        .chain(&program)
+        .filter_map(Seed::path)
+        .collect();
+    for path in args
+        .idl_seed
+        .iter()
+        .map(|(path, _)| path)
+        .chain(&args.idl_grind)
+    {
+        if !paths.contains(&path.as_str()) {
+            return Err(format!("{account} has no seed {path}"));
+        }
+    }
+
+    // The one seed left without a value is ground, unless another is picked
+    let unset: Vec<_> = seeds
+        .iter()
+        .filter_map(|seed| match seed {
+            Seed::Value { path, .. } if !args.idl_seed.iter().any(|(given, _)| given == path) => {
+                Some(path.as_str())
+            }
+            _ => None,
+        })
+        .collect();
+    let ground = match (&args.idl_grind, unset.as_slice()) {
+        (Some(ground), _) => ground.clone(),
+        (None, [ground]) => ground.to_string(),
+        (None, []) => {
+            return Err(format!(
+                "--idl-seed gives every one of {account}'s seeds a value, pick one to grind \
+                 with --idl-grind"
+            ))
+        }
+        (None, unset) => {
+            return Err(format!(
+                "give all but one of {account}'s seeds {} a value with --idl-seed PATH=VALUE, \
+                 or pick the one to grind with --idl-grind",
+                unset.join(", ")
+            ))
+        }
+    };
+
+    let value = |path: &str| {
+        args.idl_seed
+            .iter()
+            .find(|(given, _)| given == path)
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| format!("give {path} a value with --idl-seed {path}=VALUE"))
+    };
+    let owner = match program {
+        Some(Seed::Const(bytes)) => Pubkey::try_from(bytes.as_slice())
+            .map_err(|_| format!("{account}'s program isn't a key"))?,
+        Some(Seed::Value { path, .. }) => {
+            parse_pubkey(&value(&path)?).map_err(|e| format!("--idl-seed {path}: {e}"))?
+        }
+        None if args.owner != Pubkey::default() => args.owner,
+        None => idl["address"]
+            .as_str()
+            .or(idl["metadata"]["address"].as_str())
+            .ok_or_else(|| format!("{} has no program address, give --owner", path.display()))
+            .and_then(parse_pubkey)?,
+    };
+
+    let mut components = vec![];
+    let mut ground_key = None;
+    for seed in seeds {
+        let component = match seed {
+            Seed::Const(bytes) => SeedComponent::Fixed(bytes),
+            Seed::Value { path, ty } if path == ground => match ty {
+                Type::Int { signed: true, .. } | Type::Int { bytes: 1, .. } | Type::Bool => {
+                    return Err(format!("{path} can't be ground, only u16 to u128 can"))
+                }
+                Type::Int { bytes, .. } => SeedComponent::Ground(
+                    [SeedType::U16, SeedType::U32, SeedType::U64, SeedType::U128]
+                        .into_iter()
+                        .find(|seed_type| seed_type.width() == bytes),
+                ),
+                // Strings have to be written in client code, so only take characters that
+                // can be
+                Type::String => {
+                    args.seed_charset.get_or_insert(SeedCharset::Alphanumeric);
+                    SeedComponent::Ground(None)
+                }
+                Type::Pubkey => {
+                    ground_key = Some(path);
+                    SeedComponent::Ground(None)
+                }
+                Type::Bytes(Some(len)) if len > 32 => {
+                    return Err(format!("{path} is {len} bytes, more than a seed can be"))
+                }
+                Type::Bytes(Some(len)) if args.seed_len.is_some_and(|n| n as usize != len) => {
+                    return Err(format!("{path} is {len} bytes, not --seed-len"))
+                }
+                Type::Bytes(Some(len)) => {
+                    args.seed_len = Some(len as u8);
+                    SeedComponent::Ground(None)
+                }
+                Type::Bytes(None) if args.seed_len.is_none() => {
+                    return Err(format!("{path} can be any length, give it a --seed-len"))
+                }
+                Type::Bytes(None) => SeedComponent::Ground(None),
+            },
+            Seed::Value { path, ty } => {
+                let bytes =
+                    encode(ty, &value(&path)?).map_err(|e| format!("--idl-seed {path}: {e}"))?;
+                match ty {
+                    Type::Pubkey => {
+                        SeedComponent::Pubkey(Pubkey::try_from(bytes.as_slice()).unwrap())
+                    }
+                    _ => SeedComponent::Fixed(bytes),
+                }
+            }
+        };
+        components.push(component);
+    }
+    if !components
+        .iter()
+        .any(|component| matches!(component, SeedComponent::Ground(_)))
+    {
+        return Err(format!(
+            "{ground} isn't one of {account}'s seeds, it can't be ground"
+        ));
+    }
+    if components.len() > 15 {
+        return Err(format!(
+            "{account} has {} seeds, more than fit next to the bump",
+            components.len()
+        ));
+    }
+    args.owner = owner;
+    args.seeds = Some(SeedTemplate(components));
+    Ok(ground_key)
+}
+
+/// Calls `found` with the `pda` of each account in `accounts` (and in the groups of
+/// accounts among them) named `name`, whether in snake case or not
+fn find_pdas<'a>(accounts: &'a Value, name: &str, found: &mut impl FnMut(&'a Value)) {
+    let normalize = |name: &str| name.replace('_', "").to_lowercase();
+    for account in accounts.as_array().into_iter().flatten() {
+        match account.get("pda") {
+            Some(pda) if account["name"].as_str().map(normalize) == Some(normalize(name)) => {
+                found(pda)
+            }
+            _ => find_pdas(&account["accounts"], name, found),
+        }
+    }
+}
+
+/// A seed of a `pda`, with its type from the seed itself in older IDLs, and otherwise
+/// from the instruction's arguments or the account's type
+fn parse_seed(seed: &Value, instruction: &Value, types: &[Value]) -> Result<Seed, String> {
+    let kind = seed["kind"].as_str().unwrap_or_default();
+    let ty = match seed.get("type") {
+        Some(ty) => Some(parse_type(ty).ok_or_else(|| format!("unsupported seed type {ty}"))?),
+        None => None,
+    };
+    if kind == "const" {
+        let value = &seed["value"];
+        return match (ty, value) {
+            (None, Value::Array(_)) | (Some(Type::Bytes(_)), Value::Array(_)) => value
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                .collect::<Option<Vec<_>>>()
+                .map(Seed::Const)
+                .ok_or_else(|| format!("invalid constant seed {value}")),
+            (Some(ty), Value::String(text)) => encode(ty, text).map(Seed::Const),
+            (Some(ty), value) => encode(ty, &value.to_string()).map(Seed::Const),
+            (None, value) => Err(format!("invalid constant seed {value}")),
+        };
+    }
+    let path = seed["path"]
+        .as_str()
+        .ok_or_else(|| format!("seed {seed} has no path"))?
+        .to_string();
+    let ty = match (ty, kind) {
+        (Some(ty), _) => ty,
+        (None, "arg") => {
+            let (arg, rest) = split_path(&path);
+            instruction["args"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|field| field["name"] == arg)
+                .and_then(|field| field_type(&field["type"], &rest, types))
+                .ok_or_else(|| format!("can't tell the type of argument {path}"))?
+        }
+        // The account's own key, or a field of its data
+        (None, "account") => match split_path(&path) {
+            (_, rest) if rest.is_empty() => Type::Pubkey,
+            (_, rest) => seed["account"]
+                .as_str()
+                .and_then(|account| {
+                    field_type(&serde_json::json!({ "defined": account }), &rest, types)
+                })
+                .ok_or_else(|| format!("can't tell the type of {path}"))?,
+        },
+        (None, kind) => return Err(format!("unsupported seed kind {kind:?}")),
+    };
+    Ok(Seed::Value { path, ty })
+}
+
+/// `path`'s first name, and the names after it
+fn split_path(path: &str) -> (&str, Vec<&str>) {
+    let mut names = path.split('.');
+    (names.next().unwrap_or_default(), names.collect())
+}
+
+/// The type of the field `path` leads to in a value of type `ty`
+fn field_type(ty: &Value, path: &[&str], types: &[Value]) -> Option<Type> {
+    let Some((field, rest)) = path.split_first() else {
+        return parse_type(ty);
+    };
+    // `{"defined": "Name"}` in older IDLs, `{"defined": {"name": "Name"}}` in newer ones
+    let defined = ty["defined"].as_str().or(ty["defined"]["name"].as_str())?;
+    let definition = types
+        .iter()
+        .find(|definition| definition["name"] == defined)?;
+    let field = definition["type"]["fields"]
+        .as_array()?
+        .iter()
+        .find(|f| f["name"] == *field)?;
+    field_type(&field["type"], rest, types)
+}
+
+fn parse_type(ty: &Value) -> Option<Type> {
+    let int = |bytes, signed| Some(Type::Int { bytes, signed });
+    match ty {
+        Value::String(name) => match name.as_str() {
+            "u8" => int(1, false),
+            "i8" => int(1, true),
+            "u16" => int(2, false),
+            "i16" => int(2, true),
+            "u32" => int(4, false),
+            "i32" => int(4, true),
+            "u64" => int(8, false),
+            "i64" => int(8, true),
+            "u128" => int(16, false),
+            "i128" => int(16, true),
+            "bool" => Some(Type::Bool),
+            "string" => Some(Type::String),
+            "pubkey" | "publicKey" => Some(Type::Pubkey),
+            "bytes" => Some(Type::Bytes(None)),
+            _ => None,
+        },
+        Value::Object(_) => match ty["array"].as_array()?.as_slice() {
+            [element, len] if element == "u8" => Some(Type::Bytes(Some(len.as_u64()? as usize))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The bytes a seed of type `ty` is written as (integers little-endian, as Anchor's
+/// `to_le_bytes()` seeds are), from `value` as given on the command line
+fn encode(ty: Type, value: &str) -> Result<Vec<u8>, String> {
+    let bytes = match ty {
+        Type::Int { bytes, signed } => {
+            let le_bytes = match signed {
+                true => value
+                    .parse::<i128>()
+                    .ok()
+                    .filter(|n| bytes == 16 || matches!(n >> (8 * bytes - 1), 0 | -1))
+                    .map(i128::to_le_bytes),
+                false => value
+                    .parse::<u128>()
+                    .ok()
+                    .filter(|n| bytes == 16 || n >> (8 * bytes) == 0)
+                    .map(u128::to_le_bytes),
+            };
+            le_bytes
+                .map(|le_bytes| le_bytes[..bytes].to_vec())
+                .ok_or_else(|| {
+                    let sign = if signed { 'i' } else { 'u' };
+                    format!("{value:?} isn't a valid {sign}{}", 8 * bytes)
+                })?
+        }
+        Type::Bool => match value {
+            "true" => vec![1],
+            "false" => vec![0],
+            _ => return Err(format!("{value:?} isn't true or false")),
+        },
+        Type::String => value.as_bytes().to_vec(),
+        Type::Pubkey => parse_pubkey(value)?.to_bytes().to_vec(),
+        Type::Bytes(len) => {
+            let bytes = parse_hex(value)?.0;
+            if let Some(len) = len.filter(|len| *len != bytes.len()) {
+                return Err(format!("expected {len} bytes of hex, got {}", bytes.len()));
+            }
+            bytes
+        }
+    };
+    if bytes.len() > 32 {
+        return Err(format!(
+            "seeds can be at most 32 bytes, got {}",
+            bytes.len()
+        ));
+    }
+    Ok(bytes)
+}
+
+/// `PATH=VALUE`, as `--idl-seed` takes it
+pub(crate) fn parse_idl_seed(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(path, value)| (path.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected PATH=VALUE, got {s:?}"))
+}
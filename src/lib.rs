@@ -0,0 +1,51 @@
+pub mod args;
+mod base58;
+mod bench;
+pub mod bucket;
+pub mod bump;
+mod candidates;
+mod checkpoint;
+mod config;
+pub mod curve;
+mod db;
+mod devices;
+mod energy;
+#[cfg(feature = "cdylib")]
+pub mod ffi;
+pub mod gpu;
+mod grind;
+mod idl;
+mod logging;
+pub mod matcher;
+pub mod matchers;
+#[cfg(feature = "napi")]
+pub mod node;
+#[cfg(feature = "bench")]
+pub mod pipeline;
+#[cfg(feature = "pyo3")]
+mod python;
+mod queue;
+pub mod reference;
+mod results;
+mod rpc;
+pub mod seeds;
+mod sha256;
+pub mod sinks;
+pub mod snippet;
+#[cfg(feature = "tokio")]
+mod stream;
+mod template;
+mod tui;
+mod tune;
+mod webhook;
+
+pub use bench::bench;
+pub use candidates::SeedCandidates;
+pub use config::load_config;
+pub use db::list;
+pub use devices::devices;
+pub use grind::{check, grind, CancellationToken, Grinder, Progress};
+pub use results::Found;
+#[cfg(feature = "tokio")]
+pub use stream::grind_stream;
+pub use tune::tune;
@@ -0,0 +1,184 @@
+//! Where the `grind` command's log goes. Its banners, stats, matches and warnings are
+//! `tracing` events, from spans naming the CPU thread or GPU they came from, which
+//! `--log-format` writes as plain lines (as they always were), JSON or pretty-printed,
+//! to stdout or to a `--log-file`. A program running a `Grinder` gets the same events,
+//! for whatever subscriber it installs, and none without one.
+//!
+//! Plain lines going to a terminal can also have a progress bar under them, for a grind
+//! of a seed range, and `--stats-json` can write the stats as JSON alongside.
+
+use std::{
+    fmt,
+    fs::OpenOptions,
+    io::{self, IsTerminal, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressState, ProgressStyle};
+
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{
+    fmt::{
+        format::Writer, writer::MakeWriterExt, FmtContext, FormatEvent, FormatFields, MakeWriter,
+    },
+    registry::LookupSpan,
+};
+
+use crate::args::LogFormat;
+
+/// Whether `init` installed the plain subscriber with a terminal to print to, which is
+/// all a progress bar can be drawn under
+static TERMINAL: AtomicBool = AtomicBool::new(false);
+
+/// The progress bar, while there is one
+static PROGRESS_BAR: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+/// Installs the subscriber `format` and `file` ask for, unless one already is (by an
+/// earlier `grind` in the same program)
+pub(crate) fn init(format: LogFormat, file: Option<&Path>) -> Result<(), String> {
+    let file = file
+        .map(|path| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("failed to open {}: {e}", path.display()))
+        })
+        .transpose()?;
+    match file {
+        Some(file) => install(format, false, Mutex::new(file)),
+        None if format == LogFormat::Text => {
+            TERMINAL.store(io::stderr().is_terminal(), Ordering::Relaxed);
+            install(
+                format,
+                false,
+                (|| AboveBar(io::stderr()))
+                    .with_max_level(Level::WARN)
+                    .or_else(|| AboveBar(io::stdout())),
+            )
+        }
+        None => install(format, std::io::stdout().is_terminal(), std::io::stdout),
+    }
+    Ok(())
+}
+
+fn install<W>(format: LogFormat, ansi: bool, writer: W)
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(Level::INFO)
+        .with_ansi(ansi)
+        .with_writer(writer);
+    // Only fails if a subscriber is already installed, which is then left to it
+    let _ = match format {
+        LogFormat::Text => builder.event_format(Plain).try_init(),
+        LogFormat::Json => builder
+            .json()
+            .flatten_event(true)
+            .with_span_list(false)
+            .try_init(),
+        LogFormat::Pretty => builder.pretty().try_init(),
+    };
+}
+
+/// Writes an event's message alone, after `warning: ` if it is a warning
+struct Plain;
+
+impl<S, N> FormatEvent<S, N> for Plain
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        match *event.metadata().level() {
+            Level::WARN => writeln!(writer, "warning: {}", message.0),
+            _ => writeln!(writer, "{}", message.0),
+        }
+    }
+}
+
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{value:?}");
+        }
+    }
+}
+
+/// Starts a progress bar through `len` seeds, `searched` of them already, on stderr,
+/// unless the log isn't going to a terminal as plain lines
+pub(crate) fn progress_bar(len: u64, searched: u64) -> Option<ProgressBar> {
+    if !TERMINAL.load(Ordering::Relaxed) {
+        return None;
+    }
+    let bar = ProgressBar::with_draw_target(Some(len), ProgressDrawTarget::stderr())
+        .with_style(
+            ProgressStyle::with_template(
+                "{wide_bar} {percent:>3}% of {human_len} seeds, {rate}, ETA {eta} {msg}",
+            )
+            .unwrap()
+            .with_key("rate", |state: &ProgressState, w: &mut dyn fmt::Write| {
+                let _ = write!(w, "{:.0}/s", state.per_sec());
+            }),
+        )
+        .with_position(searched);
+    bar.enable_steady_tick(Duration::from_millis(200));
+    *PROGRESS_BAR.lock().unwrap() = Some(bar.clone());
+    Some(bar)
+}
+
+/// Leaves the progress bar where it got to, for the lines after it to go under
+pub(crate) fn finish_progress_bar() {
+    if let Some(bar) = PROGRESS_BAR.lock().unwrap().take() {
+        bar.abandon();
+    }
+}
+
+/// Where `--stats-json` writes to: stderr for `-`, above the progress bar if there is one,
+/// and otherwise the end of the file or named pipe at `path`
+pub(crate) fn stats_json_writer(path: &Path) -> Result<Box<dyn Write + Send>, String> {
+    if path == Path::new("-") {
+        return Ok(Box::new(AboveBar(io::stderr())));
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map(|file| Box::new(file) as Box<dyn Write + Send>)
+        .map_err(|e| format!("failed to open {}: {e}", path.display()))
+}
+
+/// Writes log lines above the progress bar, if there is one, rather than over it
+struct AboveBar<W>(W);
+
+impl<W: Write> Write for AboveBar<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let bar = PROGRESS_BAR.lock().unwrap().clone();
+        match bar {
+            Some(bar) => bar.suspend(|| self.0.write(buf)),
+            None => self.0.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
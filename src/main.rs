@@ -0,0 +1,18 @@
+use clap::Parser;
+use pda_grinder::args::Command;
+
+fn main() {
+    if let Err(e) = pda_grinder::load_config() {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+
+    match Command::parse() {
+        Command::Grind(args) => pda_grinder::grind(args),
+        Command::Check(args) => pda_grinder::check(args),
+        Command::List(args) => pda_grinder::list(args),
+        Command::Bench(args) => pda_grinder::bench(args),
+        Command::Tune(args) => pda_grinder::tune(args),
+        Command::Devices => pda_grinder::devices(),
+    }
+}
@@ -0,0 +1,576 @@
+use std::sync::Arc;
+
+use memchr::memmem::Finder;
+use regex::bytes::Regex;
+
+use crate::{args::GrindArgs, matchers::Matcher};
+
+const BS58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Position of each byte in `BS58_ALPHABET`, or `u8::MAX` if it isn't in there
+const BS58_INDEX: [u8; 256] = {
+    let mut index = [u8::MAX; 256];
+    let mut i = 0;
+    while i < BS58_ALPHABET.len() {
+        index[BS58_ALPHABET[i] as usize] = i as u8;
+        i += 1;
+    }
+    index
+};
+
+/// Characters that `--fuzzy` considers equivalent. Anything outside the base58
+/// alphabet (0, O, I, l) is dropped when the classes are expanded.
+const FUZZY_CLASSES: &[&[u8]] = &[
+    b"aA4", b"bB8", b"eE3", b"gG9", b"iI1", b"oO0", b"sS5", b"tT7", b"zZ2",
+];
+
+/// Expands a target into one bitmask of acceptable alphabet indices per position
+fn fuzzy_masks(target: &str) -> Vec<u64> {
+    target
+        .bytes()
+        .map(|c| {
+            let class = FUZZY_CLASSES
+                .iter()
+                .find(|class| class.contains(&c))
+                .map(|class| class.to_vec())
+                .unwrap_or_else(|| vec![c, c.to_ascii_lowercase(), c.to_ascii_uppercase()]);
+            class
+                .into_iter()
+                .filter(|c| BS58_INDEX[*c as usize] != u8::MAX)
+                .fold(0, |mask, c| mask | 1 << BS58_INDEX[c as usize])
+        })
+        .collect()
+}
+
+#[inline(always)]
+fn fuzzy_matches(masks: &[u64], candidate_bs58: &[u8]) -> bool {
+    // `five8` only ever emits alphabet characters, so the index lookup is always in range
+    candidate_bs58.len() >= masks.len()
+        && masks
+            .iter()
+            .zip(candidate_bs58)
+            .all(|(mask, c)| mask >> BS58_INDEX[*c as usize] & 1 == 1)
+}
+
+/// Just enough of a 256-bit unsigned integer to reason about which numeric ranges a
+/// base58 prefix covers. Limbs are most significant first so the derived `Ord` works.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct U256([u64; 4]);
+
+impl U256 {
+    const ZERO: U256 = U256([0; 4]);
+    const MAX: U256 = U256([u64::MAX; 4]);
+
+    pub(crate) fn to_f64(self) -> f64 {
+        self.0
+            .iter()
+            .fold(0.0, |acc, limb| acc * 2_f64.powi(64) + *limb as f64)
+    }
+
+    fn to_be_bytes(self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).zip(self.0) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// `self * mul + add`, or `None` if that doesn't fit in 256 bits
+    fn checked_mul_add(self, mul: u64, add: u64) -> Option<U256> {
+        let mut limbs = [0; 4];
+        let mut carry = add as u128;
+        for i in (0..4).rev() {
+            let wide = self.0[i] as u128 * mul as u128 + carry;
+            limbs[i] = wide as u64;
+            carry = wide >> 64;
+        }
+        (carry == 0).then_some(U256(limbs))
+    }
+
+    fn checked_sub_one(self) -> Option<U256> {
+        let mut limbs = self.0;
+        for limb in limbs.iter_mut().rev() {
+            let (value, borrow) = limb.overflowing_sub(1);
+            *limb = value;
+            if !borrow {
+                return Some(U256(limbs));
+            }
+        }
+        None
+    }
+
+    /// `256^n`, or `None` for `n >= 32`
+    fn pow256(n: usize) -> Option<U256> {
+        (n < 32).then(|| {
+            let mut limbs = [0; 4];
+            limbs[3 - n / 8] = 1 << (8 * (n % 8));
+            U256(limbs)
+        })
+    }
+}
+
+/// Inclusive ranges of big-endian 32-byte values whose base58 encoding starts with
+/// `prefix` (assumed to only contain alphabet characters). Empty if none exist.
+pub(crate) fn prefix_ranges(prefix: &str) -> Vec<(U256, U256)> {
+    // Leading '1's are leading zero bytes, the rest is the usual base conversion
+    let zero_bytes = prefix.bytes().take_while(|c| *c == b'1').count();
+    let rest = &prefix.as_bytes()[zero_bytes..];
+    if zero_bytes > 32 || (zero_bytes == 32 && !rest.is_empty()) {
+        return vec![];
+    }
+
+    // At least `zero_bytes` leading zero bytes, and exactly that many if more digits follow
+    let upper = U256::pow256(32 - zero_bytes)
+        .and_then(U256::checked_sub_one)
+        .unwrap_or(U256::MAX);
+    if rest.is_empty() {
+        return vec![(U256::ZERO, upper)];
+    }
+    let lower = U256::pow256(31 - zero_bytes).unwrap();
+
+    let Some(rest_value) = rest.iter().try_fold(U256::ZERO, |value, c| {
+        value.checked_mul_add(58, BS58_INDEX[*c as usize] as u64)
+    }) else {
+        return vec![];
+    };
+
+    // Try every possible number of trailing digits until the range leaves 256 bits
+    let mut ranges = vec![];
+    let (mut lo, mut hi) = (rest_value, rest_value);
+    loop {
+        let (clamped_lo, clamped_hi) = (lo.max(lower), hi.min(upper));
+        if clamped_lo <= clamped_hi {
+            ranges.push((clamped_lo, clamped_hi));
+        }
+        match (lo.checked_mul_add(58, 0), hi.checked_mul_add(58, 57)) {
+            (Some(next_lo), Some(next_hi)) => (lo, hi) = (next_lo, next_hi),
+            (Some(next_lo), None) => (lo, hi) = (next_lo, U256::MAX),
+            (None, _) => break,
+        }
+    }
+    ranges
+}
+
+/// Whether `hash` lies in the raw ranges a grind for addresses starting with `prefix`
+/// keeps candidates in, which hold exactly the hashes whose base58 encoding does
+pub fn raw_prefix_matches(prefix: &str, hash: &[u8; 32]) -> bool {
+    prefix_ranges(prefix)
+        .iter()
+        .any(|(lo, hi)| (lo.to_be_bytes()..=hi.to_be_bytes()).contains(hash))
+}
+
+/// Whether `hash` passes the raw check a grind for addresses ending with `suffix` rules
+/// candidates out by, which every hash whose base58 encoding ends with it does. It only
+/// looks at the last `MAX_RAW_SUFFIX_LEN` characters, and for suffixes no longer than
+/// that, only those hashes pass.
+pub fn raw_suffix_may_match(suffix: &str, hash: &[u8; 32]) -> bool {
+    RawSuffix::new(suffix, false).is_none_or(|raw_suffix| raw_suffix.may_match(hash))
+}
+
+/// Rejects targets that can never match, explaining why instead of grinding forever
+pub(crate) fn validate_target(args: &GrindArgs) -> Result<(), String> {
+    for (flag, value) in [("target", &args.target), ("suffix", &args.suffix)] {
+        let Some(value) = value else { continue };
+        if value.is_empty() {
+            return Err(format!("--{flag} can't be empty"));
+        }
+        // The masks go by byte, and the first byte to fail starts a character since
+        // every one outside ASCII fails
+        let char_at = |i: usize| value[i..].chars().next().unwrap();
+        if args.fuzzy {
+            if let Some(i) = fuzzy_masks(value).iter().position(|mask| *mask == 0) {
+                return Err(format!(
+                    "--{flag} {value:?}: '{}' has no look-alike in the base58 alphabet",
+                    char_at(i)
+                ));
+            }
+            continue;
+        }
+        if let Some(i) = value
+            .bytes()
+            .position(|c| BS58_INDEX[c as usize] == u8::MAX)
+        {
+            return Err(format!(
+                "--{flag} {value:?}: '{}' is not in the base58 alphabet, which leaves out \
+                 0, O, I and l to avoid look-alikes",
+                char_at(i)
+            ));
+        }
+    }
+
+    if let Some(target) = args
+        .target
+        .as_ref()
+        .filter(|_| !args.anywhere && !args.fuzzy)
+    {
+        if prefix_ranges(target).is_empty() {
+            return Err(format!(
+                "no 32-byte address starts with {target:?}: base58 encodings of 32-byte values \
+                 are at most 44 characters and never exceed \
+                 JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG (each leading '1' also uses up \
+                 a whole zero byte)"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Most look-alike spellings of a `--fuzzy` target's or suffix's characters to check on
+/// the raw hash. Each character multiplies them by up to three, and a couple of characters
+/// already rule out nearly every candidate.
+const MAX_FUZZY_SPELLINGS: usize = 64;
+
+/// One bitmask of acceptable alphabet indices per character of `value`, as `fuzzy_masks`
+/// but with only the character itself unless `--fuzzy`
+fn char_masks(value: &str, fuzzy: bool) -> Vec<u64> {
+    if fuzzy {
+        return fuzzy_masks(value);
+    }
+    value.bytes().map(|c| 1 << BS58_INDEX[c as usize]).collect()
+}
+
+/// Every string the leading `masks` accept, for as many of them as `MAX_FUZZY_SPELLINGS`
+/// allows
+fn spellings(masks: impl IntoIterator<Item = u64>) -> Vec<String> {
+    let mut spellings = vec![String::new()];
+    for mask in masks {
+        let chars: Vec<char> = BS58_ALPHABET
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| mask >> i & 1 == 1)
+            .map(|(_, c)| *c as char)
+            .collect();
+        if spellings.len() * chars.len() > MAX_FUZZY_SPELLINGS {
+            break;
+        }
+        spellings = spellings
+            .iter()
+            .flat_map(|spelling| chars.iter().map(move |c| format!("{spelling}{c}")))
+            .collect();
+    }
+    spellings
+}
+
+/// The chance that a random address matches all of the `--target` and `--suffix`. A
+/// prefix's chance is worked out exactly, as far as its `--fuzzy` spellings go, and the
+/// rest's taking each character of an address to be equally likely. `None` for a
+/// `--regex`, whose chance can't be told.
+pub(crate) fn target_probability(args: &GrindArgs) -> Option<f64> {
+    if args.regex.is_some() {
+        return None;
+    }
+    let uniform = |masks: &[u64]| {
+        masks
+            .iter()
+            .map(|mask| mask.count_ones() as f64 / 58.0)
+            .product::<f64>()
+    };
+    let mut probability = 1.0;
+    if let Some(target) = &args.target {
+        let masks = char_masks(target, args.fuzzy);
+        probability = if args.anywhere {
+            // At any of the places it fits in a (nearly always) 44 character address
+            (uniform(&masks) * (45 - masks.len().min(44)) as f64).min(1.0)
+        } else {
+            let spellings = spellings(masks.iter().copied());
+            let hits: f64 = spellings
+                .iter()
+                .flat_map(|spelling| prefix_ranges(spelling))
+                .map(|(lo, hi)| hi.to_f64() - lo.to_f64() + 1.0)
+                .sum();
+            hits / 2_f64.powi(256) * uniform(&masks[spellings[0].len()..])
+        };
+    }
+    if let Some(suffix) = &args.suffix {
+        probability *= uniform(&char_masks(suffix, args.fuzzy));
+    }
+    Some(probability)
+}
+
+/// Leading characters that every candidate able to match starts with (one of), if the
+/// target pins any down: the target itself, or only its first `--min-len` characters,
+/// and under `--fuzzy` the spellings of as many of them as `MAX_FUZZY_SPELLINGS` allows.
+fn required_prefixes(args: &GrindArgs) -> Option<Vec<String>> {
+    let target = args.target.as_deref().filter(|_| !args.anywhere)?;
+    // Targets are all alphabet characters (or look-alikes of them) by now, so ASCII
+    let len = args
+        .min_len
+        .map_or(target.len(), |min_len| min_len.min(target.len()));
+    let prefixes = spellings(char_masks(&target[..len], args.fuzzy));
+    (!prefixes[0].is_empty()).then_some(prefixes)
+}
+
+/// Most trailing characters of a suffix checked on the raw hash, the most whose `58^len`
+/// fits in a `u64`
+const MAX_RAW_SUFFIX_LEN: usize = 10;
+
+/// A suffix checked on the raw hash, without encoding it. The last `len` base58 digits
+/// of a number are its value mod `58^len`, so a candidate can only end with the suffix
+/// when its hash mod `58^len` is the value of the suffix's last `len` characters (or,
+/// under `--fuzzy`, of one of their spellings).
+#[derive(Clone)]
+struct RawSuffix {
+    /// `58^len`
+    modulus: u64,
+    /// `2^64`, `2^128` and `2^192` mod `modulus`
+    weights: [u64; 3],
+    /// Sorted values of the spellings
+    residues: Vec<u64>,
+    /// `2^len - 1`. `2^len` divides `58^len`, so the low `len` bits of a hash already
+    /// give its residue mod `2^len`, which rules most of them out before any division.
+    low_mask: u64,
+    /// Which residues mod `2^len` the spellings have
+    low_residues: [u64; 1 << MAX_RAW_SUFFIX_LEN >> 6],
+}
+
+impl RawSuffix {
+    fn new(suffix: &str, fuzzy: bool) -> Option<RawSuffix> {
+        let tail = &suffix[suffix.len().saturating_sub(MAX_RAW_SUFFIX_LEN)..];
+        // Spelled out from the last character back
+        let masks = char_masks(tail, fuzzy);
+        let spellings = spellings(masks.into_iter().rev());
+        let len = spellings[0].len();
+        if len == 0 {
+            return None;
+        }
+
+        let modulus = 58_u64.pow(len as u32);
+        let mut residues: Vec<u64> = spellings
+            .iter()
+            .map(|spelling| {
+                spelling
+                    .bytes()
+                    .rev()
+                    .fold(0, |value, c| value * 58 + BS58_INDEX[c as usize] as u64)
+            })
+            .collect();
+        residues.sort_unstable();
+        let low_mask = (1 << len) - 1;
+        let mut low_residues = [0; 1 << MAX_RAW_SUFFIX_LEN >> 6];
+        for residue in &residues {
+            let low = residue & low_mask;
+            low_residues[low as usize / 64] |= 1 << (low % 64);
+        }
+        let w64 = (u64::MAX % modulus + 1) % modulus;
+        let times_w64 = |x: u64| (x as u128 * w64 as u128 % modulus as u128) as u64;
+        let w128 = times_w64(w64);
+        Some(RawSuffix {
+            modulus,
+            weights: [w64, w128, times_w64(w128)],
+            residues,
+            low_mask,
+            low_residues,
+        })
+    }
+
+    #[inline(always)]
+    fn may_match(&self, hash: &[u8; 32]) -> bool {
+        let limb = |i: usize| u64::from_be_bytes(hash[8 * i..8 * i + 8].try_into().unwrap());
+        let low = limb(3) & self.low_mask;
+        if self.low_residues[low as usize / 64] >> (low % 64) & 1 == 0 {
+            return false;
+        }
+        // Each product is below 2^123, so the sum can't overflow
+        let [w64, w128, w192] = self.weights.map(|weight| weight as u128);
+        let wide = limb(0) as u128 * w192
+            + limb(1) as u128 * w128
+            + limb(2) as u128 * w64
+            + limb(3) as u128;
+        let residue = (wide % self.modulus as u128) as u64;
+        self.residues.binary_search(&residue).is_ok()
+    }
+}
+
+/// How well a candidate did against the `TargetMatcher`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MatchKind {
+    Miss,
+    /// Only this many leading characters of the target matched (see `--min-len`)
+    Partial(usize),
+    Full,
+}
+
+/// Decides whether a base58 encoded candidate is what we are grinding for
+#[derive(Clone)]
+pub(crate) struct TargetMatcher {
+    target: Option<String>,
+    suffix: Option<String>,
+    target_masks: Option<Vec<u64>>,
+    suffix_masks: Option<Vec<u64>>,
+    anywhere: Option<Finder<'static>>,
+    regex: Option<Regex>,
+    min_len: Option<usize>,
+    /// Raw hash ranges (inclusive, big-endian, sorted) that encode to something starting
+    /// with one of the `required_prefixes`, so that candidates outside of them never need
+    /// to be base58 encoded to be ruled out
+    raw_ranges: Option<Vec<([u8; 32], [u8; 32])>>,
+    raw_suffix: Option<RawSuffix>,
+    /// A `Grinder`'s own matcher, asked about candidates that pass everything else
+    custom: Option<Arc<dyn Matcher>>,
+}
+
+impl TargetMatcher {
+    pub(crate) fn new(args: &GrindArgs, custom: Option<Arc<dyn Matcher>>) -> TargetMatcher {
+        TargetMatcher {
+            target: args.target.clone().filter(|_| !args.fuzzy),
+            suffix: args.suffix.clone().filter(|_| !args.fuzzy),
+            target_masks: args
+                .target
+                .as_deref()
+                .filter(|_| args.fuzzy)
+                .map(fuzzy_masks),
+            suffix_masks: args
+                .suffix
+                .as_deref()
+                .filter(|_| args.fuzzy)
+                .map(fuzzy_masks),
+            anywhere: args
+                .target
+                .as_ref()
+                .filter(|_| args.anywhere)
+                .map(|target| Finder::new(target.as_bytes()).into_owned()),
+            // Compiled separately by every thread that builds a matcher. The pattern
+            // was already validated in main, so this can't fail.
+            regex: args
+                .regex
+                .as_ref()
+                .map(|pattern| Regex::new(pattern).unwrap()),
+            min_len: args.min_len,
+            raw_ranges: required_prefixes(args).map(|prefixes| {
+                // Ranges of different prefixes of the same length never overlap
+                let mut ranges: Vec<_> = prefixes
+                    .iter()
+                    .flat_map(|prefix| prefix_ranges(prefix))
+                    .map(|(lo, hi)| (lo.to_be_bytes(), hi.to_be_bytes()))
+                    .collect();
+                ranges.sort_unstable();
+                ranges
+            }),
+            raw_suffix: args
+                .suffix
+                .as_deref()
+                .and_then(|suffix| RawSuffix::new(suffix, args.fuzzy)),
+            custom,
+        }
+    }
+
+    /// Cheap check on the raw hash. `false` means the candidate can't match, so there
+    /// is no point base58 encoding it.
+    #[inline(always)]
+    pub(crate) fn may_match(&self, hash: &[u8; 32]) -> bool {
+        self.raw_ranges.as_ref().is_none_or(|ranges| {
+            // Only the first range that doesn't end below the hash can hold it
+            let i = ranges.partition_point(|(_, hi)| hi < hash);
+            ranges.get(i).is_some_and(|(lo, _)| lo <= hash)
+        }) && self
+            .raw_suffix
+            .as_ref()
+            .is_none_or(|suffix| suffix.may_match(hash))
+    }
+
+    /// Raw hash ranges (inclusive, big-endian, sorted) that every match falls in, if the
+    /// target rules candidates out by them
+    pub(crate) fn raw_ranges(&self) -> Option<&[([u8; 32], [u8; 32])]> {
+        self.raw_ranges.as_deref()
+    }
+
+    /// Whether every candidate has to be encoded to be checked, with nothing for
+    /// `may_match` to rule any of them out by
+    pub(crate) fn encodes_everything(&self) -> bool {
+        self.raw_ranges.is_none() && self.raw_suffix.is_none()
+    }
+
+    /// How `candidate`, base58 encoded as `candidate_bs58`, does
+    #[inline(always)]
+    pub(crate) fn check(&self, candidate: &[u8; 32], candidate_bs58: &[u8]) -> MatchKind {
+        let kind = self.check_target(candidate_bs58);
+        match &self.custom {
+            Some(custom) if kind != MatchKind::Miss => {
+                // SAFETY: base58 is all ASCII
+                let bs58 = unsafe { std::str::from_utf8_unchecked(candidate_bs58) };
+                if custom.matches(bs58, candidate) {
+                    kind
+                } else {
+                    MatchKind::Miss
+                }
+            }
+            _ => kind,
+        }
+    }
+
+    #[inline(always)]
+    fn check_target(&self, candidate_bs58: &[u8]) -> MatchKind {
+        if self.matches(candidate_bs58) {
+            return MatchKind::Full;
+        }
+        match self.min_len {
+            Some(min_len) => {
+                let len = self.prefix_len(candidate_bs58);
+                if len >= min_len && self.suffix_matches(candidate_bs58) {
+                    MatchKind::Partial(len)
+                } else {
+                    MatchKind::Miss
+                }
+            }
+            None => MatchKind::Miss,
+        }
+    }
+
+    /// Number of leading characters of the candidate that agree with the target
+    #[inline(always)]
+    fn prefix_len(&self, candidate_bs58: &[u8]) -> usize {
+        if let Some(masks) = &self.target_masks {
+            return masks
+                .iter()
+                .zip(candidate_bs58)
+                .take_while(|(mask, c)| *mask >> BS58_INDEX[**c as usize] & 1 == 1)
+                .count();
+        }
+        self.target.as_ref().map_or(0, |target| {
+            target
+                .bytes()
+                .zip(candidate_bs58)
+                .take_while(|(t, c)| t == *c)
+                .count()
+        })
+    }
+
+    #[inline(always)]
+    fn suffix_matches(&self, candidate_bs58: &[u8]) -> bool {
+        if let Some(suffix) = &self.suffix {
+            return candidate_bs58.ends_with(suffix.as_bytes());
+        }
+        if let Some(masks) = &self.suffix_masks {
+            return candidate_bs58
+                .len()
+                .checked_sub(masks.len())
+                .is_some_and(|tail| fuzzy_matches(masks, &candidate_bs58[tail..]));
+        }
+        true
+    }
+
+    #[inline(always)]
+    fn matches(&self, candidate_bs58: &[u8]) -> bool {
+        if let Some(regex) = &self.regex {
+            return regex.is_match(candidate_bs58);
+        }
+        // Cheapest checks first so that most candidates bail out after a byte or two
+        if let Some(target) = self.target.as_ref().filter(|_| self.anywhere.is_none()) {
+            if !candidate_bs58.starts_with(target.as_bytes()) {
+                return false;
+            }
+        }
+        if let Some(masks) = &self.target_masks {
+            if !fuzzy_matches(masks, candidate_bs58) {
+                return false;
+            }
+        }
+        if !self.suffix_matches(candidate_bs58) {
+            return false;
+        }
+        if let Some(finder) = &self.anywhere {
+            return finder.find(candidate_bs58).is_some();
+        }
+        true
+    }
+}
@@ -0,0 +1,49 @@
+//! Checks a `Grinder` can grind for beyond what `--target`, `--suffix` and `--regex` can
+//! say. A matcher is only asked about candidates that already pass those (about every
+//! canonical address when there are none), so it can be as slow as it needs to be
+//! without holding up grinds that rule most candidates out on their own.
+
+/// Decides whether an address is one to keep, given it both base58 encoded and as the raw
+/// 32 bytes. Every grinding thread shares it.
+pub trait Matcher: Send + Sync {
+    fn matches(&self, bs58: &str, raw: &[u8; 32]) -> bool;
+}
+
+/// Addresses starting with a string
+pub struct Prefix(pub String);
+
+impl Matcher for Prefix {
+    fn matches(&self, bs58: &str, _: &[u8; 32]) -> bool {
+        bs58.starts_with(&self.0)
+    }
+}
+
+/// Addresses ending with a string
+pub struct Suffix(pub String);
+
+impl Matcher for Suffix {
+    fn matches(&self, bs58: &str, _: &[u8; 32]) -> bool {
+        bs58.ends_with(&self.0)
+    }
+}
+
+/// Addresses a regex finds a match in
+pub struct Regex(pub regex::Regex);
+
+impl Matcher for Regex {
+    fn matches(&self, bs58: &str, _: &[u8; 32]) -> bool {
+        self.0.is_match(bs58)
+    }
+}
+
+/// Addresses whose bytes, as a big-endian number, are between two others (inclusive)
+pub struct NumericRange {
+    pub start: [u8; 32],
+    pub end: [u8; 32],
+}
+
+impl Matcher for NumericRange {
+    fn matches(&self, _: &str, raw: &[u8; 32]) -> bool {
+        (self.start..=self.end).contains(raw)
+    }
+}
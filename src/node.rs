@@ -0,0 +1,171 @@
+//! The native half of the Node module in `node/` (`--features napi`), built with
+//! `cargo rustc --release --lib --features napi --crate-type cdylib` and loaded by
+//! `node/index.js` once `target/release/libpda_grinder.so` is copied there as
+//! `pda_grinder.node`. `index.js` turns `start` into an async iterator.
+
+use std::{
+    str::FromStr,
+    sync::{
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+};
+
+use clap::Parser;
+use napi::{bindgen_prelude::*, Env, Task};
+use napi_derive::napi;
+use solana_pubkey::Pubkey;
+
+use crate::{
+    args::GrindArgs,
+    results::Found,
+    seeds::{parse_hex, HexBytes},
+    sinks::Channel,
+    CancellationToken, Grinder,
+};
+
+#[napi(object)]
+pub struct GrindOptions {
+    pub owner: String,
+    pub target: Option<String>,
+    pub threads: Option<u32>,
+    /// Any other `grind` command flags, e.g. `["--seed-end", "1000000"]`
+    pub flags: Option<Vec<String>>,
+}
+
+#[napi(object)]
+pub struct Match {
+    pub pubkey: String,
+    /// As printed, e.g. `1234` or `"vault"`
+    pub seed: String,
+    /// The ground seed's exact bytes
+    pub seed_bytes: Buffer,
+    pub bump: u8,
+    /// Seeds searched across all threads when this one was found
+    pub iterations: i64,
+    /// Unix seconds
+    pub found_at: i64,
+}
+
+impl From<Found> for Match {
+    fn from(found: Found) -> Match {
+        Match {
+            pubkey: found.key.to_string(),
+            seed: found.seed,
+            seed_bytes: parse_hex(&found.seed_hex)
+                .map(|HexBytes(bytes)| bytes)
+                .unwrap_or_default()
+                .into(),
+            bump: found.bump,
+            iterations: found.iterations as i64,
+            found_at: found.found_at as i64,
+        }
+    }
+}
+
+/// A running grind, for `index.js` to take matches from
+#[napi]
+pub struct Grind {
+    matches: Arc<Mutex<Receiver<Found>>>,
+    cancel: CancellationToken,
+    handle: Option<JoinHandle<std::result::Result<(), String>>>,
+}
+
+/// Starts a grind on threads of its own. The grind saves its matches as the command
+/// would, so pass `--no-file` in `flags` to only get them from `next`. Throws if the
+/// flags can't be ground for. It installs no signal handlers, so Ctrl-C is Node's to
+/// handle; `index.js` stops the grind if the process exits.
+#[napi]
+pub fn start(options: GrindOptions) -> Result<Grind> {
+    let mut argv = vec!["grind".to_owned(), "--owner".to_owned(), options.owner];
+    if let Some(target) = options.target {
+        argv.extend(["--target".to_owned(), target]);
+    }
+    if let Some(threads) = options.threads {
+        argv.extend(["--threads".to_owned(), threads.to_string()]);
+    }
+    argv.extend(options.flags.unwrap_or_default());
+    let args = GrindArgs::try_parse_from(argv)
+        .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+
+    let (sender, matches) = mpsc::channel();
+    let cancel = CancellationToken::new();
+    let grinder = Grinder::new(args)
+        .sink(Channel(sender))
+        .cancellation_token(cancel.clone());
+    let handle = grinder
+        .start()
+        .map_err(|e| Error::new(Status::InvalidArg, e))?;
+    Ok(Grind {
+        matches: Arc::new(Mutex::new(matches)),
+        cancel,
+        handle: Some(handle),
+    })
+}
+
+/// Waits for the next match on a libuv worker thread
+pub struct NextMatch(Arc<Mutex<Receiver<Found>>>);
+
+impl Task for NextMatch {
+    type Output = Option<Found>;
+    type JsValue = Option<Match>;
+
+    fn compute(&mut self) -> Result<Option<Found>> {
+        Ok(self.0.lock().unwrap().recv().ok())
+    }
+
+    fn resolve(&mut self, _: Env, found: Option<Found>) -> Result<Option<Match>> {
+        Ok(found.map(Match::from))
+    }
+}
+
+#[napi]
+impl Grind {
+    /// The next match, or null once the grind is over and every match has been taken
+    #[napi(ts_return_type = "Promise<Match | null>")]
+    pub fn next(&self) -> AsyncTask<NextMatch> {
+        AsyncTask::new(NextMatch(self.matches.clone()))
+    }
+
+    /// Stops the grind, as a Ctrl-C stops the command, and waits for it to write out its
+    /// matches. Matches that weren't taken are still there for `next`. Throws if the
+    /// grind failed partway (a GPU failing).
+    #[napi]
+    pub fn stop(&mut self) -> Result<()> {
+        self.cancel.cancel();
+        match self.handle.take().map(JoinHandle::join) {
+            Some(Ok(Err(e))) => Err(Error::from_reason(e)),
+            Some(Err(_)) => Err(Error::from_reason("the grind panicked")),
+            Some(Ok(Ok(()))) | None => Ok(()),
+        }
+    }
+}
+
+/// The address `owner` gets from `seed` with its canonical bump, to double check a
+/// match. Numbers and bigints are taken as little-endian `u64`s, and strings as their
+/// UTF-8.
+#[napi]
+pub fn check(owner: String, seed: Either4<i64, BigInt, Buffer, String>) -> Result<String> {
+    let invalid = |reason: String| Error::new(Status::InvalidArg, reason);
+    let owner = Pubkey::from_str(&owner).map_err(|e| invalid(e.to_string()))?;
+    let seed = match seed {
+        Either4::A(seed) => u64::try_from(seed)
+            .map_err(|_| invalid(format!("seed {seed} isn't a u64")))?
+            .to_le_bytes()
+            .to_vec(),
+        Either4::B(seed) => match seed.get_u64() {
+            (false, seed, true) => seed.to_le_bytes().to_vec(),
+            _ => return Err(invalid("seed isn't a u64".to_owned())),
+        },
+        Either4::C(bytes) => bytes.to_vec(),
+        Either4::D(seed) => seed.into_bytes(),
+    };
+    if seed.len() > 32 {
+        return Err(invalid(format!(
+            "seeds are at most 32 bytes, got {}",
+            seed.len()
+        )));
+    }
+    Ok(Pubkey::find_program_address(&[&seed], &owner).0.to_string())
+}
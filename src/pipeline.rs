@@ -0,0 +1,157 @@
+//! The steps `grind` takes for each `--bump-mode first` candidate, pulled out so that
+//! `benches/pipeline.rs` can time them one by one. Only built with the `bench` feature.
+
+use clap::Parser;
+use solana_pubkey::Pubkey;
+
+use crate::{
+    args::GrindArgs,
+    base58, curve,
+    grind::PDA_MARKER,
+    matcher::{MatchKind, TargetMatcher},
+    sha256::{Backend, MultiHasher},
+};
+
+/// Length of a lone u64 seed's preimage: `[seed][bump][owner][PDA_MARKER]`
+pub const PREIMAGE_LEN: usize = 8 + 1 + 32 + 21;
+
+/// A lone u64 seed's preimage, at the first bump
+#[derive(Clone)]
+pub struct Preimage {
+    bytes: [u8; PREIMAGE_LEN],
+}
+
+impl Preimage {
+    pub fn new(owner: &Pubkey) -> Preimage {
+        let mut bytes = [0; PREIMAGE_LEN];
+        bytes[8] = u8::MAX;
+        bytes[9..41].copy_from_slice(owner.as_ref());
+        bytes[41..].copy_from_slice(PDA_MARKER);
+        Preimage { bytes }
+    }
+
+    /// Writes in the next seed, all that changes between candidates
+    #[inline(always)]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.bytes[..8].copy_from_slice(&seed.to_le_bytes());
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// The hasher `grind` picks on this CPU
+pub struct Hasher {
+    backend: Backend,
+    inner: MultiHasher,
+}
+
+impl Hasher {
+    /// Room for a batch of `batch` preimages
+    pub fn new(batch: usize) -> Hasher {
+        let backend = Backend::detect(true);
+        let mut inner = MultiHasher::new(backend, batch);
+        inner.set_len(PREIMAGE_LEN);
+        Hasher { backend, inner }
+    }
+
+    pub fn backend(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    /// How many preimages the hasher takes at once
+    pub fn lanes(&self) -> usize {
+        self.backend.lanes()
+    }
+
+    /// Hashes `preimages`, no more than the batch given to `new`
+    #[inline(always)]
+    pub fn hash(&mut self, preimages: &[Preimage]) -> &[[u8; 32]] {
+        for (slot, preimage) in preimages.iter().enumerate() {
+            self.inner.msg_mut(slot).copy_from_slice(&preimage.bytes);
+        }
+        self.inner.hash(preimages.len());
+        &self.inner.hashes()[..preimages.len()]
+    }
+}
+
+/// Base58 encodes `hash` into `out`, returning its length
+#[inline(always)]
+pub fn encode(hash: &[u8; 32], out: &mut [u8; 44]) -> usize {
+    five8::encode_32(hash, out) as usize
+}
+
+/// `encode` for each of `hashes`, several at a time, into `out` along with its length
+#[inline(always)]
+pub fn encode_batch(hashes: &[[u8; 32]], out: &mut [([u8; 44], u8)]) {
+    base58::encode_batch(hashes, out)
+}
+
+#[inline(always)]
+pub fn is_off_curve(hash: &[u8; 32]) -> bool {
+    curve::off_curve(hash)
+}
+
+/// `is_off_curve` for each of `hashes`, several at a time
+#[inline(always)]
+pub fn off_curve_batch(hashes: &[[u8; 32]], out: &mut [bool]) {
+    curve::off_curve_batch(hashes, out)
+}
+
+/// What `grind --target <target>` looks for
+pub struct Target {
+    matcher: TargetMatcher,
+}
+
+impl Target {
+    pub fn new(target: &str) -> Target {
+        let args = GrindArgs::parse_from([
+            "grind",
+            "--owner",
+            "11111111111111111111111111111111",
+            "--target",
+            target,
+        ]);
+        Target {
+            matcher: TargetMatcher::new(&args, None),
+        }
+    }
+
+    /// Whether `hash` can be ruled out before encoding it
+    #[inline(always)]
+    pub fn may_match(&self, hash: &[u8; 32]) -> bool {
+        self.matcher.may_match(hash)
+    }
+
+    #[inline(always)]
+    pub fn matches(&self, hash: &[u8; 32], bs58: &[u8]) -> bool {
+        self.matcher.check(hash, bs58) == MatchKind::Full
+    }
+}
+
+/// Runs `count` seeds from `seed` through the whole pipeline, in batches the size of the
+/// hasher's, returning how many gave a match off the curve
+pub fn grind(hasher: &mut Hasher, target: &Target, owner: &Pubkey, seed: u64, count: u64) -> u64 {
+    let mut preimages = vec![Preimage::new(owner); hasher.lanes()];
+    let mut bs58 = [0; 44];
+    let mut matches = 0;
+    let mut next = seed;
+    while next < seed + count {
+        let batch = (seed + count - next).min(preimages.len() as u64) as usize;
+        for preimage in &mut preimages[..batch] {
+            preimage.set_seed(next);
+            next += 1;
+        }
+        for hash in hasher.hash(&preimages[..batch]) {
+            if !target.may_match(hash) {
+                continue;
+            }
+            let len = encode(hash, &mut bs58);
+            if target.matches(hash, &bs58[..len]) && is_off_curve(hash) {
+                matches += 1;
+            }
+        }
+    }
+    matches
+}
@@ -0,0 +1,189 @@
+//! The `pda_grinder` Python module (`--features pyo3`), built with
+//! `cargo rustc --release --lib --features pyo3 --crate-type cdylib` and imported once
+//! `target/release/libpda_grinder.so` is copied somewhere on the path as `pda_grinder.so`
+//! (`pda_grinder.pyd` on Windows).
+
+use std::{
+    str::FromStr,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use clap::Parser;
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+use solana_pubkey::Pubkey;
+
+use crate::{
+    args::GrindArgs,
+    results::Found,
+    seeds::{parse_hex, HexBytes},
+    sinks::Channel,
+    CancellationToken, Grinder,
+};
+
+/// How long `grind` waits for a match before checking for Ctrl-C again
+const SIGNAL_CHECK_EVERY: Duration = Duration::from_millis(100);
+
+/// A match, as `grind` hands it to the callback and returns it
+#[pyclass(frozen, module = "pda_grinder")]
+#[derive(Clone)]
+struct Match {
+    #[pyo3(get)]
+    pubkey: String,
+    /// As printed, e.g. `1234` or `"vault"`
+    #[pyo3(get)]
+    seed: String,
+    seed_bytes: Vec<u8>,
+    #[pyo3(get)]
+    bump: u8,
+    /// Seeds searched across all threads when this one was found
+    #[pyo3(get)]
+    iterations: u64,
+    /// Unix seconds
+    #[pyo3(get)]
+    found_at: u64,
+}
+
+#[pymethods]
+impl Match {
+    /// The ground seed's exact bytes
+    #[getter]
+    fn seed_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.seed_bytes)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Match(pubkey={:?}, seed={:?}, bump={})",
+            self.pubkey, self.seed, self.bump
+        )
+    }
+}
+
+impl From<Found> for Match {
+    fn from(found: Found) -> Match {
+        Match {
+            pubkey: found.key.to_string(),
+            seed: found.seed,
+            seed_bytes: parse_hex(&found.seed_hex)
+                .map(|HexBytes(bytes)| bytes)
+                .unwrap_or_default(),
+            bump: found.bump,
+            iterations: found.iterations,
+            found_at: found.found_at,
+        }
+    }
+}
+
+/// Grinds seeds for `owner` whose addresses start with `target`, calling `callback`
+/// with each `Match` as it is found and returning them all once the grind stops. Any
+/// other `grind` command flags go in `flags`, e.g. `["--seed-end", "1000000"]` to stop
+/// at the end of a range or `["--suffix", "xyz"]` with no target. The grind saves its
+/// matches as the command would, so pass `"--no-file"` to only get them here.
+/// Ctrl-C stops the grind and raises `KeyboardInterrupt`, and an exception raised by
+/// the callback stops it and is raised in turn. Raises `ValueError` if the flags can't
+/// be ground for, and `RuntimeError` if the grind fails partway (a GPU failing).
+#[pyfunction]
+#[pyo3(signature = (owner, target, threads=None, callback=None, flags=Vec::new()))]
+fn grind(
+    py: Python<'_>,
+    owner: &str,
+    target: Option<&str>,
+    threads: Option<u64>,
+    callback: Option<Bound<'_, PyAny>>,
+    flags: Vec<String>,
+) -> PyResult<Vec<Match>> {
+    let mut argv = vec!["grind".to_owned(), "--owner".to_owned(), owner.to_owned()];
+    if let Some(target) = target {
+        argv.extend(["--target".to_owned(), target.to_owned()]);
+    }
+    if let Some(threads) = threads {
+        argv.extend(["--threads".to_owned(), threads.to_string()]);
+    }
+    argv.extend(flags);
+    let args = GrindArgs::try_parse_from(argv).map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+    let (sender, matches) = mpsc::channel();
+    // Only for waiting on without the GIL, which needs the receiver shared
+    let matches = Mutex::new(matches);
+    let cancel = CancellationToken::new();
+    let grinder = Grinder::new(args)
+        .sink(Channel(sender))
+        .cancellation_token(cancel.clone());
+    let handle = py
+        .allow_threads(|| grinder.start())
+        .map_err(PyValueError::new_err)?;
+
+    let mut found = vec![];
+    let raised = loop {
+        match py.allow_threads(|| matches.lock().unwrap().recv_timeout(SIGNAL_CHECK_EVERY)) {
+            Ok(next) => {
+                let next = Match::from(next);
+                if let Some(callback) = &callback {
+                    if let Err(e) = callback.call1((next.clone(),)) {
+                        break Some(e);
+                    }
+                }
+                found.push(next);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break None,
+        }
+        // Python only runs its signal handlers when asked to here, the grind installs none
+        if let Err(e) = py.check_signals() {
+            break Some(e);
+        }
+    };
+    if let Some(e) = raised {
+        cancel.cancel();
+        let _ = py.allow_threads(|| handle.join());
+        return Err(e);
+    }
+    py.allow_threads(|| handle.join())
+        .map_err(|_| PyRuntimeError::new_err("the grind panicked"))?
+        .map_err(PyRuntimeError::new_err)?;
+    Ok(found)
+}
+
+/// A seed as `check` takes it
+#[derive(FromPyObject)]
+enum Seed {
+    /// A `u64`, little-endian, as `grind` grinds them by default
+    Int(u64),
+    Bytes(Vec<u8>),
+    Str(String),
+}
+
+/// The address `owner` gets from `seed` with its canonical bump, to double check a
+/// match. Integers are taken as little-endian `u64`s, and strings as their UTF-8.
+#[pyfunction]
+fn check(owner: &str, seed: Seed) -> PyResult<String> {
+    let owner = Pubkey::from_str(owner).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let seed = match seed {
+        Seed::Int(seed) => seed.to_le_bytes().to_vec(),
+        Seed::Bytes(bytes) => bytes,
+        Seed::Str(seed) => seed.into_bytes(),
+    };
+    if seed.len() > 32 {
+        return Err(PyValueError::new_err(format!(
+            "seeds are at most 32 bytes, got {}",
+            seed.len()
+        )));
+    }
+    Ok(Pubkey::find_program_address(&[&seed], &owner).0.to_string())
+}
+
+#[pymodule]
+fn pda_grinder(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Match>()?;
+    module.add_function(wrap_pyfunction!(grind, module)?)?;
+    module.add_function(wrap_pyfunction!(check, module)?)?;
+    Ok(())
+}
@@ -0,0 +1,211 @@
+//! Grinding from a queue in Redis (`--queue`), which every worker of a job shares. Chunks
+//! of seeds are numbered as the cursor in `checkpoint` numbers them, from a counter in
+//! Redis that workers take the next one from, so that any number of them can join a
+//! grind and leave it without a coordinator. Matches go onto a list of their own.
+//!
+//! A chunk is leased to the worker that takes it until it is searched and the matches in
+//! it are pushed, with the worker renewing the lease every `RENEW_EVERY`. A chunk whose
+//! lease runs out, because its worker was killed or lost its connection, is taken again
+//! before any new one, so that everything below the counter but the leased chunks has
+//! been searched.
+//!
+//! It needs a build with `--features redis`; without it, only `--queue` fails.
+
+use std::{sync::Arc, time::Duration};
+
+use tracing::error;
+
+use crate::{results::Found, sinks::ResultSink};
+
+/// How long a chunk stays leased to a worker that stops renewing it
+#[cfg_attr(not(feature = "redis"), allow(dead_code))]
+const LEASE: Duration = Duration::from_secs(60);
+
+/// How often a worker renews its leases, and releases the chunks it has searched
+pub(crate) const RENEW_EVERY: Duration = Duration::from_secs(10);
+
+#[cfg(feature = "redis")]
+pub(crate) use redis_queue::Queue;
+
+/// A `--queue` there can't be in a build without Redis
+#[cfg(not(feature = "redis"))]
+pub(crate) enum Queue {}
+
+#[cfg(not(feature = "redis"))]
+impl Queue {
+    pub(crate) fn connect(_: &str, _: &str, _: solana_pubkey::Pubkey) -> Result<Queue, String> {
+        Err("this build can't grind with --queue, rebuild it with `--features redis`".to_string())
+    }
+
+    pub(crate) fn take(&self, _: u64) -> Result<u64, String> {
+        match *self {}
+    }
+
+    pub(crate) fn renew(&self, _: &[u64]) -> Result<(), String> {
+        match *self {}
+    }
+
+    pub(crate) fn release(&self, _: &[u64]) -> Result<(), String> {
+        match *self {}
+    }
+
+    pub(crate) fn give_back(&self, _: &[u64]) -> Result<(), String> {
+        match *self {}
+    }
+
+    pub(crate) fn push(&self, _: &Found) -> Result<(), String> {
+        match *self {}
+    }
+}
+
+/// Pushes matches onto the job's list
+impl ResultSink for Arc<Queue> {
+    fn add(&mut self, found: &Found) {
+        if let Err(e) = self.push(found) {
+            error!("{e}");
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+mod redis_queue {
+    use std::{sync::Mutex, time::Duration};
+
+    use redis::{Commands, Connection, RedisResult};
+    use solana_pubkey::Pubkey;
+
+    use super::LEASE;
+    use crate::results::Found;
+
+    /// A `--queue` and the keys of the job in it
+    pub(crate) struct Queue {
+        url: String,
+        conn: Mutex<Connection>,
+        /// The counter of chunks no worker has taken yet
+        next: String,
+        /// The chunks workers are grinding, scored by when their lease runs out (in
+        /// milliseconds of the Redis clock, so the workers' own clocks don't matter)
+        leases: String,
+        /// What the workers found, as `--format jsonl` records
+        matches: String,
+        owner: Pubkey,
+    }
+
+    fn connect(url: &str) -> RedisResult<Connection> {
+        redis::Client::open(url)?.get_connection_with_timeout(Duration::from_secs(10))
+    }
+
+    /// Milliseconds since the unix epoch, by the Redis server's clock
+    fn now(conn: &mut Connection) -> RedisResult<u64> {
+        let (secs, micros): (u64, u64) = redis::cmd("TIME").query(conn)?;
+        Ok(secs * 1000 + micros / 1000)
+    }
+
+    impl Queue {
+        /// Connects to the Redis at `url` (`redis://[:password@]host[:port][/db]`) for
+        /// `job`
+        pub(crate) fn connect(url: &str, job: &str, owner: Pubkey) -> Result<Queue, String> {
+            let conn = connect(url).map_err(|e| format!("failed to connect to --queue: {e}"))?;
+            let key = |name: &str| format!("pda-grinder:{job}:{name}");
+            Ok(Queue {
+                url: url.to_owned(),
+                conn: Mutex::new(conn),
+                next: key("next"),
+                leases: key("leases"),
+                matches: key("matches"),
+                owner,
+            })
+        }
+
+        /// Leases the next chunk of `chunk_len` seeds, one whose lease ran out if there is
+        /// any, returning its position
+        pub(crate) fn take(&self, chunk_len: u64) -> Result<u64, String> {
+            self.query(|conn| {
+                redis::transaction(conn, &[&self.next, &self.leases], |conn, pipe| {
+                    let now = now(conn)?;
+                    let expired: Vec<u64> =
+                        conn.zrangebyscore_limit(&self.leases, "-inf", now, 0, 1)?;
+                    let position = match expired.first() {
+                        Some(&position) => position,
+                        None => {
+                            let next: Option<u64> = conn.get(&self.next)?;
+                            let position = next.unwrap_or(0);
+                            pipe.set(&self.next, position + chunk_len).ignore();
+                            position
+                        }
+                    };
+                    let expiry = now + LEASE.as_millis() as u64;
+                    pipe.zadd(&self.leases, position, expiry).ignore();
+                    // `None` if another worker got in first, to try again
+                    let done: Option<()> = pipe.query(conn)?;
+                    Ok(done.map(|()| position))
+                })
+            })
+            .map_err(|e| format!("failed to take seeds from --queue: {e}"))
+        }
+
+        /// Extends the leases of the chunks at `positions`, unless they ran out and went to
+        /// another worker in the meantime
+        pub(crate) fn renew(&self, positions: &[u64]) -> Result<(), String> {
+            self.set_expiry(positions, None)
+                .map_err(|e| format!("failed to renew leases in --queue: {e}"))
+        }
+
+        /// Marks the chunks at `positions` as searched
+        pub(crate) fn release(&self, positions: &[u64]) -> Result<(), String> {
+            if positions.is_empty() {
+                return Ok(());
+            }
+            self.query(|conn| conn.zrem(&self.leases, positions))
+                .map_err(|e| format!("failed to mark seeds searched in --queue: {e}"))
+        }
+
+        /// Lets the leases of the chunks at `positions` run out now, for other workers to
+        /// take them first
+        pub(crate) fn give_back(&self, positions: &[u64]) -> Result<(), String> {
+            self.set_expiry(positions, Some(0))
+                .map_err(|e| format!("failed to give seeds back to --queue: {e}"))
+        }
+
+        /// Sets when the leases at `positions` run out (from now if `None`), leaving
+        /// released ones released
+        fn set_expiry(&self, positions: &[u64], expiry: Option<u64>) -> RedisResult<()> {
+            if positions.is_empty() {
+                return Ok(());
+            }
+            self.query(|conn| {
+                let expiry = match expiry {
+                    Some(expiry) => expiry,
+                    None => now(conn)? + LEASE.as_millis() as u64,
+                };
+                let leases: Vec<_> = positions.iter().map(|p| (expiry, *p)).collect();
+                redis::cmd("ZADD")
+                    .arg(&self.leases)
+                    .arg("XX")
+                    .arg(&leases)
+                    .exec(conn)
+            })
+        }
+
+        pub(crate) fn push(&self, found: &Found) -> Result<(), String> {
+            let record = found.to_json(&self.owner).to_string();
+            self.query(|conn| conn.rpush(&self.matches, &record))
+                .map_err(|e| format!("failed to push {} to --queue: {e}", found.key))
+        }
+
+        /// Runs `query` on the connection, connecting again (once) if it was dropped
+        fn query<T>(
+            &self,
+            mut query: impl FnMut(&mut Connection) -> RedisResult<T>,
+        ) -> RedisResult<T> {
+            let mut conn = self.conn.lock().unwrap();
+            match query(&mut conn) {
+                Err(e) if e.is_connection_dropped() || e.is_io_error() => {
+                    *conn = connect(&self.url)?;
+                    query(&mut conn)
+                }
+                result => result,
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! A slow, single-threaded grind that is easy to check by reading it, for testing the
+//! batched hashing, curve checks and pointer juggling of the real one against, and for
+//! anyone who would rather audit twenty lines than trust a match. Each address is hashed
+//! on its own with `sha2` and curve checked by `Pubkey::is_on_curve`, none of which the
+//! real grind uses.
+
+use std::ops::Range;
+
+use sha2::{Digest, Sha256};
+use solana_pubkey::Pubkey;
+
+use crate::grind::PDA_MARKER;
+
+/// The canonical address and bump of `seed` for `owner`, as
+/// `Pubkey::find_program_address(&[seed], owner)` gives them: the first bump down from 255
+/// whose address, `sha256(seed || bump || owner || "ProgramDerivedAddress")`, is off the
+/// curve. `None` for the rare seed whose every bump is on it.
+pub fn derive_canonical(owner: &Pubkey, seed: &[u8]) -> Option<(Pubkey, u8)> {
+    (1..=u8::MAX).rev().find_map(|bump| {
+        let hash: [u8; 32] = Sha256::new()
+            .chain_update(seed)
+            .chain_update([bump])
+            .chain_update(owner)
+            .chain_update(PDA_MARKER)
+            .finalize()
+            .into();
+        let key = Pubkey::new_from_array(hash);
+        (!key.is_on_curve()).then_some((key, bump))
+    })
+}
+
+/// The `(seed, bump, address)` of every seed in `seeds`, in order, whose canonical address
+/// starts with `target` and whose canonical bump is at least `min_bump`. These are what
+/// `grind --target <target> --seed-start <start> --seed-end <end>` finds, with
+/// `min_bump` 256 minus its `--lookahead` (255 by default) or, for `--bump-mode any`, 1.
+/// Seeds are little-endian `u64`s, as the grind's are by default.
+pub fn grind_range(
+    owner: &Pubkey,
+    seeds: Range<u64>,
+    target: &str,
+    min_bump: u8,
+) -> Vec<(u64, u8, Pubkey)> {
+    seeds
+        .filter_map(|seed| {
+            let (key, bump) = derive_canonical(owner, &seed.to_le_bytes())?;
+            (bump >= min_bump && key.to_string().starts_with(target)).then_some((seed, bump, key))
+        })
+        .collect()
+}
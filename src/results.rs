@@ -0,0 +1,344 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread::JoinHandle,
+    time::{Duration, Instant, SystemTime},
+};
+
+use solana_pubkey::Pubkey;
+use tracing::{error, warn};
+
+use crate::{args::OutputFormat, rpc::Rpc, sinks::ResultSink};
+
+pub(crate) const CSV_HEADER: &str = "pubkey,seed,seed_hex,bump,owner,found_at,iterations\n";
+
+/// How long found seeds can sit in the writer's buffer
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A match, as sent to the results writer and passed on to its sinks
+#[derive(Clone, Debug)]
+pub struct Found {
+    /// The PDA
+    pub key: Pubkey,
+    /// Seed as printed, see `format_seed`
+    pub seed: String,
+    /// The exact ground seed bytes, whatever the seed type and endianness
+    pub seed_hex: String,
+    pub bump: u8,
+    /// Seeds searched across all threads when this one was found
+    pub iterations: u64,
+    /// Unix seconds
+    pub found_at: u64,
+}
+
+impl Found {
+    /// The match as a `--format jsonl` record
+    pub(crate) fn to_json(&self, owner: &Pubkey) -> serde_json::Value {
+        serde_json::json!({
+            "pubkey": self.key.to_string(),
+            "seed": self.seed,
+            "seed_hex": self.seed_hex,
+            "bump": self.bump,
+            "owner": owner.to_string(),
+            "timestamp": self.found_at,
+        })
+    }
+}
+
+/// Writes `found` to `out` as a record of `format`
+pub(crate) fn write_record(
+    out: &mut impl Write,
+    found: &Found,
+    format: OutputFormat,
+    owner: &Pubkey,
+) -> io::Result<()> {
+    let Found {
+        key,
+        seed,
+        seed_hex,
+        bump,
+        iterations,
+        found_at,
+    } = found;
+    match format {
+        OutputFormat::Text => writeln!(
+            out,
+            "{key}: {seed} hex {seed_hex} bump {bump} owner {owner}"
+        ),
+        OutputFormat::Jsonl => writeln!(out, "{}", found.to_json(owner)),
+        OutputFormat::Csv => writeln!(
+            out,
+            "{key},{},{seed_hex},{bump},{owner},{},{iterations}",
+            csv_field(seed),
+            format_utc(*found_at),
+        ),
+    }
+}
+
+/// The `--output` file. Records are only ever written out as whole lines, and a
+/// line torn by a crash or power loss is cut off the next time the file is opened,
+/// so readers never see a partial record.
+pub struct ResultsFile {
+    path: PathBuf,
+    file: File,
+    /// Lines waiting for `flush`
+    buffer: Vec<u8>,
+    format: OutputFormat,
+    owner: Pubkey,
+    /// Flush and fsync after every record instead of once a second
+    pub fsync: bool,
+}
+
+impl ResultsFile {
+    /// Opens `path` for appending, creating it and its parent directories as needed.
+    /// A new (or empty) csv file starts with the header row.
+    pub fn open(path: &Path, format: OutputFormat, owner: Pubkey, fsync: bool) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = File::options()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+
+        // Anything after the last newline is a record that was cut short
+        let len = file.metadata()?.len();
+        if len > 0 {
+            let mut last = [0];
+            file.seek(SeekFrom::Start(len - 1))?;
+            file.read_exact(&mut last)?;
+            if last != *b"\n" {
+                let contents = std::fs::read(path)?;
+                let complete = contents
+                    .iter()
+                    .rposition(|b| *b == b'\n')
+                    .map_or(0, |i| i + 1);
+                file.set_len(complete as u64)?;
+                warn!(
+                    "dropped a partial record at the end of {}: {:?}",
+                    path.display(),
+                    String::from_utf8_lossy(&contents[complete..])
+                );
+            }
+        }
+
+        if format == OutputFormat::Csv && file.metadata()?.len() == 0 {
+            file.write_all(CSV_HEADER.as_bytes())?;
+        }
+        Ok(ResultsFile {
+            path: path.to_owned(),
+            file,
+            buffer: Vec::new(),
+            format,
+            owner,
+            fsync,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Addresses already in the file, skipping lines that don't parse (e.g. from
+    /// another `--format`). The address stands in for the owner and seeds that
+    /// derive it, and unlike the seed alone also tells apart `--seeds` templates.
+    pub(crate) fn read_found(&self) -> HashSet<Pubkey> {
+        let Ok(contents) = std::fs::read_to_string(&self.path) else {
+            return HashSet::new();
+        };
+        contents
+            .lines()
+            .filter_map(|line| match self.format {
+                OutputFormat::Text => line.split_once(':')?.0.parse().ok(),
+                OutputFormat::Jsonl => serde_json::from_str::<serde_json::Value>(line)
+                    .ok()?
+                    .get("pubkey")?
+                    .as_str()?
+                    .parse()
+                    .ok(),
+                OutputFormat::Csv => line.split_once(',')?.0.parse().ok(),
+            })
+            .collect()
+    }
+}
+
+impl ResultSink for ResultsFile {
+    /// Buffers one found seed, see `flush`
+    fn add(&mut self, found: &Found) {
+        // Writing to a `Vec` can't fail
+        let _ = write_record(&mut self.buffer, found, self.format, &self.owner);
+        if self.fsync {
+            self.flush();
+        }
+    }
+
+    /// Writes out buffered lines in a single `write_all`, so they can only be cut
+    /// short by a crash, and not interleave with other processes appending to the file
+    fn flush(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let written = self.file.write_all(&self.buffer);
+        self.buffer.clear();
+        if let Err(e) = written.and_then(|()| match self.fsync {
+            true => self.file.sync_data(),
+            false => Ok(()),
+        }) {
+            error!("failed to write to {}: {e}", self.path.display());
+        }
+    }
+
+    fn finish(self: Box<Self>) {
+        if let Err(e) = self.file.sync_all() {
+            error!("failed to sync {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Hands found seeds to the sinks (the `--output` file, `--db`, `--queue`, `--webhook`
+/// and any a `Grinder` adds) from a thread of its own, so grinding threads only ever
+/// push onto a channel. Addresses that are already in the file, `seen`, or were found
+/// earlier in the run (threads can meet after wrapping around), are only passed on once.
+/// With an `rpc` (`--check-unused`), ones that already hold an account aren't at all.
+pub(crate) struct ResultsWriter {
+    sender: Sender<Found>,
+    handle: JoinHandle<()>,
+}
+
+impl ResultsWriter {
+    pub(crate) fn spawn(
+        mut seen: HashSet<Pubkey>,
+        mut sinks: Vec<Box<dyn ResultSink>>,
+        rpc: Option<Rpc>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel::<Found>();
+        let handle = std::thread::spawn(move || {
+            let mut last_flush = Instant::now();
+            loop {
+                match receiver.recv_timeout(FLUSH_INTERVAL) {
+                    Ok(found) if !seen.insert(found.key) => {}
+                    Ok(found) if rpc.as_ref().is_some_and(|rpc| is_used(rpc, &found.key)) => {}
+                    Ok(found) => {
+                        for sink in &mut sinks {
+                            sink.add(&found);
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    // Every sender is gone, so the grind is over
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if last_flush.elapsed() >= FLUSH_INTERVAL {
+                    sinks.iter_mut().for_each(|sink| sink.flush());
+                    last_flush = Instant::now();
+                }
+            }
+
+            for mut sink in sinks {
+                sink.flush();
+                sink.finish();
+            }
+        });
+        ResultsWriter { sender, handle }
+    }
+
+    pub(crate) fn sender(&self) -> Sender<Found> {
+        self.sender.clone()
+    }
+
+    /// Writes out everything sent so far and waits for it to reach the disk. Every
+    /// other sender has to be dropped first.
+    pub(crate) fn finish(self) {
+        drop(self.sender);
+        self.handle.join().unwrap();
+    }
+}
+
+/// Whether `key` already holds an account, warning that it does. One that can't be
+/// checked is kept, with an error.
+fn is_used(rpc: &Rpc, key: &Pubkey) -> bool {
+    match rpc.account(key) {
+        Ok(Some(account)) => {
+            warn!(
+                pubkey = %key,
+                "leaving out {key}, which already holds an account of {} lamports owned by {}",
+                account.lamports,
+                account.owner
+            );
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            error!("failed to check that {key} is unused with --rpc, keeping it: {e}");
+            false
+        }
+    }
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Quotes `field` if it holds a comma, quote or newline (`--seed-charset printable`
+/// and wordlist seeds can)
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// `output` with the current time and a random run id added to its name, e.g.
+/// `results-2024-06-01T12-00-00-abcd.txt` for `results.txt`
+pub(crate) fn per_run_path(output: &Path) -> PathBuf {
+    let time = format_utc(unix_now()).replace(':', "-");
+    let time = time.trim_end_matches('Z');
+    let run_id = rand::random::<u16>();
+    let stem = output
+        .file_stem()
+        .unwrap_or("results".as_ref())
+        .to_string_lossy();
+    let name = match output.extension() {
+        Some(ext) => format!("{stem}-{time}-{run_id:04x}.{}", ext.to_string_lossy()),
+        None => format!("{stem}-{time}-{run_id:04x}"),
+    };
+    output.with_file_name(name)
+}
+
+/// Writes `manifest` to `path` through a temporary file, so that a crash never
+/// leaves half of one behind
+pub(crate) fn write_manifest(path: &Path, manifest: &serde_json::Value) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let contents = serde_json::to_string_pretty(manifest).map_err(io::Error::from)?;
+    std::fs::write(&tmp, contents + "\n")?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Formats unix seconds as `2025-01-31T18:00:00Z`, the inverse of `parse_utc`
+pub(crate) fn format_utc(secs: u64) -> String {
+    let (days, secs) = (secs / (24 * 60 * 60), secs % (24 * 60 * 60));
+    let (h, min, sec) = (secs / (60 * 60), secs / 60 % 60, secs % 60);
+
+    // Years counted from March so leap days come last, as in `parse_utc`
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (year_of_era * 365 + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let d = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let m = (month_from_march + 2) % 12 + 1;
+    let y = era * 400 + year_of_era + u64::from(m <= 2);
+    format!("{y:04}-{m:02}-{d:02}T{h:02}:{min:02}:{sec:02}Z")
+}
@@ -0,0 +1,85 @@
+//! Asking a Solana RPC node whether a match's address already holds an account, for
+//! `--check-unused` to leave out the ones that do.
+
+use std::time::Duration;
+
+use solana_pubkey::Pubkey;
+
+/// How many times a request is sent before giving up on it, waiting `RETRY_WAIT` longer
+/// each time
+const ATTEMPTS: u32 = 3;
+const RETRY_WAIT: Duration = Duration::from_secs(1);
+
+/// How long a request can take to be answered
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An account found at an address
+pub(crate) struct Account {
+    pub(crate) lamports: u64,
+    pub(crate) owner: String,
+}
+
+/// A JSON-RPC endpoint, such as `https://api.mainnet-beta.solana.com`
+pub(crate) struct Rpc {
+    agent: ureq::Agent,
+    url: String,
+}
+
+impl Rpc {
+    pub(crate) fn new(url: String) -> Self {
+        Rpc {
+            agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+            url,
+        }
+    }
+
+    /// The account at `key`, if there is one, from `getAccountInfo` (without its data)
+    pub(crate) fn account(&self, key: &Pubkey) -> Result<Option<Account>, String> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAccountInfo",
+            "params": [
+                key.to_string(),
+                {
+                    "encoding": "base64",
+                    "dataSlice": { "offset": 0, "length": 0 },
+                    "commitment": "confirmed",
+                },
+            ],
+        });
+        let mut attempt = 1;
+        let response: serde_json::Value = loop {
+            let sent = self
+                .agent
+                .post(&self.url)
+                .set("Content-Type", "application/json")
+                .send_string(&request.to_string())
+                .map_err(|e| e.to_string())
+                .and_then(|response| response.into_string().map_err(|e| e.to_string()))
+                .and_then(|body| serde_json::from_str(&body).map_err(|e| e.to_string()));
+            match sent {
+                Ok(response) => break response,
+                Err(e) if attempt == ATTEMPTS => return Err(e),
+                Err(_) => std::thread::sleep(RETRY_WAIT * attempt),
+            }
+            attempt += 1;
+        };
+        if let Some(error) = response.get("error") {
+            return Err(error["message"]
+                .as_str()
+                .map_or_else(|| error.to_string(), str::to_string));
+        }
+        let value = &response["result"]["value"];
+        if value.is_null() {
+            return Ok(None);
+        }
+        match (value["lamports"].as_u64(), value["owner"].as_str()) {
+            (Some(lamports), Some(owner)) => Ok(Some(Account {
+                lamports,
+                owner: owner.to_string(),
+            })),
+            _ => Err(format!("unexpected getAccountInfo response {response}")),
+        }
+    }
+}
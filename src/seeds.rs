@@ -0,0 +1,373 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use solana_pubkey::Pubkey;
+
+use crate::args::parse_pubkey;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SeedType {
+    U16,
+    U32,
+    U64,
+    U128,
+}
+
+impl SeedType {
+    pub fn width(self) -> usize {
+        match self {
+            SeedType::U16 => 2,
+            SeedType::U32 => 4,
+            SeedType::U64 => 8,
+            SeedType::U128 => 16,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            SeedType::U16 => "u16",
+            SeedType::U32 => "u32",
+            SeedType::U64 => "u64",
+            SeedType::U128 => "u128",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Endianness {
+    Le,
+    Be,
+}
+
+/// How ground seed bytes are shown to the user (and read back by `check`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeedKind {
+    Int(Endianness),
+    Bytes,
+    /// `--seed-charset` and `--wordlist` seeds
+    Str,
+    /// The public keys of the keypairs a `--template` grinds
+    Key,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum SeedCharset {
+    Alphanumeric,
+    Alpha,
+    Lower,
+    Numeric,
+    Hex,
+    /// Printable ASCII except for space, quotes and backslash
+    Printable,
+}
+
+impl SeedCharset {
+    pub(crate) fn chars(self) -> &'static [u8] {
+        match self {
+            SeedCharset::Alphanumeric => {
+                b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz"
+            }
+            SeedCharset::Alpha => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz",
+            SeedCharset::Lower => b"abcdefghijklmnopqrstuvwxyz",
+            SeedCharset::Numeric => b"0123456789",
+            SeedCharset::Hex => b"0123456789abcdef",
+            SeedCharset::Printable => {
+                b"!#$%&()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[]^_`abcdefghijklmnopqrstuvwxyz{|}~"
+            }
+        }
+    }
+}
+
+/// Seeds made of `--words` dictionary words followed by `--word-digits` digits. Every
+/// combination has an index, so threads can split them up like any other counter.
+pub struct Wordlist {
+    words: Vec<Vec<u8>>,
+    count: u32,
+    digits: u32,
+    /// Number of combinations
+    pub(crate) space: u64,
+}
+
+impl Wordlist {
+    pub(crate) fn load(path: &Path, count: u32, digits: u32) -> Result<Wordlist, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read wordlist {}: {e}", path.display()))?;
+        let words: Vec<Vec<u8>> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|word| !word.is_empty())
+            .map(|word| word.as_bytes().to_vec())
+            .collect();
+        if words.is_empty() {
+            return Err(format!("wordlist {} has no words", path.display()));
+        }
+        let space = (words.len() as u64)
+            .checked_pow(count)
+            .and_then(|space| space.checked_mul(10_u64.pow(digits)))
+            .ok_or_else(|| {
+                format!(
+                    "{count} words from {} make too many combinations, use fewer --words",
+                    path.display()
+                )
+            })?;
+        Ok(Wordlist {
+            words,
+            count,
+            digits,
+            space,
+        })
+    }
+
+    /// Writes seed number `index` (wrapping around) into `out` and returns its length,
+    /// or `None` if it is too long to be a seed
+    #[inline(always)]
+    pub(crate) fn write(&self, index: u64, out: &mut [u8; 32]) -> Option<usize> {
+        let digits_space = 10_u64.pow(self.digits);
+        let base = self.words.len() as u64;
+        let mut index = index % self.space;
+        let mut number = index % digits_space;
+        index /= digits_space;
+
+        let mut len = 0;
+        for _ in 0..self.count {
+            let word = &self.words[(index % base) as usize];
+            index /= base;
+            out.get_mut(len..len + word.len())?.copy_from_slice(word);
+            len += word.len();
+        }
+        let end = len + self.digits as usize;
+        for digit in out.get_mut(len..end)?.iter_mut().rev() {
+            *digit = b'0' + (number % 10) as u8;
+            number /= 10;
+        }
+        Some(end)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum SeedComponent {
+    Fixed(Vec<u8>),
+    Pubkey(Pubkey),
+    /// The seed being ground, optionally with its integer type (`<grind:u32>`)
+    Ground(Option<SeedType>),
+}
+
+/// The seeds a PDA is derived from, with exactly one of them being ground
+#[derive(Clone, Debug)]
+pub struct SeedTemplate(pub Vec<SeedComponent>);
+
+impl SeedTemplate {
+    pub(crate) fn from_args(
+        seeds: Option<SeedTemplate>,
+        seed_prefix: Option<HexBytes>,
+    ) -> SeedTemplate {
+        match (seeds, seed_prefix) {
+            (Some(seeds), _) => seeds,
+            (None, Some(HexBytes(prefix))) => SeedTemplate(vec![
+                SeedComponent::Fixed(prefix),
+                SeedComponent::Ground(None),
+            ]),
+            (None, None) => SeedTemplate(vec![SeedComponent::Ground(None)]),
+        }
+    }
+
+    /// Concatenated fixed seeds before and after the ground seed, which is how they
+    /// end up in the preimage
+    pub(crate) fn fixed_bytes(&self) -> (Vec<u8>, Vec<u8>) {
+        let ground = self
+            .0
+            .iter()
+            .position(|c| matches!(c, SeedComponent::Ground(_)))
+            .unwrap();
+        let concat = |components: &[SeedComponent]| {
+            components
+                .iter()
+                .flat_map(|c| match c {
+                    SeedComponent::Fixed(bytes) => bytes.as_slice(),
+                    SeedComponent::Pubkey(key) => key.as_ref(),
+                    SeedComponent::Ground(_) => &[],
+                })
+                .copied()
+                .collect()
+        };
+        (concat(&self.0[..ground]), concat(&self.0[ground + 1..]))
+    }
+
+    /// Integer type of the ground seed, from `--seed-type` or a typed `<grind:..>`
+    pub(crate) fn seed_type(&self, seed_type: Option<SeedType>) -> Result<SeedType, String> {
+        let template_type = self.0.iter().find_map(|c| match c {
+            SeedComponent::Ground(seed_type) => *seed_type,
+            _ => None,
+        });
+        match (template_type, seed_type) {
+            (Some(a), Some(b)) if a != b => Err(format!(
+                "--seeds grinds a {} but --seed-type is {}",
+                a.name(),
+                b.name()
+            )),
+            (a, b) => Ok(a.or(b).unwrap_or(SeedType::U64)),
+        }
+    }
+
+    pub(crate) fn seeds<'a>(&'a self, ground: &'a [u8]) -> Vec<&'a [u8]> {
+        self.0
+            .iter()
+            .map(|c| match c {
+                SeedComponent::Fixed(bytes) => bytes.as_slice(),
+                SeedComponent::Pubkey(key) => key.as_ref(),
+                SeedComponent::Ground(_) => ground,
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for SeedTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let components = self
+            .0
+            .iter()
+            .map(|c| match c {
+                SeedComponent::Fixed(bytes) => match std::str::from_utf8(bytes) {
+                    Ok(s) => format!("b{s:?}"),
+                    Err(_) => format!("0x{}", to_hex(bytes)),
+                },
+                SeedComponent::Pubkey(key) => key.to_string(),
+                SeedComponent::Ground(None) => "<ground seed>".to_string(),
+                SeedComponent::Ground(Some(seed_type)) => format!("<ground {}>", seed_type.name()),
+            })
+            .collect::<Vec<_>>();
+        write!(f, "[{}]", components.join(", "))
+    }
+}
+
+/// A `--seeds` template, one seed of it at a time: a `"string"`, `0x<hex>` bytes, a
+/// `<pubkey:KEY>` or the `<grind>` (or typed `<grind:u32>`) seed, separated by commas
+pub(crate) fn parse_seed_template(s: &str) -> Result<SeedTemplate, String> {
+    let mut components = vec![];
+    let mut rest = s.trim();
+    while !rest.is_empty() {
+        let (component, tail) = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').ok_or("unterminated string seed")?;
+            (
+                SeedComponent::Fixed(quoted.as_bytes()[..end].to_vec()),
+                &quoted[end + 1..],
+            )
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let token = rest[..end].trim();
+            let component = match token.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+                Some("grind") => SeedComponent::Ground(None),
+                Some(inner) => match inner.split_once(':') {
+                    Some(("grind", ty)) => {
+                        SeedComponent::Ground(Some(SeedType::from_str(ty, true)?))
+                    }
+                    Some(("pubkey", key)) => SeedComponent::Pubkey(parse_pubkey(key)?),
+                    _ => return Err(format!("unknown seed placeholder {token:?}")),
+                },
+                None if token.starts_with("0x") => SeedComponent::Fixed(parse_hex(token)?.0),
+                None => {
+                    return Err(format!(
+                        "unrecognized seed {token:?}, expected \"string\", 0x<hex>, \
+                         <pubkey:KEY> or <grind>"
+                    ))
+                }
+            };
+            (component, &rest[end..])
+        };
+        components.push(component);
+        rest = tail.trim_start();
+        if !rest.is_empty() {
+            rest = rest
+                .strip_prefix(',')
+                .ok_or_else(|| format!("expected ',' before {rest:?}"))?
+                .trim_start();
+        }
+    }
+
+    // Same limits `create_program_address` enforces, with one seed left for the bump
+    let ground_seeds = components
+        .iter()
+        .filter(|c| matches!(c, SeedComponent::Ground(_)))
+        .count();
+    if ground_seeds != 1 {
+        return Err(format!(
+            "expected exactly one <grind> seed, got {ground_seeds}"
+        ));
+    }
+    if components.len() > 15 {
+        return Err(format!(
+            "at most 15 seeds fit next to the bump, got {}",
+            components.len()
+        ));
+    }
+    for component in &components {
+        if let SeedComponent::Fixed(bytes) = component {
+            if bytes.len() > 32 {
+                return Err(format!(
+                    "seeds can be at most 32 bytes, got {}",
+                    bytes.len()
+                ));
+            }
+        }
+    }
+    Ok(SeedTemplate(components))
+}
+
+/// Raw bytes given on the command line as (optionally `0x` prefixed) hex
+#[derive(Clone, Debug)]
+pub struct HexBytes(pub Vec<u8>);
+
+pub(crate) fn parse_hex(s: &str) -> Result<HexBytes, String> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if let Some(c) = s.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("{c:?} isn't a hex digit"));
+    }
+    if !s.len().is_multiple_of(2) {
+        return Err("hex string must have an even number of digits".to_string());
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|e| e.to_string()))
+        .collect::<Result<_, _>>()
+        .map(HexBytes)
+}
+
+/// Parses a fixed seed: hex if it starts with `0x`, UTF-8 bytes otherwise
+pub(crate) fn parse_seed_literal(s: &str) -> Result<HexBytes, String> {
+    let bytes = match s.starts_with("0x") {
+        true => parse_hex(s)?,
+        false => HexBytes(s.as_bytes().to_vec()),
+    };
+    if bytes.0.len() > 32 {
+        return Err(format!(
+            "seeds can be at most 32 bytes, got {}",
+            bytes.0.len()
+        ));
+    }
+    Ok(bytes)
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Formats ground seed bytes the way `check` expects them back: decimal for integer
+/// seeds, hex for `--seed-len` seeds, a quoted string for `--seed-charset` seeds and base58
+/// for keys
+pub(crate) fn format_seed(seed_bytes: &[u8], kind: SeedKind) -> String {
+    match kind {
+        SeedKind::Int(Endianness::Le) => {
+            let mut le_bytes = [0; 16];
+            le_bytes[..seed_bytes.len()].copy_from_slice(seed_bytes);
+            u128::from_le_bytes(le_bytes).to_string()
+        }
+        SeedKind::Int(Endianness::Be) => {
+            let mut be_bytes = [0; 16];
+            be_bytes[16 - seed_bytes.len()..].copy_from_slice(seed_bytes);
+            u128::from_be_bytes(be_bytes).to_string()
+        }
+        SeedKind::Bytes => format!("0x{}", to_hex(seed_bytes)),
+        SeedKind::Str => format!("{:?}", String::from_utf8_lossy(seed_bytes)),
+        SeedKind::Key => Pubkey::new_from_array(seed_bytes.try_into().unwrap()).to_string(),
+    }
+}
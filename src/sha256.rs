@@ -0,0 +1,685 @@
+//! Multi-buffer SHA-256, hashing several preimages of the same length at once with one
+//! preimage per SIMD lane. The lanes are plain arrays that LLVM vectorizes inside the
+//! `#[target_feature]` functions, picked at runtime.
+//!
+//! CPUs with SHA instructions (SHA-NI on x86, the ARMv8 SHA2 extension) hash one
+//! preimage at a time instead, which is about as fast as 8 AVX2 lanes and much faster
+//! than anything else when only one preimage is ready at a time.
+//!
+//! Preimages are written one after another into an arena of whole batches, which is
+//! hashed in one go, a group of lanes at a time. They are padded once, up front, and
+//! only the compression function runs per hash. A 62 byte preimage (a lone u64 seed) takes two blocks, the second of which is
+//! nothing but padding, so its message schedule is worked out once as well.
+
+use clap::ValueEnum;
+use sha2::digest::{consts::U64, generic_array::GenericArray};
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+pub(crate) const H: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// 16 lanes in AVX-512 registers
+    Avx512,
+    /// One preimage at a time with the x86 SHA extensions
+    ShaNi,
+    /// 8 lanes in AVX2 registers
+    Avx2,
+    /// One preimage at a time with the ARMv8 SHA2 instructions
+    #[cfg_attr(not(target_arch = "aarch64"), allow(dead_code))]
+    ArmSha2,
+    /// One preimage at a time through the `sha2` crate's portable code
+    Portable,
+}
+
+impl Backend {
+    /// Whether this CPU can hash with this backend
+    pub(crate) fn supported(self) -> bool {
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx512 => {
+                is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512vl")
+            }
+            #[cfg(target_arch = "x86_64")]
+            Backend::ShaNi => {
+                is_x86_feature_detected!("sha")
+                    && is_x86_feature_detected!("sse2")
+                    && is_x86_feature_detected!("ssse3")
+                    && is_x86_feature_detected!("sse4.1")
+            }
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "aarch64")]
+            Backend::ArmSha2 => std::arch::is_aarch64_feature_detected!("sha2"),
+            Backend::Portable => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// The fastest backend this CPU supports. Without `multi_lane`, preimages are hashed
+    /// one at a time and the SIMD lanes would sit idle.
+    pub(crate) fn detect(multi_lane: bool) -> Backend {
+        let fastest: &[Backend] = if multi_lane {
+            &[
+                Backend::Avx512,
+                Backend::ShaNi,
+                Backend::Avx2,
+                Backend::ArmSha2,
+            ]
+        } else {
+            &[Backend::ShaNi, Backend::ArmSha2]
+        };
+        fastest
+            .iter()
+            .copied()
+            .find(|backend| backend.supported())
+            .unwrap_or(Backend::Portable)
+    }
+
+    pub(crate) fn lanes(self) -> usize {
+        match self {
+            Backend::Avx512 => 16,
+            Backend::Avx2 => 8,
+            Backend::ShaNi | Backend::ArmSha2 => 4,
+            Backend::Portable => 1,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Backend::Avx512 => "avx512 x16",
+            Backend::ShaNi => "sha-ni x4",
+            Backend::Avx2 => "avx2 x8",
+            Backend::ArmSha2 => "armv8 sha2 x4",
+            Backend::Portable => "portable",
+        }
+    }
+}
+
+/// Preimages waiting to be hashed and their hashes
+pub(crate) struct MultiHasher {
+    backend: Backend,
+    len: usize,
+    /// `K[t] + W[t]` for a final block that holds only padding
+    tail: Option<[u32; 64]>,
+    /// How many preimages fit, a whole number of lane groups
+    slots: usize,
+    /// The padded preimages back to back, each starting on a cache line
+    msgs: Vec<Block>,
+    hashes: Vec<[u8; 32]>,
+    /// What `hash_byte` can reuse of the first preimage
+    fixed: Box<Fixed>,
+}
+
+/// A cache line's worth of the arena, which is also a SHA-256 block
+#[derive(Clone, Copy)]
+#[repr(C, align(64))]
+struct Block([u8; 64]);
+
+/// The arena's bytes
+#[inline(always)]
+fn bytes(blocks: &[Block]) -> &[u8] {
+    // SAFETY: a `Block` is 64 bytes with no padding
+    unsafe { core::slice::from_raw_parts(blocks.as_ptr().cast(), blocks.len() * 64) }
+}
+
+#[inline(always)]
+fn bytes_mut(blocks: &mut [Block]) -> &mut [u8] {
+    // SAFETY: as above
+    unsafe { core::slice::from_raw_parts_mut(blocks.as_mut_ptr().cast(), blocks.len() * 64) }
+}
+
+/// Everything about the first preimage's hash that doesn't depend on the byte at `pos`
+struct Fixed {
+    pos: usize,
+    /// The state going into the block holding `pos`
+    block_start: [u32; 8],
+    /// The working variables after the rounds before the word holding `pos`
+    vars: [u32; 8],
+    /// The block's message schedule, missing the words in `varying`
+    w: [u32; 64],
+    /// Bit `t` is set if `w[t]` depends on the byte at `pos`
+    varying: u64,
+}
+
+impl MultiHasher {
+    /// Room for `capacity` preimages, rounded up to fill the last group of lanes
+    pub(crate) fn new(backend: Backend, capacity: usize) -> MultiHasher {
+        let slots = capacity.max(1).next_multiple_of(backend.lanes());
+        MultiHasher {
+            backend,
+            len: 0,
+            tail: None,
+            slots,
+            msgs: Vec::new(),
+            hashes: vec![[0; 32]; slots],
+            fixed: Box::new(Fixed {
+                pos: 0,
+                block_start: H,
+                vars: H,
+                w: [0; 64],
+                varying: 0,
+            }),
+        }
+    }
+
+    /// Sets the length of the preimages, writing the SHA-256 padding after them. Slots
+    /// keep their padding until the length changes, so only the preimages themselves
+    /// have to be written for each hash.
+    pub(crate) fn set_len(&mut self, len: usize) {
+        if len == self.len {
+            return;
+        }
+        self.len = len;
+        let padded_len = padded_len(len);
+        self.msgs
+            .resize(self.slots * padded_len / 64, Block([0; 64]));
+        for msg in bytes_mut(&mut self.msgs).chunks_exact_mut(padded_len) {
+            msg[len..].fill(0);
+            msg[len] = 0x80;
+            msg[padded_len - 8..].copy_from_slice(&(len as u64 * 8).to_be_bytes());
+        }
+        self.tail = (padded_len - len > 64).then(|| {
+            let tail = &bytes(&self.msgs)[padded_len - 64..padded_len];
+            let mut w = [0_u32; 64];
+            for (t, word) in tail.chunks_exact(4).enumerate() {
+                w[t] = u32::from_be_bytes(word.try_into().unwrap());
+            }
+            for t in 16..64 {
+                w[t] = schedule(&w, t);
+            }
+            core::array::from_fn(|t| K[t].wrapping_add(w[t]))
+        });
+    }
+
+    /// The `slot`th preimage, to be written before `hash`
+    #[inline(always)]
+    pub(crate) fn msg_mut(&mut self, slot: usize) -> &mut [u8] {
+        let at = slot * padded_len(self.len);
+        &mut bytes_mut(&mut self.msgs)[at..at + self.len]
+    }
+
+    #[inline(always)]
+    pub(crate) fn msg(&self, slot: usize) -> &[u8] {
+        let at = slot * padded_len(self.len);
+        &bytes(&self.msgs)[at..at + self.len]
+    }
+
+    /// Overwrites the preimage in slot `to` with the one in slot `from`
+    #[inline(always)]
+    pub(crate) fn copy_msg(&mut self, from: usize, to: usize) {
+        let stride = padded_len(self.len);
+        bytes_mut(&mut self.msgs).copy_within(from * stride..from * stride + self.len, to * stride);
+    }
+
+    /// Hashes the first `count` preimages
+    #[inline(always)]
+    pub(crate) fn hash(&mut self, count: usize) {
+        let blocks = padded_len(self.len) / 64;
+        let tail = self.tail.as_ref();
+        let lanes = match self.backend {
+            // Interleaving lanes only pays off when there's more than one preimage
+            Backend::ShaNi | Backend::ArmSha2 if count == 1 => 1,
+            backend => backend.lanes(),
+        };
+        let groups = bytes(&self.msgs).chunks_exact(lanes * blocks * 64);
+        let groups = groups.zip(self.hashes.chunks_exact_mut(lanes));
+        for (msgs, hashes) in groups.take(count.div_ceil(lanes)) {
+            match self.backend {
+                // SAFETY: `detect` only picks these on CPUs that support them
+                #[cfg(target_arch = "x86_64")]
+                Backend::Avx512 => unsafe {
+                    hash_avx512(msgs, blocks, tail, hashes.try_into().unwrap())
+                },
+                #[cfg(target_arch = "x86_64")]
+                Backend::Avx2 => unsafe {
+                    hash_avx2(msgs, blocks, tail, hashes.try_into().unwrap())
+                },
+                #[cfg(target_arch = "x86_64")]
+                Backend::ShaNi if lanes == 1 => unsafe {
+                    hash_sha_ni::<1>(msgs, blocks, tail, hashes.try_into().unwrap())
+                },
+                #[cfg(target_arch = "x86_64")]
+                Backend::ShaNi => unsafe {
+                    hash_sha_ni::<4>(msgs, blocks, tail, hashes.try_into().unwrap())
+                },
+                #[cfg(target_arch = "aarch64")]
+                Backend::ArmSha2 if lanes == 1 => unsafe {
+                    hash_arm_sha2::<1>(msgs, blocks, tail, hashes.try_into().unwrap())
+                },
+                #[cfg(target_arch = "aarch64")]
+                Backend::ArmSha2 => unsafe {
+                    hash_arm_sha2::<4>(msgs, blocks, tail, hashes.try_into().unwrap())
+                },
+                _ => {
+                    // SAFETY: `GenericArray<u8, U64>` is a `[u8; 64]`
+                    let msg = unsafe {
+                        core::slice::from_raw_parts(
+                            msgs.as_ptr().cast::<GenericArray<u8, U64>>(),
+                            blocks,
+                        )
+                    };
+                    let mut state = H;
+                    sha2::compress256(&mut state, msg);
+                    for (bytes, word) in hashes[0].chunks_exact_mut(4).zip(state) {
+                        bytes.copy_from_slice(&word.to_be_bytes());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Works out the part of the first preimage's hash that doesn't depend on the byte at `pos`, for
+    /// `hash_byte` to reuse while only that byte changes. CPUs with SHA instructions
+    /// compute the message schedule in hardware and have nothing to gain from this.
+    pub(crate) fn fix_all_but(&mut self, pos: usize) {
+        self.fixed.pos = pos;
+        if matches!(self.backend, Backend::ShaNi | Backend::ArmSha2) {
+            return;
+        }
+        let (block, word) = (pos / 64, pos % 64 / 4);
+        let mut state = H.map(|h| [h]);
+        for block in 0..block {
+            compress(
+                &mut state,
+                block_words(bytes(&self.msgs), block).map(|w| [w]),
+            );
+        }
+        let block_start = state.map(|[h]| h);
+
+        let mut w = [0; 64];
+        w[..16].copy_from_slice(&block_words(bytes(&self.msgs), block));
+        let mut varying = 1 << word;
+        for t in 16..64 {
+            if varying & (1 << (t - 16) | 1 << (t - 15) | 1 << (t - 7) | 1 << (t - 2)) != 0 {
+                varying |= 1 << t;
+            } else {
+                w[t] = schedule(&w, t);
+            }
+        }
+        let mut vars = state;
+        for t in 0..word {
+            round(&mut vars, [K[t].wrapping_add(w[t])]);
+        }
+        *self.fixed = Fixed {
+            pos,
+            block_start,
+            vars: vars.map(|[v]| v),
+            w,
+            varying,
+        };
+    }
+
+    /// Hashes the first preimage with `byte` at the position given to `fix_all_but`, only redoing
+    /// the rounds and schedule words that depend on it
+    #[inline(always)]
+    pub(crate) fn hash_byte(&mut self, byte: u8) {
+        let fixed = &*self.fixed;
+        let msg = bytes_mut(&mut self.msgs);
+        msg[fixed.pos] = byte;
+        if matches!(self.backend, Backend::ShaNi | Backend::ArmSha2) {
+            return self.hash(1);
+        }
+        let (block, word) = (fixed.pos / 64, fixed.pos % 64 / 4);
+        let at = block * 64 + word * 4;
+        let mut w = fixed.w;
+        w[word] = u32::from_be_bytes(msg[at..at + 4].try_into().unwrap());
+        for t in 16..64 {
+            if fixed.varying & (1 << t) != 0 {
+                w[t] = schedule(&w, t);
+            }
+        }
+        let mut vars = fixed.vars.map(|v| [v]);
+        for t in word..64 {
+            round(&mut vars, [K[t].wrapping_add(w[t])]);
+        }
+        let mut state = fixed.block_start.map(|h| [h]);
+        for (word, new) in state.iter_mut().zip(vars) {
+            *word = add(*word, new);
+        }
+
+        let message_blocks = padded_len(self.len) / 64 - usize::from(self.tail.is_some());
+        for block in block + 1..message_blocks {
+            compress(&mut state, block_words(msg, block).map(|w| [w]));
+        }
+        if let Some(tail) = &self.tail {
+            compress_scheduled(&mut state, tail);
+        }
+        for (bytes, [word]) in self.hashes[0].chunks_exact_mut(4).zip(state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn hashes(&self) -> &[[u8; 32]] {
+        &self.hashes
+    }
+}
+
+/// Length of a `len` byte message once padded: a 0x80 byte and the 8 byte bit length,
+/// rounded up to whole blocks
+pub(crate) fn padded_len(len: usize) -> usize {
+    (len + 9).div_ceil(64) * 64
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512vl")]
+unsafe fn hash_avx512(
+    msgs: &[u8],
+    blocks: usize,
+    tail: Option<&[u32; 64]>,
+    hashes: &mut [[u8; 32]; 16],
+) {
+    hash_lanes(msgs, blocks, tail, hashes)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn hash_avx2(
+    msgs: &[u8],
+    blocks: usize,
+    tail: Option<&[u32; 64]>,
+    hashes: &mut [[u8; 32]; 8],
+) {
+    hash_lanes(msgs, blocks, tail, hashes)
+}
+
+/// `hash_lanes` with the x86 SHA extensions. These do one preimage at a time, so lanes
+/// are only interleaved to hide the latency of the round instructions.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+unsafe fn hash_sha_ni<const L: usize>(
+    msgs: &[u8],
+    blocks: usize,
+    tail: Option<&[u32; 64]>,
+    hashes: &mut [[u8; 32]; L],
+) {
+    use core::arch::x86_64::*;
+
+    debug_assert_eq!(msgs.len(), L * blocks * 64);
+    // The instructions keep the state as ABEF and CDGH
+    let dcba = _mm_loadu_si128(H.as_ptr().cast());
+    let efgh = _mm_shuffle_epi32(_mm_loadu_si128(H.as_ptr().add(4).cast()), 0x1b);
+    let cdab = _mm_shuffle_epi32(dcba, 0xb1);
+    let mut abef = [_mm_alignr_epi8(cdab, efgh, 8); L];
+    let mut cdgh = [_mm_blend_epi16(efgh, cdab, 0xf0); L];
+
+    let swap = _mm_set_epi64x(0x0c0d_0e0f_0809_0a0b, 0x0405_0607_0001_0203);
+    let message_blocks = blocks - usize::from(tail.is_some());
+    for block in 0..message_blocks {
+        let (abef_start, cdgh_start) = (abef, cdgh);
+        // The message schedules, kept as rings of the last 16 words
+        let mut w = [[_mm_setzero_si128(); 4]; L];
+        for i in 0..16 {
+            let k = _mm_loadu_si128(K.as_ptr().add(i * 4).cast());
+            for l in 0..L {
+                let w = &mut w[l];
+                w[i % 4] = if i < 4 {
+                    let words = msgs.as_ptr().add((l * blocks + block) * 64 + i * 16);
+                    _mm_shuffle_epi8(_mm_loadu_si128(words.cast()), swap)
+                } else {
+                    let (w0, w1) = (w[i % 4], w[(i + 1) % 4]);
+                    let (w2, w3) = (w[(i + 2) % 4], w[(i + 3) % 4]);
+                    let w0 =
+                        _mm_add_epi32(_mm_sha256msg1_epu32(w0, w1), _mm_alignr_epi8(w3, w2, 4));
+                    _mm_sha256msg2_epu32(w0, w3)
+                };
+                sha_ni_rounds4(&mut abef[l], &mut cdgh[l], _mm_add_epi32(w[i % 4], k));
+            }
+        }
+        for l in 0..L {
+            abef[l] = _mm_add_epi32(abef[l], abef_start[l]);
+            cdgh[l] = _mm_add_epi32(cdgh[l], cdgh_start[l]);
+        }
+    }
+    if let Some(tail) = tail {
+        let (abef_start, cdgh_start) = (abef, cdgh);
+        for i in 0..16 {
+            let kw = _mm_loadu_si128(tail.as_ptr().add(i * 4).cast());
+            for l in 0..L {
+                sha_ni_rounds4(&mut abef[l], &mut cdgh[l], kw);
+            }
+        }
+        for l in 0..L {
+            abef[l] = _mm_add_epi32(abef[l], abef_start[l]);
+            cdgh[l] = _mm_add_epi32(cdgh[l], cdgh_start[l]);
+        }
+    }
+
+    for l in 0..L {
+        let feba = _mm_shuffle_epi32(abef[l], 0x1b);
+        let dchg = _mm_shuffle_epi32(cdgh[l], 0xb1);
+        let mut state = [0_u32; 8];
+        _mm_storeu_si128(state.as_mut_ptr().cast(), _mm_blend_epi16(feba, dchg, 0xf0));
+        _mm_storeu_si128(
+            state.as_mut_ptr().add(4).cast(),
+            _mm_alignr_epi8(dchg, feba, 8),
+        );
+        for (bytes, word) in hashes[l].chunks_exact_mut(4).zip(state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+    }
+}
+
+/// Four rounds, given their `K[t] + W[t]`
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sha,sse2")]
+#[inline]
+unsafe fn sha_ni_rounds4(
+    abef: &mut core::arch::x86_64::__m128i,
+    cdgh: &mut core::arch::x86_64::__m128i,
+    kw: core::arch::x86_64::__m128i,
+) {
+    use core::arch::x86_64::*;
+
+    // Each instruction does two rounds, with the low half of its last operand
+    *cdgh = _mm_sha256rnds2_epu32(*cdgh, *abef, kw);
+    *abef = _mm_sha256rnds2_epu32(*abef, *cdgh, _mm_shuffle_epi32(kw, 0x0e));
+}
+
+/// `hash_sha_ni` with the ARMv8 SHA2 instructions
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "sha2")]
+unsafe fn hash_arm_sha2<const L: usize>(
+    msgs: &[u8],
+    blocks: usize,
+    tail: Option<&[u32; 64]>,
+    hashes: &mut [[u8; 32]; L],
+) {
+    use core::arch::aarch64::*;
+
+    debug_assert_eq!(msgs.len(), L * blocks * 64);
+    let mut abcd = [vld1q_u32(H.as_ptr()); L];
+    let mut efgh = [vld1q_u32(H.as_ptr().add(4)); L];
+    let message_blocks = blocks - usize::from(tail.is_some());
+    for block in 0..message_blocks {
+        let (abcd_start, efgh_start) = (abcd, efgh);
+        // The message schedules, kept as rings of the last 16 words
+        let mut w = [[vdupq_n_u32(0); 4]; L];
+        for i in 0..16 {
+            let k = vld1q_u32(K.as_ptr().add(i * 4));
+            for l in 0..L {
+                let w = &mut w[l];
+                w[i % 4] = if i < 4 {
+                    let words = msgs.as_ptr().add((l * blocks + block) * 64 + i * 16);
+                    vreinterpretq_u32_u8(vrev32q_u8(vld1q_u8(words)))
+                } else {
+                    let w0 = vsha256su0q_u32(w[i % 4], w[(i + 1) % 4]);
+                    vsha256su1q_u32(w0, w[(i + 2) % 4], w[(i + 3) % 4])
+                };
+                arm_sha2_rounds4(&mut abcd[l], &mut efgh[l], vaddq_u32(w[i % 4], k));
+            }
+        }
+        for l in 0..L {
+            abcd[l] = vaddq_u32(abcd[l], abcd_start[l]);
+            efgh[l] = vaddq_u32(efgh[l], efgh_start[l]);
+        }
+    }
+    if let Some(tail) = tail {
+        let (abcd_start, efgh_start) = (abcd, efgh);
+        for i in 0..16 {
+            let kw = vld1q_u32(tail.as_ptr().add(i * 4));
+            for l in 0..L {
+                arm_sha2_rounds4(&mut abcd[l], &mut efgh[l], kw);
+            }
+        }
+        for l in 0..L {
+            abcd[l] = vaddq_u32(abcd[l], abcd_start[l]);
+            efgh[l] = vaddq_u32(efgh[l], efgh_start[l]);
+        }
+    }
+
+    for l in 0..L {
+        let mut state = [0_u32; 8];
+        vst1q_u32(state.as_mut_ptr(), abcd[l]);
+        vst1q_u32(state.as_mut_ptr().add(4), efgh[l]);
+        for (bytes, word) in hashes[l].chunks_exact_mut(4).zip(state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+    }
+}
+
+/// Four rounds, given their `K[t] + W[t]`
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "sha2")]
+#[inline]
+unsafe fn arm_sha2_rounds4(
+    abcd: &mut core::arch::aarch64::uint32x4_t,
+    efgh: &mut core::arch::aarch64::uint32x4_t,
+    kw: core::arch::aarch64::uint32x4_t,
+) {
+    use core::arch::aarch64::*;
+
+    let abcd_start = *abcd;
+    *abcd = vsha256hq_u32(abcd_start, *efgh, kw);
+    *efgh = vsha256h2q_u32(*efgh, abcd_start, kw);
+}
+
+/// One `u32` per lane
+type Words<const L: usize> = [u32; L];
+
+#[inline(always)]
+fn each<const L: usize>(f: impl Fn(usize) -> u32) -> Words<L> {
+    let mut words = [0; L];
+    for (lane, word) in words.iter_mut().enumerate() {
+        *word = f(lane);
+    }
+    words
+}
+
+#[inline(always)]
+fn add<const L: usize>(a: Words<L>, b: Words<L>) -> Words<L> {
+    each(|l| a[l].wrapping_add(b[l]))
+}
+
+#[inline(always)]
+fn hash_lanes<const L: usize>(
+    msgs: &[u8],
+    blocks: usize,
+    tail: Option<&[u32; 64]>,
+    hashes: &mut [[u8; 32]; L],
+) {
+    // Lets the bounds checks below go
+    assert_eq!(msgs.len(), L * blocks * 64);
+    let mut state = H.map(|h| [h; L]);
+    let message_blocks = blocks - usize::from(tail.is_some());
+    for block in 0..message_blocks {
+        let words: [Words<L>; 16] = core::array::from_fn(|i| {
+            each(|l| {
+                let at = (l * blocks + block) * 64 + i * 4;
+                u32::from_be_bytes(msgs[at..at + 4].try_into().unwrap())
+            })
+        });
+        compress(&mut state, words);
+    }
+    if let Some(tail) = tail {
+        compress_scheduled(&mut state, tail);
+    }
+    for (l, hash) in hashes.iter_mut().enumerate() {
+        for (i, word) in state.iter().enumerate() {
+            hash[i * 4..i * 4 + 4].copy_from_slice(&word[l].to_be_bytes());
+        }
+    }
+}
+
+/// The big-endian words of `msg`'s `block`th block
+#[inline(always)]
+fn block_words(msg: &[u8], block: usize) -> [u32; 16] {
+    core::array::from_fn(|i| {
+        let at = block * 64 + i * 4;
+        u32::from_be_bytes(msg[at..at + 4].try_into().unwrap())
+    })
+}
+
+/// Word `t` of the message schedule `w`
+#[inline(always)]
+fn schedule(w: &[u32; 64], t: usize) -> u32 {
+    w[t - 16]
+        .wrapping_add(small_sigma0(w[t - 15]))
+        .wrapping_add(w[t - 7])
+        .wrapping_add(small_sigma1(w[t - 2]))
+}
+
+#[inline(always)]
+fn small_sigma0(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+#[inline(always)]
+fn small_sigma1(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+#[inline(always)]
+fn compress<const L: usize>(state: &mut [Words<L>; 8], mut w: [Words<L>; 16]) {
+    let mut vars = *state;
+    for (t, k) in K.iter().enumerate() {
+        // The message schedule, kept as a ring of the last 16 words
+        if t >= 16 {
+            let (w15, w2) = (w[(t + 1) % 16], w[(t + 14) % 16]);
+            let s0 = each(|l| small_sigma0(w15[l]));
+            let s1 = each(|l| small_sigma1(w2[l]));
+            w[t % 16] = add(add(w[t % 16], s0), add(w[(t + 9) % 16], s1));
+        }
+        round(&mut vars, add(w[t % 16], [*k; L]));
+    }
+    for (word, new) in state.iter_mut().zip(vars) {
+        *word = add(*word, new);
+    }
+}
+
+/// `compress` for a block whose `K[t] + W[t]` are already known
+#[inline(always)]
+fn compress_scheduled<const L: usize>(state: &mut [Words<L>; 8], kw: &[u32; 64]) {
+    let mut vars = *state;
+    for kw in kw {
+        round(&mut vars, [*kw; L]);
+    }
+    for (word, new) in state.iter_mut().zip(vars) {
+        *word = add(*word, new);
+    }
+}
+
+#[inline(always)]
+fn round<const L: usize>(vars: &mut [Words<L>; 8], kw: Words<L>) {
+    let [a, b, c, d, e, f, g, h] = *vars;
+    let s1 = each(|l| e[l].rotate_right(6) ^ e[l].rotate_right(11) ^ e[l].rotate_right(25));
+    let ch = each(|l| (e[l] & f[l]) ^ (!e[l] & g[l]));
+    let t1 = add(add(h, s1), add(ch, kw));
+    let s0 = each(|l| a[l].rotate_right(2) ^ a[l].rotate_right(13) ^ a[l].rotate_right(22));
+    let maj = each(|l| (a[l] & b[l]) ^ (a[l] & c[l]) ^ (b[l] & c[l]));
+    *vars = [add(t1, add(s0, maj)), a, b, c, add(d, t1), e, f, g];
+}
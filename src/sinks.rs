@@ -0,0 +1,77 @@
+//! Where the results writer delivers matches: the `--output` file, `--db`, `--queue` and
+//! `--webhook` a grind's arguments ask for, and whatever sinks a `Grinder` adds, such as
+//! a `Channel` to another thread of the program embedding it.
+
+use std::{
+    io::{self, Write},
+    sync::mpsc::Sender,
+};
+
+use solana_pubkey::Pubkey;
+
+use crate::{
+    args::OutputFormat,
+    results::{write_record, Found, CSV_HEADER},
+};
+pub use crate::{results::ResultsFile as File, webhook::Webhook};
+
+/// Takes each match a grind finds, once each, on the results writer's thread. Sinks
+/// report their own errors, a grind goes on whatever becomes of its matches.
+pub trait ResultSink: Send {
+    fn add(&mut self, found: &Found);
+
+    /// Writes out anything `add` held on to, at least once a second
+    fn flush(&mut self) {}
+
+    /// Called once the grind is over, after a last `flush`
+    fn finish(self: Box<Self>) {}
+}
+
+/// Prints each match to stdout as a record of `format`, as it would go in the `--output`
+/// file
+pub struct Stdout {
+    format: OutputFormat,
+    owner: Pubkey,
+}
+
+impl Stdout {
+    /// Prints the header row first for `OutputFormat::Csv`
+    pub fn new(format: OutputFormat, owner: Pubkey) -> Stdout {
+        if format == OutputFormat::Csv {
+            print!("{CSV_HEADER}");
+        }
+        Stdout { format, owner }
+    }
+}
+
+impl ResultSink for Stdout {
+    fn add(&mut self, found: &Found) {
+        let mut stdout = io::stdout().lock();
+        if let Err(e) = write_record(&mut stdout, found, self.format, &self.owner) {
+            eprintln!("failed to print {}: {e}", found.key);
+        }
+    }
+
+    fn flush(&mut self) {
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Sends each match down a channel, for another thread to take it from there. Matches
+/// the receiver isn't around for any more are dropped.
+pub struct Channel(pub Sender<Found>);
+
+impl ResultSink for Channel {
+    fn add(&mut self, found: &Found) {
+        let _ = self.0.send(found.clone());
+    }
+}
+
+/// A `Grinder::run` callback
+pub(crate) struct Callback<F>(pub(crate) F);
+
+impl<F: FnMut(&Found) + Send> ResultSink for Callback<F> {
+    fn add(&mut self, found: &Found) {
+        (self.0)(found)
+    }
+}
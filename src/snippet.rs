@@ -0,0 +1,168 @@
+//! The code that derives a match, printed for each one that `--emit` asks for, to paste
+//! into a program or its client as it is.
+
+use solana_pubkey::Pubkey;
+
+use crate::{
+    args::Emit,
+    seeds::{Endianness, SeedComponent, SeedKind, SeedTemplate},
+};
+
+/// The derivation of the match of `seed_bytes` and `bump`, in `emit`'s language
+pub fn snippet(
+    emit: Emit,
+    template: &SeedTemplate,
+    seed_kind: SeedKind,
+    seed_bytes: &[u8],
+    bump: u8,
+    owner: &Pubkey,
+) -> String {
+    match emit {
+        Emit::Rust => rust(template, seed_kind, seed_bytes, bump, owner),
+        Emit::Ts => ts(template, seed_kind, seed_bytes, bump, owner),
+    }
+}
+
+/// An Anchor `seeds` and `bump` constraint, and the `Pubkey::create_program_address`
+/// call that gives the same address
+fn rust(
+    template: &SeedTemplate,
+    seed_kind: SeedKind,
+    seed_bytes: &[u8],
+    bump: u8,
+    owner: &Pubkey,
+) -> String {
+    let seeds: Vec<String> = template
+        .0
+        .iter()
+        .map(|component| match component {
+            SeedComponent::Fixed(bytes) => rust_bytes(bytes),
+            SeedComponent::Pubkey(key) => format!("pubkey!(\"{key}\").as_ref()"),
+            SeedComponent::Ground(_) => rust_ground(seed_kind, seed_bytes),
+        })
+        .collect();
+    let seeds = seeds.join(", ");
+    format!(
+        "seeds = [{seeds}], bump = {bump}\n\
+         Pubkey::create_program_address(&[{seeds}, &[{bump}]], &pubkey!(\"{owner}\"))"
+    )
+}
+
+/// The ground seed as Rust, as the integer or key it was ground as where it was one
+fn rust_ground(seed_kind: SeedKind, seed_bytes: &[u8]) -> String {
+    let endianness = match seed_kind {
+        SeedKind::Int(endianness) => endianness,
+        SeedKind::Key => {
+            let key = Pubkey::new_from_array(seed_bytes.try_into().unwrap());
+            return format!("pubkey!(\"{key}\").as_ref()");
+        }
+        SeedKind::Bytes | SeedKind::Str => return rust_bytes(seed_bytes),
+    };
+    let int_type = match seed_bytes.len() {
+        2 => "u16",
+        4 => "u32",
+        8 => "u64",
+        _ => "u128",
+    };
+    let mut bytes = [0; 16];
+    match endianness {
+        Endianness::Le => {
+            bytes[..seed_bytes.len()].copy_from_slice(seed_bytes);
+            format!("&{}{int_type}.to_le_bytes()", u128::from_le_bytes(bytes))
+        }
+        Endianness::Be => {
+            bytes[16 - seed_bytes.len()..].copy_from_slice(seed_bytes);
+            format!("&{}{int_type}.to_be_bytes()", u128::from_be_bytes(bytes))
+        }
+    }
+}
+
+/// `bytes` as a byte string literal if they are printable ASCII, or else as an array
+fn rust_bytes(bytes: &[u8]) -> String {
+    if is_printable(bytes) {
+        let escaped: String = bytes
+            .iter()
+            .flat_map(|b| std::ascii::escape_default(*b))
+            .map(char::from)
+            .collect();
+        format!("b\"{escaped}\"")
+    } else {
+        let bytes: Vec<String> = bytes.iter().map(|b| format!("{b:#04x}")).collect();
+        format!("&[{}]", bytes.join(", "))
+    }
+}
+
+/// A `PublicKey.findProgramAddressSync` call, which gives the match back as the bump is
+/// always the canonical one, with integers written with `BN` in the endianness and width
+/// they were ground in
+fn ts(
+    template: &SeedTemplate,
+    seed_kind: SeedKind,
+    seed_bytes: &[u8],
+    bump: u8,
+    owner: &Pubkey,
+) -> String {
+    let seeds: Vec<String> = template
+        .0
+        .iter()
+        .map(|component| match component {
+            SeedComponent::Fixed(bytes) => ts_bytes(bytes),
+            SeedComponent::Pubkey(key) => format!("new PublicKey(\"{key}\").toBuffer()"),
+            SeedComponent::Ground(_) => ts_ground(seed_kind, seed_bytes),
+        })
+        .collect();
+    format!(
+        "const [pda, bump] = PublicKey.findProgramAddressSync([{}], new PublicKey(\"{owner}\")); \
+         // bump {bump}",
+        seeds.join(", ")
+    )
+}
+
+/// The ground seed as TypeScript, as the integer or key it was ground as where it was one
+fn ts_ground(seed_kind: SeedKind, seed_bytes: &[u8]) -> String {
+    let endianness = match seed_kind {
+        SeedKind::Int(endianness) => endianness,
+        SeedKind::Key => {
+            let key = Pubkey::new_from_array(seed_bytes.try_into().unwrap());
+            return format!("new PublicKey(\"{key}\").toBuffer()");
+        }
+        SeedKind::Bytes | SeedKind::Str => return ts_bytes(seed_bytes),
+    };
+    let mut bytes = [0; 16];
+    let (seed, endianness) = match endianness {
+        Endianness::Le => {
+            bytes[..seed_bytes.len()].copy_from_slice(seed_bytes);
+            (u128::from_le_bytes(bytes), "le")
+        }
+        Endianness::Be => {
+            bytes[16 - seed_bytes.len()..].copy_from_slice(seed_bytes);
+            (u128::from_be_bytes(bytes), "be")
+        }
+    };
+    // Past 2^53 a JavaScript number can't hold every integer, so `BN` parses a string
+    let seed = match seed < 1 << 53 {
+        true => seed.to_string(),
+        false => format!("\"{seed}\""),
+    };
+    format!(
+        "new BN({seed}).toArrayLike(Buffer, \"{endianness}\", {})",
+        seed_bytes.len()
+    )
+}
+
+/// `bytes` as a `Buffer` of a string if they are printable ASCII, or else of an array
+fn ts_bytes(bytes: &[u8]) -> String {
+    if is_printable(bytes) {
+        let string = String::from_utf8_lossy(bytes);
+        format!("Buffer.from({})", serde_json::json!(string))
+    } else {
+        let bytes: Vec<String> = bytes.iter().map(|b| format!("{b:#04x}")).collect();
+        format!("Buffer.from([{}])", bytes.join(", "))
+    }
+}
+
+/// Whether `bytes` can be written as a string literal without escapes for control
+/// characters or non-ASCII bytes
+fn is_printable(bytes: &[u8]) -> bool {
+    bytes.iter().all(|b| b.is_ascii_graphic() || *b == b' ')
+}
@@ -0,0 +1,61 @@
+//! Grinding from async code (`--features tokio`): the grind runs on threads of its own as
+//! ever, and its matches come out of a tokio stream.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
+use tracing::error;
+
+use crate::{args::GrindArgs, results::Found, sinks::ResultSink, CancellationToken, Grinder};
+
+impl ResultSink for UnboundedSender<Found> {
+    fn add(&mut self, found: &Found) {
+        // Nobody is listening once the stream is dropped
+        let _ = self.send(found.clone());
+    }
+}
+
+/// The matches `grind_stream` returns, stopping the grind when dropped
+struct Matches {
+    receiver: UnboundedReceiverStream<Found>,
+    cancel: CancellationToken,
+}
+
+impl Stream for Matches {
+    type Item = Found;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Found>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Drop for Matches {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+/// Starts grinding for `args` on a thread of its own, returning a stream of the matches
+/// (once each, as they are written out) that ends when the grind stops. Dropping the
+/// stream stops the grind too, as its `CancellationToken` would. If the grind can't run,
+/// the stream ends without a match and the error is logged.
+pub fn grind_stream(args: GrindArgs) -> impl Stream<Item = Found> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let cancel = CancellationToken::new();
+    let grinder = Grinder::new(args)
+        .sink(sender)
+        .cancellation_token(cancel.clone());
+    std::thread::spawn(move || {
+        if let Err(e) = grinder.run(|_| {}) {
+            error!("{e}");
+        }
+    });
+    Matches {
+        receiver: UnboundedReceiverStream::new(receiver),
+        cancel,
+    }
+}
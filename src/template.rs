@@ -0,0 +1,128 @@
+//! Built-in `--template`s for the accounts well-known programs derive, so that their
+//! seeds (and the program they belong to) don't have to be laid out by hand with
+//! `--owner` and `--seeds`. What varies in those seeds is a key, e.g. a token account's
+//! mint, and an address nobody holds the secret key of is no use there, so a template
+//! grinds over new keypairs and saves the keypair of each match.
+
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use curve25519_dalek::EdwardsPoint;
+use sha2::{Digest, Sha512};
+use solana_pubkey::{pubkey, Pubkey};
+use tracing::warn;
+
+use crate::{
+    args::{GrindArgs, Template},
+    seeds::{SeedComponent, SeedTemplate},
+};
+
+/// Derives every associated token account, from `[wallet, token program, mint]`
+pub(crate) const ASSOCIATED_TOKEN_PROGRAM: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// The SPL token program, which `--token-program` defaults to
+pub(crate) const TOKEN_PROGRAM: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Metaplex's token metadata program, which derives a mint's metadata account from
+/// `["metadata", program, mint]`
+pub(crate) const TOKEN_METADATA_PROGRAM: Pubkey =
+    pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Sets `args`' owner and seeds to the ones its `--template` derives from, and returns
+/// what the ground key is, e.g. `mint`
+pub(crate) fn apply(args: &mut GrindArgs) -> Result<&'static str, String> {
+    let Some(template) = args.template else {
+        unreachable!("only called for a --template");
+    };
+    let ground = SeedComponent::Ground(None);
+    let (owner, seeds, key) = match template {
+        Template::Ata => {
+            let token_program = SeedComponent::Pubkey(args.token_program.unwrap_or(TOKEN_PROGRAM));
+            match (args.wallet, args.mint) {
+                (Some(wallet), None) if args.mint_grind => (
+                    ASSOCIATED_TOKEN_PROGRAM,
+                    vec![SeedComponent::Pubkey(wallet), token_program, ground],
+                    "mint",
+                ),
+                (None, Some(mint)) if args.wallet_grind => (
+                    ASSOCIATED_TOKEN_PROGRAM,
+                    vec![ground, token_program, SeedComponent::Pubkey(mint)],
+                    "wallet",
+                ),
+                _ => {
+                    return Err("--template ata grinds mints for a --wallet (--mint-grind) \
+                                or wallets for a --mint (--wallet-grind)"
+                        .to_string())
+                }
+            }
+        }
+        Template::MetaplexMetadata => {
+            if args.wallet.is_some()
+                || args.mint.is_some()
+                || args.wallet_grind
+                || args.token_program.is_some()
+            {
+                return Err(
+                    "--template metaplex-metadata only grinds mints, and takes no \
+                            --wallet, --mint or --token-program"
+                        .to_string(),
+                );
+            }
+            (
+                TOKEN_METADATA_PROGRAM,
+                vec![
+                    SeedComponent::Fixed(b"metadata".to_vec()),
+                    SeedComponent::Pubkey(TOKEN_METADATA_PROGRAM),
+                    ground,
+                ],
+                "mint",
+            )
+        }
+    };
+    // Most likely from a `grinder.toml` or `PDA_GRINDER_OWNER` set for other grinds
+    if args.owner != Pubkey::default() && args.owner != owner {
+        warn!(
+            "--template grinds for program {owner}, not --owner {}",
+            args.owner
+        );
+    }
+    args.owner = owner;
+    args.seeds = Some(SeedTemplate(seeds));
+    Ok(key)
+}
+
+/// A new keypair's secret key (the 32-byte seed `solana-keygen` keeps) and its public key
+pub(crate) fn new_keypair() -> ([u8; 32], [u8; 32]) {
+    let secret: [u8; 32] = rand::random();
+    (secret, public_key(&secret))
+}
+
+/// The ed25519 public key of `secret`: the base point times its clamped SHA-512
+pub(crate) fn public_key(secret: &[u8; 32]) -> [u8; 32] {
+    let hash = Sha512::digest(secret);
+    EdwardsPoint::mul_base_clamped(hash[..32].try_into().unwrap())
+        .compress()
+        .to_bytes()
+}
+
+/// Writes the keypair of `secret` to `<public key>.json` in `dir`, as the JSON array of
+/// its 64 bytes that `solana-keygen` writes (and the CLIs read), only readable by its
+/// owner on unix
+pub(crate) fn save_keypair(dir: &Path, secret: &[u8; 32]) -> Result<PathBuf, String> {
+    let public = public_key(secret);
+    let path = dir.join(format!("{}.json", Pubkey::new_from_array(public)));
+    let bytes: Vec<u8> = secret.iter().chain(&public).copied().collect();
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    std::fs::create_dir_all(dir)
+        .and_then(|()| options.open(&path))
+        .and_then(|mut file| write!(file, "{}", serde_json::json!(bytes)))
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    Ok(path)
+}
@@ -0,0 +1,323 @@
+//! The `--tui` dashboard, drawn in place of the stats lines (and the matches) while a grind
+//! runs: the hashrate over the last ten minutes, each device's rate, the matches so far
+//! and how long the next one should take. The stats thread draws it every tick, and it
+//! reads the keyboard then, so there is no thread of its own.
+//!
+//! It needs a build with `--features tui`; without it, only `--tui` fails.
+
+use std::time::Duration;
+
+/// What the grind is up to, as of a tick
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub(crate) struct Snapshot {
+    pub(crate) searched: u64,
+    pub(crate) elapsed: Duration,
+    pub(crate) matches: u64,
+    pub(crate) partial: u64,
+    /// The chance a seed gives a match, if it can be told
+    pub(crate) seed_match_rate: Option<f64>,
+    /// The share of the seed range searched, for a grind of a range
+    pub(crate) range_searched: Option<f64>,
+    pub(crate) paused: bool,
+    /// Seeds each CPU thread and then each GPU has searched, with its name
+    pub(crate) devices: Vec<(String, u64)>,
+}
+
+/// A key pressed on the dashboard
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub(crate) enum Command {
+    Stop,
+    TogglePause,
+}
+
+#[cfg(feature = "tui")]
+pub(crate) use dashboard::Dashboard;
+
+/// A `--tui` there can't be in a build without ratatui
+#[cfg(not(feature = "tui"))]
+pub(crate) enum Dashboard {}
+
+#[cfg(not(feature = "tui"))]
+impl Dashboard {
+    pub(crate) fn new(_: String) -> Result<Dashboard, String> {
+        Err("this build can't show --tui, rebuild it with `--features tui`".to_string())
+    }
+
+    pub(crate) fn add(&mut self, _: &crate::results::Found) {
+        match *self {}
+    }
+
+    pub(crate) fn draw(&mut self, _: &Snapshot) -> Option<Command> {
+        match *self {}
+    }
+
+    pub(crate) fn finish(self) {
+        match self {}
+    }
+}
+
+#[cfg(feature = "tui")]
+mod dashboard {
+    use std::{
+        collections::VecDeque,
+        io::IsTerminal,
+        time::{Duration, Instant},
+    };
+
+    use ratatui::{
+        crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+        layout::{Constraint, Layout},
+        style::{Color, Style, Stylize},
+        text::Line,
+        widgets::{Bar, BarChart, BarGroup, Block, List, ListItem, Paragraph, Sparkline},
+        DefaultTerminal, Frame,
+    };
+
+    use super::{Command, Snapshot};
+    use crate::{grind::format_eta, results::Found};
+
+    /// Hashrate samples kept for the graph, one a second
+    const HISTORY: usize = 600;
+
+    /// Matches kept for the log
+    const LOG: usize = 100;
+
+    pub(crate) struct Dashboard {
+        /// What is being ground for, as the title
+        title: String,
+        /// Set up on the first draw, so that nothing printed before it garbles it
+        terminal: Option<DefaultTerminal>,
+        /// Seeds searched a second, oldest first
+        hashrates: Vec<u64>,
+        /// Each device's seeds a second, as of the last sample
+        device_rates: Vec<u64>,
+        /// When the last sample was taken, with the seeds each device had searched by then
+        last_sample: Option<(Instant, Vec<u64>)>,
+        /// Newest first
+        log: VecDeque<Found>,
+    }
+
+    impl Dashboard {
+        pub(crate) fn new(title: String) -> Result<Dashboard, String> {
+            if !std::io::stdout().is_terminal() {
+                return Err("--tui needs stdout to be a terminal".to_string());
+            }
+            Ok(Dashboard {
+                title,
+                terminal: None,
+                hashrates: vec![],
+                device_rates: vec![],
+                last_sample: None,
+                log: VecDeque::new(),
+            })
+        }
+
+        /// Logs a match
+        pub(crate) fn add(&mut self, found: &Found) {
+            if self.log.len() == LOG {
+                self.log.pop_back();
+            }
+            self.log.push_front(found.clone());
+        }
+
+        /// Draws the dashboard as of `snapshot`, and returns what was asked for at the
+        /// keyboard since the last draw, if anything
+        pub(crate) fn draw(&mut self, snapshot: &Snapshot) -> Option<Command> {
+            self.sample(snapshot);
+            let terminal = self.terminal.get_or_insert_with(ratatui::init);
+            let (title, hashrates, device_rates, log) =
+                (&self.title, &self.hashrates, &self.device_rates, &self.log);
+            if let Err(e) =
+                terminal.draw(|frame| render(frame, title, snapshot, hashrates, device_rates, log))
+            {
+                eprintln!("failed to draw the dashboard: {e}");
+            }
+
+            let mut command = None;
+            while event::poll(Duration::ZERO).unwrap_or(false) {
+                let Ok(Event::Key(key)) = event::read() else {
+                    continue;
+                };
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                command = match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => Some(Command::Stop),
+                    // The terminal is raw, so Ctrl-C is a key rather than a signal
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        Some(Command::Stop)
+                    }
+                    KeyCode::Char('p') => Some(Command::TogglePause),
+                    _ => command,
+                };
+            }
+            command
+        }
+
+        /// Puts the terminal back as it was, for the summary to be printed
+        pub(crate) fn finish(self) {
+            if self.terminal.is_some() {
+                ratatui::restore();
+            }
+        }
+
+        /// Samples the hashrates once a second
+        fn sample(&mut self, snapshot: &Snapshot) {
+            let counts: Vec<_> = snapshot.devices.iter().map(|(_, count)| *count).collect();
+            let Some((time, last_counts)) = &self.last_sample else {
+                self.device_rates = vec![0; counts.len()];
+                self.last_sample = Some((Instant::now(), counts));
+                return;
+            };
+            let secs = time.elapsed().as_secs_f64();
+            if secs < 1.0 {
+                return;
+            }
+            self.device_rates = counts
+                .iter()
+                .zip(last_counts)
+                .map(|(now, before)| ((now - before) as f64 / secs) as u64)
+                .collect();
+            if self.hashrates.len() == HISTORY {
+                self.hashrates.remove(0);
+            }
+            self.hashrates.push(self.device_rates.iter().sum());
+            self.last_sample = Some((Instant::now(), counts));
+        }
+    }
+
+    fn render(
+        frame: &mut Frame,
+        title: &str,
+        snapshot: &Snapshot,
+        hashrates: &[u64],
+        device_rates: &[u64],
+        log: &VecDeque<Found>,
+    ) {
+        let [header, graph, devices, matches, footer] = Layout::vertical([
+            Constraint::Length(6),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Min(4),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let elapsed = snapshot.elapsed.as_secs_f64();
+        let average = snapshot.searched as f64 / elapsed.max(f64::EPSILON);
+        // Until the first sample, a second in
+        let rate = hashrates.last().map_or(average, |rate| *rate as f64);
+        let mut lines = vec![Line::from(format!(
+            "elapsed {}   searched {}   {}/s now, {}/s average",
+            format_elapsed(snapshot.elapsed),
+            si(snapshot.searched as f64),
+            si(rate),
+            si(average),
+        ))];
+        lines.push(Line::from(match snapshot.seed_match_rate {
+            Some(match_rate) if rate > 0.0 => format!(
+                "matches {} (expected ~{:.1})   partial {}   one every ~{} at this rate",
+                snapshot.matches,
+                snapshot.searched as f64 * match_rate,
+                snapshot.partial,
+                format_eta(1.0 / (match_rate * rate))
+            ),
+            _ => format!(
+                "matches {}   partial {}",
+                snapshot.matches, snapshot.partial
+            ),
+        }));
+        if let Some(searched) = snapshot.range_searched {
+            let share_rate = average / snapshot.searched.max(1) as f64 * searched;
+            lines.push(Line::from(range_line(searched, share_rate)));
+        }
+        if snapshot.paused {
+            lines.push(Line::from("paused".yellow().bold()));
+        }
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(format!(" {title} "))),
+            header,
+        );
+
+        let peak = hashrates.iter().max().copied().unwrap_or_default();
+        frame.render_widget(
+            Sparkline::default()
+                .block(Block::bordered().title(format!(
+                    " hashrate, last {} (peak {}/s) ",
+                    format_elapsed(Duration::from_secs(hashrates.len() as u64)),
+                    si(peak as f64)
+                )))
+                .data(&hashrates[hashrates.len().saturating_sub(graph.width as usize - 2)..])
+                .style(Style::default().fg(Color::Cyan)),
+            graph,
+        );
+
+        let bars: Vec<_> = snapshot
+            .devices
+            .iter()
+            .zip(device_rates)
+            .map(|((name, _), rate)| {
+                Bar::default()
+                    .value(*rate)
+                    .label(Line::from(name.as_str()))
+                    .text_value(si(*rate as f64))
+            })
+            .collect();
+        let width = (devices.width.saturating_sub(2) / bars.len().max(1) as u16)
+            .saturating_sub(1)
+            .clamp(1, 8);
+        frame.render_widget(
+            BarChart::default()
+                .block(Block::bordered().title(" seeds/s per device "))
+                .data(BarGroup::default().bars(&bars))
+                .bar_width(width)
+                .bar_gap(1)
+                .bar_style(Style::default().fg(Color::Green)),
+            devices,
+        );
+
+        let items: Vec<_> = log
+            .iter()
+            .map(|found| {
+                ListItem::new(format!(
+                    "{}  seed {}  bump {}",
+                    found.key, found.seed, found.bump
+                ))
+            })
+            .collect();
+        frame.render_widget(
+            List::new(items).block(Block::bordered().title(" matches, newest first ")),
+            matches,
+        );
+
+        frame.render_widget(Line::from(" q stop   p pause/resume".dim()), footer);
+    }
+
+    /// E.g. `1h02m03s`
+    fn format_elapsed(elapsed: Duration) -> String {
+        let secs = elapsed.as_secs();
+        match (secs / 3600, secs / 60 % 60, secs % 60) {
+            (0, 0, s) => format!("{s}s"),
+            (0, m, s) => format!("{m}m{s:02}s"),
+            (h, m, s) => format!("{h}h{m:02}m{s:02}s"),
+        }
+    }
+
+    /// `count` with a k, M, G or T suffix past a thousand, e.g. `12.3M`
+    fn si(count: f64) -> String {
+        const PREFIXES: [(&str, f64); 4] = [("T", 1e12), ("G", 1e9), ("M", 1e6), ("k", 1e3)];
+        match PREFIXES.iter().find(|(_, size)| count >= *size) {
+            Some((prefix, size)) => format!("{:.1}{prefix}", count / size),
+            None => format!("{count:.0}"),
+        }
+    }
+
+    /// A seed range's share searched, and how long the rest will take at `rate`
+    fn range_line(searched: f64, rate: f64) -> String {
+        let eta = match rate > 0.0 && searched > 0.0 {
+            true => format!(", done in ~{}", format_eta((1.0 - searched) / rate)),
+            false => String::new(),
+        };
+        format!("{:.2}% of the seed range searched{eta}", searched * 100.0)
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    sync::{atomic::AtomicBool, Arc},
+    time::Instant,
+};
+
+use clap::{Parser, ValueEnum};
+
+use crate::{
+    args::{BumpMode, CheckOrder, GrindArgs, TuneArgs},
+    bench::TARGET,
+    curve,
+    grind::{install_handlers, run, CancellationToken, Grinder},
+    sha256::Backend,
+};
+
+/// `--batch-size`s tried, besides the default
+const BATCH_SIZES: [u32; 4] = [64, 256, 1024, 4096];
+
+/// Most `--lookahead` tried. Each bump past the first finds half as many more seeds as the
+/// one before it for as much hashing, so the fastest is nearly always 1 or 2.
+const MAX_TUNED_LOOKAHEAD: u8 = 4;
+
+/// The settings `tune` tries
+#[derive(Clone, Copy)]
+struct Settings {
+    hasher: Backend,
+    batch_size: Option<u32>,
+    lookahead: u8,
+    check_order: CheckOrder,
+}
+
+impl Settings {
+    fn describe(&self, bump_mode: BumpMode) -> String {
+        let batch_size = self
+            .batch_size
+            .map_or("default".to_string(), |size| size.to_string());
+        let mut description = format!("hasher {}, batch size {batch_size}", self.hasher.name());
+        if bump_mode == BumpMode::First {
+            let check_order = self.check_order.to_possible_value().unwrap();
+            description += &format!(
+                ", lookahead {}, check order {}",
+                self.lookahead,
+                check_order.get_name()
+            );
+        }
+        description
+    }
+}
+
+/// Grinds for `--trial` with each of a few settings in turn, keeping the fastest of each
+/// before moving on to the next, and writes the fastest of all to `--config`
+pub fn tune(args: TuneArgs) {
+    let first = args.bump_mode == BumpMode::First;
+    // `--bump-mode any` only hashes and curve checks a batch at a time on CPUs that curve
+    // check in batches
+    let batched = first || curve::batched();
+    println!(
+        "tuning --bump-mode {} on {} threads, {}s per trial",
+        args.bump_mode.to_possible_value().unwrap().get_name(),
+        args.threads,
+        args.trial.as_secs_f64()
+    );
+
+    let defaults = Settings {
+        hasher: Backend::detect(batched),
+        batch_size: None,
+        lookahead: 1,
+        check_order: CheckOrder::Auto,
+    };
+    let hashers = Backend::value_variants()
+        .iter()
+        .filter(|hasher| hasher.supported())
+        .map(|&hasher| Settings { hasher, ..defaults });
+    // One Ctrl-C stops whichever trial is running and skips the rest
+    let (cancel, paused) = (CancellationToken::new(), Arc::new(AtomicBool::new(false)));
+    install_handlers(cancel.clone(), paused.clone(), true);
+    let trials = Trials {
+        args: &args,
+        cancel,
+        paused,
+    };
+    let Some(mut best) = fastest(&trials, hashers) else {
+        return;
+    };
+    if batched {
+        let batch_sizes = [None]
+            .into_iter()
+            .chain(BATCH_SIZES.map(Some))
+            .map(|batch_size| Settings {
+                batch_size,
+                ..best.0
+            });
+        let Some(tuned) = fastest(&trials, batch_sizes) else {
+            return;
+        };
+        best = tuned;
+    }
+    if first {
+        let lookaheads = (1..=MAX_TUNED_LOOKAHEAD).map(|lookahead| Settings {
+            lookahead,
+            ..best.0
+        });
+        let Some(tuned) = fastest(&trials, lookaheads) else {
+            return;
+        };
+        let check_orders = CheckOrder::value_variants()
+            .iter()
+            .map(|&check_order| Settings {
+                check_order,
+                ..tuned.0
+            });
+        let Some(tuned) = fastest(&trials, check_orders) else {
+            return;
+        };
+        best = tuned;
+    }
+
+    let (settings, rate) = best;
+    println!(
+        "fastest: {} ({rate:.0} seeds/s{})",
+        settings.describe(args.bump_mode),
+        if first {
+            " at their canonical bump"
+        } else {
+            ""
+        },
+    );
+    if args.dry_run {
+        return;
+    }
+    if let Err(e) = write_config(&args, settings) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+    println!("wrote {}", args.config.display());
+}
+
+/// The trials `tune` runs, which Ctrl-C cancels (and SIGUSR1 pauses) together
+struct Trials<'a> {
+    args: &'a TuneArgs,
+    cancel: CancellationToken,
+    paused: Arc<AtomicBool>,
+}
+
+/// Grinds with each of `candidates` and returns the fastest along with how many seeds it
+/// searched at their canonical bump a second, or `None` if Ctrl-C cut the trials short
+fn fastest(trials: &Trials, candidates: impl Iterator<Item = Settings>) -> Option<(Settings, f64)> {
+    let args = trials.args;
+    let mut fastest: Option<(Settings, f64)> = None;
+    for settings in candidates {
+        let searched = trial(trials, settings);
+        if trials.cancel.is_cancelled() {
+            return None;
+        }
+        // `--bump-mode first` only finds the seeds whose canonical bump is among the ones
+        // it matches, the rest are searched for nothing
+        let (rate, found) = match args.bump_mode {
+            BumpMode::First => {
+                let rate = searched * (1.0 - 0.5_f64.powi(settings.lookahead as i32));
+                (rate, format!(", {rate:.0} at their canonical bump"))
+            }
+            BumpMode::Any => (searched, String::new()),
+        };
+        println!(
+            "{}: {searched:.0} seeds/s{found}",
+            settings.describe(args.bump_mode)
+        );
+        if fastest.is_none_or(|(_, best)| rate > best) {
+            fastest = Some((settings, rate));
+        }
+    }
+    fastest
+}
+
+/// Grinds with `settings` for `--trial` and returns how many seeds it searched a second
+fn trial(trials: &Trials, settings: Settings) -> f64 {
+    let args = trials.args;
+    let mut grind_args = GrindArgs::parse_from([
+        "grind",
+        "--owner",
+        "11111111111111111111111111111111",
+        "--target",
+        TARGET,
+    ]);
+    // As in `bench`, everything that matters is set here rather than parsed. Without a
+    // target or suffix to tune for, trials never stop for a match.
+    if args.target.is_some() || args.suffix.is_some() {
+        grind_args.target = args.target.clone();
+        grind_args.suffix = args.suffix.clone();
+    } else {
+        grind_args.suffix = None;
+    }
+    grind_args.no_file = true;
+    grind_args.no_manifest = true;
+    grind_args.quiet = true;
+    grind_args.threads = args.threads;
+    grind_args.duration = Some(args.trial);
+    grind_args.bump_mode = args.bump_mode;
+    grind_args.bump_depth = None;
+    grind_args.hasher = Some(settings.hasher);
+    grind_args.gpu = None;
+    grind_args.batch_size = settings.batch_size;
+    grind_args.lookahead = settings.lookahead;
+    grind_args.check_order = settings.check_order;
+
+    let start_time = Instant::now();
+    let grinder = Grinder::new(grind_args)
+        .cancellation_token(trials.cancel.clone())
+        .pause_flag(trials.paused.clone());
+    let threads = run(grinder).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    });
+    let elapsed = start_time.elapsed().as_secs_f64();
+    let searched: u64 = threads.iter().map(|(searched, _)| searched).sum();
+    searched as f64 / elapsed
+}
+
+/// Sets `settings` in `--config`, keeping everything else it sets
+fn write_config(args: &TuneArgs, settings: Settings) -> Result<(), String> {
+    let path = &args.config;
+    let mut config = match std::fs::read_to_string(path) {
+        Ok(contents) => contents
+            .parse::<toml::Table>()
+            .map_err(|e| format!("failed to parse {}: {e}", path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => toml::Table::new(),
+        Err(e) => return Err(format!("failed to read {}: {e}", path.display())),
+    };
+    let name = |value: Option<clap::builder::PossibleValue>| {
+        toml::Value::String(value.unwrap().get_name().to_string())
+    };
+    config.insert("bump_mode".into(), name(args.bump_mode.to_possible_value()));
+    config.insert("hasher".into(), name(settings.hasher.to_possible_value()));
+    match settings.batch_size {
+        Some(size) => config.insert("batch_size".into(), (size as i64).into()),
+        None => config.remove("batch_size"),
+    };
+    if args.bump_mode == BumpMode::First {
+        config.insert("lookahead".into(), (settings.lookahead as i64).into());
+        config.insert(
+            "check_order".into(),
+            name(settings.check_order.to_possible_value()),
+        );
+    }
+    std::fs::write(path, config.to_string())
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
@@ -0,0 +1,94 @@
+//! Reporting matches to a `--webhook` as they are found, from a thread of its own so that
+//! a slow or unreachable endpoint holds up nothing but the reports.
+
+use std::{
+    sync::mpsc::{self, Sender},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use solana_pubkey::Pubkey;
+use tracing::error;
+
+use crate::{results::Found, sinks::ResultSink};
+
+/// How many times a report is sent before giving up on it, waiting `RETRY_WAIT` longer
+/// each time
+const ATTEMPTS: u32 = 3;
+const RETRY_WAIT: Duration = Duration::from_secs(1);
+
+/// How long a report can take to be accepted
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reports each match as JSON to a URL
+pub struct Webhook {
+    sender: Sender<serde_json::Value>,
+    handle: JoinHandle<()>,
+    owner: Pubkey,
+    hostname: String,
+    /// When the grind started, for the hashrate a report gives
+    started: Instant,
+}
+
+impl Webhook {
+    /// Starts the thread that POSTs reports to `url`
+    pub fn spawn(url: String, owner: Pubkey, started: Instant) -> Self {
+        let (sender, receiver) = mpsc::channel::<serde_json::Value>();
+        let handle = std::thread::spawn(move || {
+            let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+            for report in receiver {
+                for attempt in 1..=ATTEMPTS {
+                    let sent = agent
+                        .post(&url)
+                        .set("Content-Type", "application/json")
+                        .send_string(&report.to_string());
+                    match sent {
+                        Ok(_) => break,
+                        Err(e) if attempt == ATTEMPTS => {
+                            error!("failed to report {} to --webhook: {e}", report["pubkey"]);
+                        }
+                        Err(_) => std::thread::sleep(RETRY_WAIT * attempt),
+                    }
+                }
+            }
+        });
+        Webhook {
+            sender,
+            handle,
+            owner,
+            hostname: hostname(),
+            started,
+        }
+    }
+}
+
+impl ResultSink for Webhook {
+    /// Queues a report of `found`: its `--format jsonl` record, with where it was found
+    /// and how fast that machine grinds
+    fn add(&mut self, found: &Found) {
+        let mut report = found.to_json(&self.owner);
+        report["hostname"] = self.hostname.clone().into();
+        report["hashrate"] =
+            (found.iterations as f64 / self.started.elapsed().as_secs_f64()).into();
+        // Only fails if the thread panicked, which `finish` reports
+        let _ = self.sender.send(report);
+    }
+
+    /// Waits for every report so far to be sent (or given up on)
+    fn finish(self: Box<Self>) {
+        drop(self.sender);
+        self.handle.join().unwrap();
+    }
+}
+
+/// This machine's name, for telling reports from different workers apart
+fn hostname() -> String {
+    ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
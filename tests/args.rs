@@ -0,0 +1,76 @@
+use std::time::UNIX_EPOCH;
+
+use clap::Parser;
+use pda_grinder::args::{BumpMode, GrindArgs};
+
+const OWNER: &str = "11111111111111111111111111111111";
+
+fn parse(flags: &[&str]) -> Result<GrindArgs, clap::Error> {
+    GrindArgs::try_parse_from(
+        ["grind", "--owner", OWNER, "--target", "A"]
+            .iter()
+            .chain(flags),
+    )
+}
+
+#[test]
+fn hex_seeds_must_be_hex_digits() {
+    let prefix = |hex| parse(&["--seed-prefix", hex]).map(|args| args.seed_prefix.unwrap().0);
+    assert_eq!(prefix("0x00ff").unwrap(), [0x00, 0xff]);
+    for hex in ["0xé0", "0x0é0", "0x+1", "0xzz", "0x0"] {
+        assert!(prefix(hex).is_err(), "{hex}");
+    }
+}
+
+#[test]
+fn durations_add_up_without_overflowing() {
+    let duration = |duration| parse(&["--duration", duration]).map(|args| args.duration.unwrap());
+    assert_eq!(duration("1h30m").unwrap().as_secs(), 90 * 60);
+    assert_eq!(duration("2d1s").unwrap().as_secs(), 2 * 24 * 60 * 60 + 1);
+    for duration in [
+        "",
+        "0s",
+        "90",
+        "1x",
+        "999999999999999999d",
+        "18446744073709551615s1s",
+    ] {
+        assert!(parse(&["--duration", duration]).is_err(), "{duration}");
+    }
+}
+
+#[test]
+fn canonical_is_another_name_for_bump_mode_first() {
+    let bump_mode = |mode| parse(&["--bump-mode", mode]).unwrap().bump_mode;
+    assert_eq!(bump_mode("first"), BumpMode::First);
+    assert_eq!(bump_mode("canonical"), BumpMode::First);
+    assert_eq!(bump_mode("any"), BumpMode::Any);
+}
+
+#[test]
+fn deadlines_are_real_utc_times() {
+    let deadline = |time| parse(&["--deadline", time]).map(|args| args.deadline.unwrap());
+    let secs = |time| {
+        deadline(time)
+            .unwrap()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    };
+    assert_eq!(secs("1970-01-01T00:00:00Z"), 0);
+    assert_eq!(secs("2025-01-31T18:00:00Z"), 1_738_346_400);
+    assert_eq!(secs("2024-02-29T00:00:00Z"), 1_709_164_800);
+    assert_eq!(secs("2000-02-29T12:30:15Z"), 951_827_415);
+    assert_eq!(secs("1738346400"), 1_738_346_400);
+    for time in [
+        "2025-02-29T00:00:00Z",
+        "2100-02-29T00:00:00Z",
+        "2025-04-31T00:00:00Z",
+        "2025-13-01T00:00:00Z",
+        "2025-01-00T00:00:00Z",
+        "2025-01-31T24:00:00Z",
+        "2025-01-31T18:00:00",
+    ] {
+        assert!(deadline(time).is_err(), "{time}");
+    }
+}
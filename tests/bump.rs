@@ -0,0 +1,85 @@
+use pda_grinder::{bump::canonical_bump_matches, curve::off_curve};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use sha2::{Digest, Sha256};
+use solana_pubkey::Pubkey;
+
+/// The addresses of `seed` at bumps 255, 254, ... 1, as `create_program_address` hashes
+/// them
+fn candidates(seed: &[u8], owner: &Pubkey) -> Vec<[u8; 32]> {
+    (1..=u8::MAX)
+        .rev()
+        .map(|bump| {
+            Sha256::new()
+                .chain_update(seed)
+                .chain_update([bump])
+                .chain_update(owner)
+                .chain_update(b"ProgramDerivedAddress")
+                .finalize()
+                .into()
+        })
+        .collect()
+}
+
+#[test]
+fn canonical_bump_matches_agrees_with_find_program_address() {
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    let mut below_window = 0;
+    for _ in 0..2_000 {
+        let owner = Pubkey::new_from_array(rng.gen());
+        let seed: [u8; 8] = rng.gen();
+        let (key, bump) = Pubkey::find_program_address(&[&seed], &owner);
+        let candidates = candidates(&seed, &owner);
+        assert_eq!(candidates[(u8::MAX - bump) as usize], key.to_bytes());
+
+        // Candidates that "match" at random, so the canonical one does about half the time
+        let matching: Vec<bool> = (0..candidates.len()).map(|_| rng.gen()).collect();
+        for window in [1, 2, 3, 4, 7, 8, 16, 64, 255] {
+            let in_window = u8::MAX - bump < window;
+            below_window += usize::from(!in_window);
+            let expected = (in_window && matching[(u8::MAX - bump) as usize]).then_some(bump);
+            for curve_first in [false, true] {
+                let mut asked = vec![0; window as usize];
+                let found = canonical_bump_matches(
+                    &candidates[..window as usize],
+                    curve_first,
+                    off_curve,
+                    |i| {
+                        asked[i] += 1;
+                        matching[i]
+                    },
+                );
+                assert_eq!(found, expected, "{owner} {seed:?} window {window}");
+                assert!(asked.iter().all(|&n| n <= 1));
+                if curve_first {
+                    assert_eq!(asked.iter().sum::<usize>(), usize::from(in_window));
+                }
+            }
+        }
+        // Everything matches: the canonical bump whenever it is in the window
+        for window in [1, 16, 255] {
+            let found =
+                canonical_bump_matches(&candidates[..window as usize], false, off_curve, |_| true);
+            assert_eq!(found, (u8::MAX - bump < window).then_some(bump));
+        }
+    }
+    // Seeds whose canonical bump is past the window came up too
+    assert!(below_window > 0);
+}
+
+#[test]
+fn nothing_matching_skips_the_curve_checks() {
+    let owner = Pubkey::new_unique();
+    let candidates = candidates(&7_u64.to_le_bytes(), &owner);
+    let mut curve_checks = 0;
+    let found = canonical_bump_matches(
+        &candidates[..16],
+        false,
+        |candidate| {
+            curve_checks += 1;
+            off_curve(candidate)
+        },
+        |_| false,
+    );
+    assert_eq!(found, None);
+    assert_eq!(curve_checks, 0);
+}
@@ -0,0 +1,24 @@
+use pda_grinder::SeedCandidates;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn seed_candidates_agree_with_find_program_address() {
+    let mut rng = StdRng::seed_from_u64(0xca2d);
+    for start in [rng.gen(), 0, u64::MAX - 1500] {
+        let owner = Pubkey::new_from_array(rng.gen());
+        let candidates: Vec<_> = SeedCandidates::new(owner, start).take(3000).collect();
+        // Past `u64::MAX` there are none
+        assert_eq!(
+            candidates.len(),
+            3000.min(((u64::MAX - start) as usize).saturating_add(1))
+        );
+        for (i, (seed, bump, key)) in candidates.into_iter().enumerate() {
+            assert_eq!(seed, start + i as u64);
+            assert_eq!(
+                (key, bump),
+                Pubkey::find_program_address(&[&seed.to_le_bytes()], &owner)
+            );
+        }
+    }
+}
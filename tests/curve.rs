@@ -0,0 +1,82 @@
+use pda_grinder::curve::{off_curve, off_curve_batch, LANES};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use solana_pubkey::Pubkey;
+
+/// `p = 2^255 - 19`, little endian
+const P: [u8; 32] = {
+    let mut p = [0xff; 32];
+    p[0] = 0xed;
+    p[31] = 0x7f;
+    p
+};
+
+fn on_curve(key: &[u8; 32]) -> bool {
+    Pubkey::new_from_array(*key).is_on_curve()
+}
+
+/// `p + k` as little endian bytes, for small `k`
+fn p_plus(k: i64) -> [u8; 32] {
+    let mut bytes = P;
+    let low = u64::from_le_bytes(bytes[..8].try_into().unwrap()).wrapping_add(k as u64);
+    bytes[..8].copy_from_slice(&low.to_le_bytes());
+    bytes
+}
+
+/// Candidates decompression is most likely to treat differently: small y (including ±1,
+/// where x is 0), y around and past `p`, the top of the 255 bits, each with and without
+/// the sign bit
+fn edge_cases() -> Vec<[u8; 32]> {
+    let mut keys = vec![];
+    for k in 0..64_u64 {
+        let mut key = [0; 32];
+        key[..8].copy_from_slice(&k.to_le_bytes());
+        keys.push(key);
+    }
+    for k in -64..=18 {
+        keys.push(p_plus(k));
+    }
+    keys.push([0xff; 32]);
+    let sign_flipped: Vec<_> = keys
+        .iter()
+        .map(|key| {
+            let mut key = *key;
+            key[31] ^= 0x80;
+            key
+        })
+        .collect();
+    keys.extend(sign_flipped);
+    keys
+}
+
+fn random_keys(count: usize) -> Vec<[u8; 32]> {
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    (0..count).map(|_| rng.gen()).collect()
+}
+
+#[test]
+fn off_curve_agrees_with_decompression() {
+    for key in edge_cases().iter().chain(&random_keys(200_000)) {
+        assert_eq!(off_curve(key), !on_curve(key), "{key:?}");
+    }
+}
+
+#[test]
+fn off_curve_batch_agrees_with_decompression() {
+    let keys: Vec<_> = edge_cases()
+        .into_iter()
+        .chain(random_keys(20_000))
+        .collect();
+    // Every length up to a few groups, for the padded remainder
+    for len in 0..=3 * LANES + 1 {
+        let mut out = vec![false; len];
+        off_curve_batch(&keys[..len], &mut out);
+        for (key, off_curve) in keys.iter().zip(out) {
+            assert_eq!(off_curve, !on_curve(key), "{key:?}");
+        }
+    }
+    let mut out = vec![false; keys.len()];
+    off_curve_batch(&keys, &mut out);
+    for (key, off_curve) in keys.iter().zip(out) {
+        assert_eq!(off_curve, !on_curve(key), "{key:?}");
+    }
+}
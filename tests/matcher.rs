@@ -0,0 +1,138 @@
+use clap::Parser;
+use pda_grinder::{
+    args::GrindArgs,
+    matcher::{raw_prefix_matches, raw_suffix_may_match},
+    Grinder,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Random keys, a share of them with leading zero bytes (leading '1's) or leading 0xff
+/// bytes (the longest encodings), and the keys at either end
+fn keys(rng: &mut StdRng, count: usize) -> Vec<[u8; 32]> {
+    let mut keys = vec![[0; 32], [0xff; 32]];
+    let mut one = [0; 32];
+    one[31] = 1;
+    keys.push(one);
+    for _ in 0..count {
+        let mut key: [u8; 32] = rng.gen();
+        match rng.gen_range(0..4) {
+            0 => key[..rng.gen_range(1..=32)].fill(0),
+            1 => key[..rng.gen_range(1..=32)].fill(0xff),
+            _ => {}
+        }
+        keys.push(key);
+    }
+    keys
+}
+
+/// Strings near `bs58`: its own prefixes or suffixes (`from_end`) of every length, the
+/// same with one character changed, and random ones
+fn near(rng: &mut StdRng, bs58: &str, from_end: bool) -> Vec<String> {
+    let mut strings = vec![];
+    for len in 1..=bs58.len() {
+        let own = match from_end {
+            true => &bs58[bs58.len() - len..],
+            false => &bs58[..len],
+        };
+        strings.push(own.to_string());
+        let mut changed = own.as_bytes().to_vec();
+        let i = rng.gen_range(0..len);
+        changed[i] = BASE58[rng.gen_range(0..58)];
+        strings.push(String::from_utf8(changed).unwrap());
+    }
+    for _ in 0..4 {
+        let len = rng.gen_range(1..=6);
+        strings.push(
+            (0..len)
+                .map(|_| BASE58[rng.gen_range(0..58)] as char)
+                .collect(),
+        );
+    }
+    strings
+}
+
+#[test]
+fn raw_prefix_matches_agrees_with_bs58() {
+    let mut rng = StdRng::seed_from_u64(0x9e1f);
+    for key in keys(&mut rng, 2000) {
+        let bs58 = bs58::encode(key).into_string();
+        for prefix in near(&mut rng, &bs58, false) {
+            assert_eq!(
+                raw_prefix_matches(&prefix, &key),
+                bs58.starts_with(&prefix),
+                "{prefix} for {bs58}"
+            );
+        }
+    }
+}
+
+#[test]
+fn raw_prefix_matches_leading_ones() {
+    // Each leading '1' is a zero byte, and no 32 byte key has more than 32 of them
+    let mut rng = StdRng::seed_from_u64(0x111);
+    for zeros in 0..=32 {
+        let mut key: [u8; 32] = rng.gen();
+        key[..zeros].fill(0);
+        if zeros < 32 {
+            key[zeros] = key[zeros].max(1);
+        }
+        let bs58 = bs58::encode(key).into_string();
+        for ones in 1..=33 {
+            let prefix = "1".repeat(ones);
+            assert_eq!(
+                raw_prefix_matches(&prefix, &key),
+                bs58.starts_with(&prefix),
+                "{prefix} for {bs58}"
+            );
+        }
+        assert!(!raw_prefix_matches(&format!("{}2", "1".repeat(32)), &key));
+    }
+}
+
+#[test]
+fn raw_suffix_may_match_agrees_with_bs58() {
+    let mut rng = StdRng::seed_from_u64(0x5f1c);
+    for key in keys(&mut rng, 2000) {
+        let bs58 = bs58::encode(key).into_string();
+        for suffix in near(&mut rng, &bs58, true) {
+            let ends_with = bs58.ends_with(&suffix);
+            let may_match = raw_suffix_may_match(&suffix, &key);
+            // Only the last 10 characters are checked, so longer suffixes can pass
+            // without ending the address, but never fail when they do
+            if suffix.len() <= 10 {
+                assert_eq!(may_match, ends_with, "{suffix} for {bs58}");
+            } else if ends_with {
+                assert!(may_match, "{suffix} for {bs58}");
+            }
+        }
+    }
+}
+
+#[test]
+fn unmatchable_targets_name_the_character() {
+    let error = |flags: &[&str]| {
+        let owner = "11111111111111111111111111111111";
+        let args = [
+            "grind",
+            "--owner",
+            owner,
+            "--no-file",
+            "--no-manifest",
+            "--quiet",
+        ];
+        Grinder::new(GrindArgs::parse_from(args.iter().chain(flags)))
+            .run(|_| {})
+            .unwrap_err()
+    };
+    assert!(error(&["--target", "ab0"]).contains("'0' is not in the base58 alphabet"));
+    assert!(error(&["--suffix", "aé"]).contains("'é' is not in the base58 alphabet"));
+    for (target, c) in [("é", 'é'), ("abé", 'é'), ("a_b", '_')] {
+        assert!(
+            error(&["--target", target, "--fuzzy"])
+                .contains(&format!("'{c}' has no look-alike in the base58 alphabet")),
+            "{target}"
+        );
+    }
+}
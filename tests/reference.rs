@@ -0,0 +1,157 @@
+use std::sync::mpsc;
+
+use clap::Parser;
+use pda_grinder::{
+    args::GrindArgs,
+    reference::{derive_canonical, grind_range},
+    sinks::Channel,
+    Grinder,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use solana_pubkey::Pubkey;
+
+const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+#[test]
+fn derive_canonical_agrees_with_find_program_address() {
+    let mut rng = StdRng::seed_from_u64(0x7ef);
+    for _ in 0..2000 {
+        let owner = Pubkey::new_from_array(rng.gen());
+        let seed: Vec<u8> = (0..rng.gen_range(0..=32)).map(|_| rng.gen()).collect();
+        assert_eq!(
+            derive_canonical(&owner, &seed),
+            Some(Pubkey::find_program_address(&[&seed], &owner))
+        );
+    }
+}
+
+/// Grinds `seeds` with the real grind, returning its matches in order
+fn grind(
+    owner: &Pubkey,
+    start: u64,
+    end: u64,
+    target: &str,
+    flags: &[&str],
+) -> Vec<(u64, u8, Pubkey)> {
+    let owner = owner.to_string();
+    let (start, end) = (start.to_string(), end.to_string());
+    let mut args = vec![
+        "grind",
+        "--owner",
+        &owner,
+        "--target",
+        target,
+        "--seed-start",
+        &start,
+        "--seed-end",
+        &end,
+        "--no-file",
+        "--no-manifest",
+        "--quiet",
+    ];
+    args.extend(flags);
+    let (sender, matches) = mpsc::channel();
+    Grinder::new(GrindArgs::parse_from(args))
+        .sink(Channel(sender))
+        .run(|_| {})
+        .unwrap();
+    let mut matches: Vec<_> = matches
+        .into_iter()
+        .map(|found| (found.seed.parse().unwrap(), found.bump, found.key))
+        .collect();
+    matches.sort_unstable();
+    matches
+}
+
+/// Grinds ranges of seeds for a random owner and target with `flags`, checking the grind
+/// finds what the reference does at a bump of at least `min_bump`
+fn grinds_like_the_reference(rng_seed: u64, flags: &[&str], min_bump: u8) {
+    let mut rng = StdRng::seed_from_u64(rng_seed);
+    let owner = Pubkey::new_from_array(rng.gen());
+    let target = (BASE58[rng.gen_range(0..BASE58.len())] as char).to_string();
+    let start = rng.gen_range(0..u64::MAX - 10_000);
+    for (start, end) in [(start, start + 10_000), (u64::MAX - 2_000, u64::MAX)] {
+        let expected = grind_range(&owner, start..end, &target, min_bump);
+        // About one seed in 100 or so gives a one character target
+        assert!(!expected.is_empty());
+        assert_eq!(
+            grind(&owner, start, end, &target, flags),
+            expected,
+            "{flags:?} from {start} to {end} for {owner} with target {target}"
+        );
+    }
+}
+
+#[test]
+fn grind_finds_what_the_reference_does() {
+    grinds_like_the_reference(0x9a1d, &[], 255);
+}
+
+#[test]
+fn grind_with_lookahead_finds_what_the_reference_does() {
+    grinds_like_the_reference(0x9a1e, &["--lookahead", "3"], 253);
+    grinds_like_the_reference(0x9a1f, &["--lookahead", "16", "--threads", "1"], 240);
+}
+
+#[test]
+fn grind_curve_first_finds_what_the_reference_does() {
+    grinds_like_the_reference(
+        0x9a20,
+        &["--lookahead", "3", "--check-order", "curve-first"],
+        253,
+    );
+}
+
+#[test]
+fn grind_any_bump_finds_what_the_reference_does() {
+    grinds_like_the_reference(0x9a21, &["--bump-mode", "any"], 1);
+}
+
+#[test]
+fn charset_shard_grinds_every_seed_once() {
+    let mut rng = StdRng::seed_from_u64(0x9a22);
+    let owner = Pubkey::new_from_array(rng.gen());
+    // Most keys start with one of the first few characters
+    let target = (BASE58[rng.gen_range(0..4)] as char).to_string();
+    let mut expected: Vec<_> = (0..10_000)
+        .filter_map(|n| {
+            let seed = format!("{n:04}");
+            let (key, bump) = derive_canonical(&owner, seed.as_bytes())?;
+            (bump == 255 && key.to_string().starts_with(&target))
+                .then(|| (format!("{seed:?}"), key))
+        })
+        .collect();
+    expected.sort_unstable();
+    assert!(expected.len() > 100);
+
+    let owner = owner.to_string();
+    let args = [
+        "grind",
+        "--owner",
+        &owner,
+        "--target",
+        &target,
+        "--seed-charset",
+        "numeric",
+        "--seed-len",
+        "4",
+        "--shard",
+        "0/1",
+        "--threads",
+        "16",
+        "--no-file",
+        "--no-manifest",
+        "--quiet",
+    ];
+    let (sender, matches) = mpsc::channel();
+    Grinder::new(GrindArgs::parse_from(args))
+        .sink(Channel(sender))
+        .run(|_| {})
+        .unwrap();
+    let mut matches: Vec<_> = matches
+        .into_iter()
+        .map(|found| (found.seed, found.key))
+        .collect();
+    matches.sort_unstable();
+    assert_eq!(matches, expected);
+}
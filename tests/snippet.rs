@@ -0,0 +1,339 @@
+use pda_grinder::{
+    args::Emit,
+    seeds::{Endianness, SeedComponent, SeedKind, SeedTemplate, SeedType},
+    snippet::snippet,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use solana_pubkey::Pubkey;
+
+/// The seeds a template gives with `seed_bytes` as its ground seed
+fn seeds(template: &SeedTemplate, seed_bytes: &[u8]) -> Vec<Vec<u8>> {
+    template
+        .0
+        .iter()
+        .map(|component| match component {
+            SeedComponent::Fixed(bytes) => bytes.clone(),
+            SeedComponent::Pubkey(key) => key.to_bytes().to_vec(),
+            SeedComponent::Ground(_) => seed_bytes.to_vec(),
+        })
+        .collect()
+}
+
+/// `list` split at the commas outside of brackets, parentheses and strings
+fn split_list(list: &str) -> Vec<&str> {
+    let (mut parts, mut depth, mut in_string, mut escaped, mut start) =
+        (vec![], 0, false, false, 0);
+    for (i, c) in list.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '[' | '(' if !in_string => depth += 1,
+            ']' | ')' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                parts.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(list[start..].trim());
+    parts
+}
+
+/// The bytes of a `0x12, 0x34` list (or a decimal one, like the bump's)
+fn byte_list(list: &str) -> Vec<u8> {
+    split_list(list)
+        .iter()
+        .map(|b| match b.strip_prefix("0x") {
+            Some(hex) => u8::from_str_radix(hex, 16).unwrap(),
+            None => b.parse().unwrap(),
+        })
+        .collect()
+}
+
+/// `bytes` of `width` as the `endianness` (`le` or `be`) bytes of `value`
+fn int_bytes(value: u128, width: usize, endianness: &str) -> Vec<u8> {
+    match endianness {
+        "le" => value.to_le_bytes()[..width].to_vec(),
+        "be" => value.to_be_bytes()[16 - width..].to_vec(),
+        _ => panic!("unknown endianness {endianness}"),
+    }
+}
+
+/// The bytes an emitted Rust seed expression evaluates to
+fn rust_seed(seed: &str) -> Vec<u8> {
+    if let Some(string) = seed.strip_prefix("b\"").and_then(|s| s.strip_suffix('"')) {
+        let mut bytes = vec![];
+        let mut chars = string.bytes();
+        while let Some(b) = chars.next() {
+            bytes.push(match b {
+                b'\\' => chars.next().unwrap(),
+                b => b,
+            });
+        }
+        bytes
+    } else if let Some(list) = seed.strip_prefix("&[").and_then(|s| s.strip_suffix(']')) {
+        byte_list(list)
+    } else if let Some(key) = seed
+        .strip_prefix("pubkey!(\"")
+        .and_then(|s| s.strip_suffix("\").as_ref()"))
+    {
+        key.parse::<Pubkey>().unwrap().to_bytes().to_vec()
+    } else {
+        let int = seed.strip_prefix('&').unwrap();
+        let (int, endianness) = int
+            .strip_suffix(".to_le_bytes()")
+            .map(|int| (int, "le"))
+            .or_else(|| int.strip_suffix(".to_be_bytes()").map(|int| (int, "be")))
+            .unwrap_or_else(|| panic!("unexpected seed {seed}"));
+        let split = int.find('u').unwrap();
+        let width = int[split + 1..].parse::<usize>().unwrap() / 8;
+        int_bytes(int[..split].parse().unwrap(), width, endianness)
+    }
+}
+
+/// The seeds and bump of both lines of a `--emit rust` snippet, checking they agree
+fn parse_rust(snippet: &str, owner: &Pubkey) -> (Vec<Vec<u8>>, u8) {
+    let (constraint, call) = snippet.split_once('\n').unwrap();
+    let (seeds, bump) = constraint
+        .strip_prefix("seeds = [")
+        .and_then(|s| s.split_once("], bump = "))
+        .unwrap();
+    let seeds: Vec<Vec<u8>> = split_list(seeds).into_iter().map(rust_seed).collect();
+    let bump: u8 = bump.parse().unwrap();
+
+    let call_seeds = call
+        .strip_prefix("Pubkey::create_program_address(&[")
+        .and_then(|s| s.strip_suffix(&format!("], &pubkey!(\"{owner}\"))")))
+        .unwrap();
+    let mut call_seeds: Vec<Vec<u8>> = split_list(call_seeds).into_iter().map(rust_seed).collect();
+    assert_eq!(call_seeds.pop(), Some(vec![bump]));
+    assert_eq!(call_seeds, seeds);
+    (seeds, bump)
+}
+
+/// The bytes an emitted TypeScript seed expression evaluates to
+fn ts_seed(seed: &str) -> Vec<u8> {
+    if let Some(list) = seed
+        .strip_prefix("Buffer.from([")
+        .and_then(|s| s.strip_suffix("])"))
+    {
+        byte_list(list)
+    } else if let Some(string) = seed
+        .strip_prefix("Buffer.from(")
+        .and_then(|s| s.strip_suffix(')'))
+    {
+        serde_json::from_str::<String>(string).unwrap().into_bytes()
+    } else if let Some(key) = seed
+        .strip_prefix("new PublicKey(\"")
+        .and_then(|s| s.strip_suffix("\").toBuffer()"))
+    {
+        key.parse::<Pubkey>().unwrap().to_bytes().to_vec()
+    } else {
+        let (value, rest) = seed
+            .strip_prefix("new BN(")
+            .and_then(|s| s.split_once(").toArrayLike(Buffer, \""))
+            .unwrap_or_else(|| panic!("unexpected seed {seed}"));
+        let (endianness, width) = rest.strip_suffix(')').unwrap().split_once("\", ").unwrap();
+        // Strings past 2^53, which a JavaScript number can't hold exactly
+        let value: u128 = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            Some(value) => {
+                assert!(value.parse::<u128>().unwrap() >= 1 << 53, "{seed}");
+                value.parse().unwrap()
+            }
+            None => {
+                assert!(value.parse::<u128>().unwrap() < 1 << 53, "{seed}");
+                value.parse().unwrap()
+            }
+        };
+        int_bytes(value, width.parse().unwrap(), endianness)
+    }
+}
+
+/// The seeds and bump of a `--emit ts` snippet
+fn parse_ts(snippet: &str, owner: &Pubkey) -> (Vec<Vec<u8>>, u8) {
+    let (call, bump) = snippet.split_once("; // bump ").unwrap();
+    let seeds = call
+        .strip_prefix("const [pda, bump] = PublicKey.findProgramAddressSync([")
+        .and_then(|s| s.strip_suffix(&format!("], new PublicKey(\"{owner}\"))")))
+        .unwrap();
+    let seeds = split_list(seeds).into_iter().map(ts_seed).collect();
+    (seeds, bump.parse().unwrap())
+}
+
+/// Templates and ground seeds covering every kind of seed a snippet writes
+fn cases(rng: &mut StdRng) -> Vec<(SeedTemplate, SeedKind, Vec<u8>)> {
+    let key = Pubkey::new_from_array(rng.gen());
+    let mut cases = vec![];
+    for (seed_type, endianness) in [
+        (SeedType::U16, Endianness::Le),
+        (SeedType::U32, Endianness::Be),
+        (SeedType::U64, Endianness::Le),
+        (SeedType::U64, Endianness::Be),
+        (SeedType::U128, Endianness::Le),
+        (SeedType::U128, Endianness::Be),
+    ] {
+        let template = SeedTemplate(vec![
+            SeedComponent::Fixed(b"vault".to_vec()),
+            SeedComponent::Pubkey(key),
+            SeedComponent::Ground(Some(seed_type)),
+        ]);
+        for _ in 0..20 {
+            // Small seeds too, which `--emit ts` writes as numbers rather than strings
+            let mut seed_bytes: Vec<u8> = (0..seed_type.width()).map(|_| rng.gen()).collect();
+            let zeroed = rng.gen_range(0..=seed_type.width());
+            match endianness {
+                Endianness::Le => seed_bytes[seed_type.width() - zeroed..].fill(0),
+                Endianness::Be => seed_bytes[..zeroed].fill(0),
+            }
+            cases.push((template.clone(), SeedKind::Int(endianness), seed_bytes));
+        }
+    }
+    // A key seed next to fixed seeds that aren't printable, or need escaping
+    let template = SeedTemplate(vec![
+        SeedComponent::Fixed(vec![0, 0xff, b'\n']),
+        SeedComponent::Ground(None),
+        SeedComponent::Fixed(b"a \"quoted\" \\ 'seed'".to_vec()),
+    ]);
+    cases.push((template, SeedKind::Key, rng.gen::<[u8; 32]>().to_vec()));
+    let template = SeedTemplate(vec![SeedComponent::Ground(None)]);
+    cases.push((
+        template.clone(),
+        SeedKind::Bytes,
+        vec![0x00, 0x7f, 0x80, 0xff, 0x20],
+    ));
+    cases.push((template.clone(), SeedKind::Bytes, b"printable".to_vec()));
+    cases.push((template, SeedKind::Str, b"Ab3~!".to_vec()));
+    cases
+}
+
+#[test]
+fn rust_snippets_give_back_the_seeds() {
+    let mut rng = StdRng::seed_from_u64(0x5a1d);
+    let owner = Pubkey::new_from_array(rng.gen());
+    for (template, seed_kind, seed_bytes) in cases(&mut rng) {
+        let bump = rng.gen();
+        let snippet = snippet(Emit::Rust, &template, seed_kind, &seed_bytes, bump, &owner);
+        assert_eq!(
+            parse_rust(&snippet, &owner),
+            (seeds(&template, &seed_bytes), bump),
+            "{snippet}"
+        );
+    }
+}
+
+#[test]
+fn rust_snippet_writes_seeds_as_they_were_ground() {
+    let key: Pubkey = "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi"
+        .parse()
+        .unwrap();
+    let owner: Pubkey = "11111111111111111111111111111111".parse().unwrap();
+    let template = SeedTemplate(vec![
+        SeedComponent::Fixed(b"vault".to_vec()),
+        SeedComponent::Pubkey(key),
+        SeedComponent::Ground(Some(SeedType::U64)),
+    ]);
+    assert_eq!(
+        snippet(
+            Emit::Rust,
+            &template,
+            SeedKind::Int(Endianness::Le),
+            &1234_u64.to_le_bytes(),
+            254,
+            &owner
+        ),
+        "seeds = [b\"vault\", pubkey!(\"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi\").as_ref(), \
+         &1234u64.to_le_bytes()], bump = 254\n\
+         Pubkey::create_program_address(&[b\"vault\", \
+         pubkey!(\"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi\").as_ref(), \
+         &1234u64.to_le_bytes(), &[254]], &pubkey!(\"11111111111111111111111111111111\"))"
+    );
+    let template = SeedTemplate(vec![SeedComponent::Ground(Some(SeedType::U32))]);
+    assert_eq!(
+        snippet(
+            Emit::Rust,
+            &template,
+            SeedKind::Int(Endianness::Be),
+            &7_u32.to_be_bytes(),
+            255,
+            &owner
+        )
+        .lines()
+        .next(),
+        Some("seeds = [&7u32.to_be_bytes()], bump = 255")
+    );
+    let template = SeedTemplate(vec![SeedComponent::Ground(None)]);
+    assert_eq!(
+        snippet(
+            Emit::Rust,
+            &template,
+            SeedKind::Bytes,
+            &[0, 0xab],
+            255,
+            &owner
+        )
+        .lines()
+        .next(),
+        Some("seeds = [&[0x00, 0xab]], bump = 255")
+    );
+}
+
+#[test]
+fn ts_snippets_give_back_the_seeds() {
+    let mut rng = StdRng::seed_from_u64(0x75);
+    let owner = Pubkey::new_from_array(rng.gen());
+    for (template, seed_kind, seed_bytes) in cases(&mut rng) {
+        let bump = rng.gen();
+        let snippet = snippet(Emit::Ts, &template, seed_kind, &seed_bytes, bump, &owner);
+        assert_eq!(
+            parse_ts(&snippet, &owner),
+            (seeds(&template, &seed_bytes), bump),
+            "{snippet}"
+        );
+    }
+}
+
+#[test]
+fn ts_snippet_writes_seeds_as_they_were_ground() {
+    let key: Pubkey = "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi"
+        .parse()
+        .unwrap();
+    let owner: Pubkey = "11111111111111111111111111111111".parse().unwrap();
+    let template = SeedTemplate(vec![
+        SeedComponent::Fixed(b"vault".to_vec()),
+        SeedComponent::Pubkey(key),
+        SeedComponent::Ground(Some(SeedType::U64)),
+    ]);
+    assert_eq!(
+        snippet(
+            Emit::Ts,
+            &template,
+            SeedKind::Int(Endianness::Le),
+            &1234_u64.to_le_bytes(),
+            254,
+            &owner
+        ),
+        "const [pda, bump] = PublicKey.findProgramAddressSync([Buffer.from(\"vault\"), \
+         new PublicKey(\"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi\").toBuffer(), \
+         new BN(1234).toArrayLike(Buffer, \"le\", 8)], \
+         new PublicKey(\"11111111111111111111111111111111\")); // bump 254"
+    );
+    let template = SeedTemplate(vec![
+        SeedComponent::Fixed(vec![0, 0xff]),
+        SeedComponent::Ground(Some(SeedType::U64)),
+    ]);
+    assert_eq!(
+        snippet(
+            Emit::Ts,
+            &template,
+            SeedKind::Int(Endianness::Be),
+            &u64::MAX.to_be_bytes(),
+            255,
+            &owner
+        ),
+        "const [pda, bump] = PublicKey.findProgramAddressSync([Buffer.from([0x00, 0xff]), \
+         new BN(\"18446744073709551615\").toArrayLike(Buffer, \"be\", 8)], \
+         new PublicKey(\"11111111111111111111111111111111\")); // bump 255"
+    );
+}